@@ -0,0 +1,246 @@
+const assert = require('node:assert/strict')
+const { constants: fsConsts } = require('node:fs')
+const { access: checkAccess, open, stat, statfs, truncate: truncateFile } = require('node:fs/promises')
+
+const { PERM_OF_MOUNT } = require('../index.js')
+const { MemFs, err, mountTest, python, run, withMount } = require('./helpers.js')
+
+const { E2BIG, EACCES, ENOENT, EOPNOTSUPP, EPERM } = require('node:os').constants.errno
+
+const macOnly = process.platform !== 'darwin' && 'macOS only'
+const linuxOnly = process.platform !== 'linux' && 'Linux only'
+
+mountTest('gives creation time from getxtimes on macOS', { skip: macOnly }, async () => {
+  const fs = new MemFs()
+  const file = fs.addFile(fs.root.ino, 'f', 'data')
+  const crtime = Date.UTC(2020, 1, 2, 3, 4, 5)
+  file.btime = crtime
+  const getxtimes = async (_e, ino) =>
+    ino === file.ino ? { type: 'Times', field0: { bkuptime: 0, crtime } } : err(ENOENT)
+  await withMount(fs, { overrides: { getxtimes } }, async ({ path }) => {
+    const { birthtimeMs } = await stat(path('f'))
+    assert.equal(birthtimeMs, crtime)
+  })
+})
+
+mountTest('honors flags set with chflags, and refuses clearing of fileFlags', { skip: macOnly }, async () => {
+  // values from sys/stat.h on macOS
+  const UF_IMMUTABLE = 0x2
+  const UF_APPEND = 0x4
+  const fs = new MemFs()
+  const file = fs.addFile(fs.root.ino, 'f', 'data')
+  await withMount(fs, { config: { fileFlags: UF_APPEND } }, async ({ path }) => {
+    await run('chflags', ['uchg', path('f')])
+    const script = [
+      'import os, stat, sys',
+      'flags = os.stat(sys.argv[1]).st_flags',
+      'print(bool(flags & stat.UF_IMMUTABLE), bool(flags & stat.UF_APPEND))',
+      'for probe in (lambda: open(sys.argv[1], "a"), lambda: os.chflags(sys.argv[1], stat.UF_IMMUTABLE)):',
+      '  try:',
+      '    probe()',
+      '    print(0)',
+      '  except OSError as exc:',
+      '    print(exc.errno)',
+    ]
+    const out = await python(script.join('\n'), path('f'))
+    assert.deepEqual(out.trim().split(/\s+/), ['True', 'True', String(EPERM), String(EPERM)])
+  })
+  assert.equal(file.flags & UF_IMMUTABLE, UF_IMMUTABLE)
+})
+
+mountTest('reports mtime as missing creation time with btimeFromMtime', { skip: macOnly }, async () => {
+  const fs = new MemFs()
+  const file = fs.addFile(fs.root.ino, 'f', 'data')
+  file.btime = 0
+  file.mtime = Date.UTC(2021, 5, 6, 7, 8, 9)
+  await withMount(fs, { config: { btimeFromMtime: true } }, async ({ path }) => {
+    const { birthtimeMs, mtimeMs } = await stat(path('f'))
+    assert.equal(birthtimeMs, mtimeMs)
+  })
+})
+
+mountTest('routes size-only setattr to truncate, which advances mtime', async () => {
+  const fs = new MemFs()
+  const file = fs.addFile(fs.root.ino, 'f', 'some data')
+  file.mtime = Date.now() - 60_000
+  const truncate = async (_e, ino, _fh, size) => {
+    const node = fs.nodes.get(ino)
+    node.data = Buffer.concat([node.data, Buffer.alloc(size)]).subarray(0, size)
+    node.mtime = node.ctime = Date.now()
+    return { type: 'Attr', field0: fs.attr(node) }
+  }
+  await withMount(fs, { overrides: { truncate } }, async ({ path }) => {
+    const before = await stat(path('f'))
+    await truncateFile(path('f'), 4)
+    const after = await stat(path('f'))
+    assert.equal(after.size, 4)
+    assert.ok(after.mtimeMs > before.mtimeMs)
+    assert.equal(fs.calls.setattr, undefined)
+  })
+})
+
+mountTest('reports statistics of statfs callback', async () => {
+  const fs = new MemFs()
+  const stats = { blocks: 1000, bfree: 500, bavail: 400, files: 100, ffree: 50, bsize: 4096 }
+  const statfsCb = async () => ({ type: 'Stats', field0: stats })
+  await withMount(fs, { overrides: { statfs: statfsCb } }, async ({ root }) => {
+    // fsid isn't in FUSE's statfs reply, and kernel sets it, hence, only counts are checked
+    const reported = await statfs(root)
+    assert.equal(reported.blocks, stats.blocks)
+    assert.equal(reported.bfree, stats.bfree)
+    assert.equal(reported.bavail, stats.bavail)
+    assert.equal(reported.files, stats.files)
+    assert.equal(reported.ffree, stats.ffree)
+  })
+})
+
+mountTest('applies mount-wide modes to PERM_OF_MOUNT, and masks to all', async () => {
+  const fs = new MemFs()
+  fs.addFile(fs.root.ino, 'default').perm = PERM_OF_MOUNT
+  fs.addFile(fs.root.ino, 'own').perm = 0o777
+  fs.addDir(fs.root.ino, 'dir').perm = PERM_OF_MOUNT
+  const config = { fileMode: 0o644, dirMode: 0o750, fmask: 0o002 }
+  await withMount(fs, { config }, async ({ path }) => {
+    assert.equal((await stat(path('default'))).mode & 0o7777, 0o644)
+    assert.equal((await stat(path('own'))).mode & 0o7777, 0o775)
+    assert.equal((await stat(path('dir'))).mode & 0o7777, 0o750)
+  })
+})
+
+mountTest('coalesces concurrent getattr requests into one js call', async () => {
+  const fs = new MemFs()
+  fs.addFile(fs.root.ino, 'f', 'data')
+  // with zero timeouts, kernel asks getattr for every fstat
+  const config = { entryTimeoutMs: 0, attrTimeoutMs: 0, getattrCoalesceMs: 5000 }
+  await withMount(fs, { config }, async ({ path }) => {
+    const file = await open(path('f'))
+    try {
+      await file.stat()
+      const callsBefore = fs.calls.getattr
+      const stats = await Promise.all(Array.from({ length: 20 }, () => file.stat()))
+      assert.ok(stats.every(({ size }) => size === 4))
+      assert.equal(fs.calls.getattr, callsBefore)
+    } finally {
+      await file.close()
+    }
+  })
+})
+
+mountTest('answers stat after write with attributes, that write has returned', async () => {
+  const fs = new MemFs()
+  fs.addFile(fs.root.ino, 'f', 'data')
+  const { write } = fs.callbacks()
+  const writeWithAttr = async (...args) => {
+    const { field0: written } = await write(...args)
+    return { type: 'WrittenWithAttr', field0: { written, attr: fs.attr(fs.nodes.get(args[1])) } }
+  }
+  const config = { entryTimeoutMs: 0, attrTimeoutMs: 0, getattrCoalesceMs: 5000 }
+  await withMount(fs, { overrides: { write: writeWithAttr }, config }, async ({ path }) => {
+    const file = await open(path('f'), 'r+')
+    try {
+      await file.write(' and more', 4)
+      const callsBefore = fs.calls.getattr
+      assert.equal((await file.stat()).size, 13)
+      assert.equal(fs.calls.getattr, callsBefore)
+    } finally {
+      await file.close()
+    }
+  })
+})
+
+mountTest('gives handle-specific attributes to operations on open file, and committed ones to stat', async () => {
+  const fs = new MemFs()
+  const file = fs.addFile(fs.root.ino, 'f', 'committed')
+  const pending = new Map()
+  const write = async (_e, _ino, fh, { offset, data, len }) => {
+    const buf = pending.get(fh) ?? Buffer.from(file.data)
+    const end = offset + len
+    const grown = end > buf.length ? Buffer.concat([buf, Buffer.alloc(end - buf.length)]) : buf
+    data.copy(grown, offset, 0, len)
+    pending.set(fh, grown)
+    return { type: 'Written', field0: len }
+  }
+  const getattr = async (_e, ino, fh) => {
+    const attrs = fs.attr(fs.nodes.get(ino))
+    if (fh != null && pending.has(fh)) {
+      attrs.size = pending.get(fh).length
+    }
+    return { type: 'Attr', field0: attrs }
+  }
+  const config = { entryTimeoutMs: 0, attrTimeoutMs: 0 }
+  await withMount(fs, { overrides: { write, getattr }, config }, async ({ path }) => {
+    // Linux kernel gives handle to getattr, when lseek(2) looks for end of file, but not in fstat(2)
+    const script = [
+      'import os, sys',
+      'fd = os.open(sys.argv[1], os.O_RDWR)',
+      "os.pwrite(fd, b' and more', 9)",
+      'print(os.lseek(fd, 0, os.SEEK_END), os.stat(sys.argv[1]).st_size)',
+      'os.close(fd)',
+    ]
+    const out = await python(script.join('\n'), path('f'))
+    assert.deepEqual(out.trim().split(' ').map(Number), [18, 9])
+  })
+})
+
+mountTest('reports times before epoch, and replaces invalid ones with epoch', async () => {
+  const fs = new MemFs()
+  fs.addFile(fs.root.ino, 'old').mtime = -86_400_000
+  fs.addFile(fs.root.ino, 'invalid').mtime = Number.MAX_SAFE_INTEGER
+  await withMount(fs, async ({ path }) => {
+    assert.equal((await stat(path('old'))).mtimeMs, -86_400_000)
+    assert.equal((await stat(path('invalid'))).mtimeMs, 0)
+  })
+})
+
+mountTest('answers xattr requests without js callbacks, when noXattr is set', { skip: linuxOnly }, async () => {
+  const fs = new MemFs()
+  fs.addFile(fs.root.ino, 'f').xattrs.set('user.k', Buffer.from('v'))
+  await withMount(fs, { config: { noXattr: true } }, async ({ path }) => {
+    const script = [
+      'import os, sys',
+      'for probe in (lambda: os.getxattr(sys.argv[1], "user.k"), lambda: os.listxattr(sys.argv[1])):',
+      '  try:',
+      '    probe()',
+      '  except OSError as exc:',
+      '    print(exc.errno)',
+    ]
+    const out = await python(script.join('\n'), path('f'))
+    assert.deepEqual(out.trim().split('\n').map(Number), [EOPNOTSUPP, EOPNOTSUPP])
+  })
+  assert.equal(fs.calls.getxattr, undefined)
+  assert.equal(fs.calls.listxattr, undefined)
+})
+
+mountTest('grants and denies access with access callback', async () => {
+  const fs = new MemFs()
+  fs.addFile(fs.root.ino, 'open', 'data')
+  const secret = fs.addFile(fs.root.ino, 'secret', 'data')
+  const access = async (_e, ino) =>
+    ino === secret.ino ? { type: 'Denied', field0: { code: EACCES } } : { type: 'Granted' }
+  await withMount(fs, { overrides: { access } }, async ({ path }) => {
+    await checkAccess(path('open'), fsConsts.R_OK)
+    await assert.rejects(checkAccess(path('secret'), fsConsts.R_OK), { code: 'EACCES' })
+  })
+})
+
+mountTest('reads large xattr values, and refuses ones over kernel limit', { skip: linuxOnly }, async () => {
+  const fs = new MemFs()
+  const file = fs.addFile(fs.root.ino, 'f')
+  // there is no setxattr, hence, values are stored on js side
+  const large = Buffer.alloc(60 * 1024, 'x')
+  file.xattrs.set('user.large', large)
+  file.xattrs.set('user.huge', Buffer.alloc(64 * 1024 + 1, 'x'))
+  await withMount(fs, async ({ path }) => {
+    const script = [
+      'import os, sys',
+      'value = os.getxattr(sys.argv[1], "user.large")',
+      'print(len(value), set(value) == {ord("x")})',
+      'try:',
+      '  os.getxattr(sys.argv[1], "user.huge")',
+      'except OSError as exc:',
+      '  print(exc.errno)',
+    ]
+    const out = await python(script.join('\n'), path('f'))
+    assert.deepEqual(out.trim().split(/\s+/), [String(large.length), 'True', String(E2BIG)])
+  })
+})
@@ -0,0 +1,171 @@
+const assert = require('node:assert/strict')
+const { readdir, stat } = require('node:fs/promises')
+
+const { MemFs, mountTest, python, withMount } = require('./helpers.js')
+
+const linuxOnly = process.platform !== 'linux' && 'Linux only'
+
+// type of directory entries in readdir(3)
+const DT_DIR = 4
+
+mountTest('lists directory without opendir callbacks, when no-opendir support is granted', async () => {
+  const fs = new MemFs()
+  fs.addFile(fs.root.ino, 'a')
+  fs.addDir(fs.root.ino, 'b')
+  const config = { capabilities: { noOpendirSupport: true } }
+  await withMount(fs, { config }, async ({ root }) => {
+    assert.deepEqual((await readdir(root)).sort(), ['a', 'b'])
+    assert.deepEqual((await readdir(root)).sort(), ['a', 'b'])
+  })
+  assert.deepEqual(fs.initInfo.deniedCaps, [])
+  assert.equal(fs.calls.opendir, undefined)
+  assert.equal(fs.calls.releasedir, undefined)
+  assert.ok(fs.calls.readdir > 0)
+})
+
+mountTest('reads open directory again from cached listing', async () => {
+  const fs = new MemFs()
+  fs.addFile(fs.root.ino, 'a')
+  fs.addFile(fs.root.ino, 'b')
+  const config = { cacheDirListings: true }
+  await withMount(fs, { config }, async ({ root }) => {
+    // listdir of descriptor rewinds it at the end, and both passes read with the same handle
+    const script = [
+      'import os, sys',
+      'fd = os.open(sys.argv[1], os.O_RDONLY | os.O_DIRECTORY)',
+      'print(sorted(os.listdir(fd)) == sorted(os.listdir(fd)) == ["a", "b"])',
+      'os.close(fd)',
+    ]
+    assert.equal((await python(script.join('\n'), root)).trim(), 'True')
+  })
+  assert.equal(fs.calls.opendir, 1)
+  // first pass reads a page and an empty one, that ends the listing
+  assert.equal(fs.calls.readdir, 2)
+})
+
+mountTest('lists all entries of backend, that paginates with cursors', async () => {
+  const fs = new MemFs()
+  // long names don't fit into one kernel's buffer, and pages are cut on Rust side
+  const names = Array.from({ length: 300 }, (_, i) => `${String(i).padStart(4, '0')}-${'n'.repeat(100)}`)
+  for (const name of names) {
+    fs.addFile(fs.root.ino, name)
+  }
+  const children = [...fs.root.children.entries()]
+  const tokens = []
+  const pageSize = 50
+  const listPage = async (_e, _ino, _fh, offset, _reqId, token) => {
+    tokens.push(token)
+    // cursor is opaque to the crate, and offset is used only, when listing starts over
+    const start = token == null ? offset : Number(token.slice('cursor-'.length))
+    const lst = children.slice(start, start + pageSize).map(([name, ino], i) => ({
+      ino,
+      offset: start + i + 1,
+      kind: fs.nodes.get(ino).kind,
+      name,
+    }))
+    const next = start + lst.length
+    return next < children.length
+      ? { type: 'Partial', field0: { lst, token: `cursor-${next}` } }
+      : { type: 'Lst', field0: lst }
+  }
+  await withMount(fs, { overrides: { readdir: listPage } }, async ({ root }) => {
+    const listed = await readdir(root)
+    assert.deepEqual(listed.sort(), [...names].sort())
+  })
+  assert.ok(tokens.filter((token) => token != null).length >= children.length / pageSize - 1)
+})
+
+mountTest('lists entries, that have not fit into kernel buffer, in the next readdir', async () => {
+  const fs = new MemFs()
+  // with names this long, kernel's buffer is full in the middle of a listing
+  const names = Array.from({ length: 200 }, (_, i) => `${String(i).padStart(4, '0')}-${'n'.repeat(200)}`)
+  for (const name of names) {
+    fs.addFile(fs.root.ino, name)
+  }
+  await withMount(fs, async ({ root }) => {
+    const listed = await readdir(root)
+    assert.deepEqual(listed.sort(), names)
+  })
+  assert.ok(fs.calls.readdir > 2)
+})
+
+mountTest('lists directory, opened with cacheDir, from kernel cache', async () => {
+  const fs = new MemFs()
+  fs.addFile(fs.root.ino, 'a')
+  const opendir = async () => ({ type: 'Params', field0: { fh: fs.nextFh++, cacheDir: true, keepCache: true } })
+  await withMount(fs, { overrides: { opendir } }, async ({ root }) => {
+    assert.deepEqual(await readdir(root), ['a'])
+    const readdirCalls = fs.calls.readdir
+    assert.deepEqual(await readdir(root), ['a'])
+    assert.equal(fs.calls.readdir, readdirCalls)
+  })
+})
+
+mountTest('lists ".." with inode of parent directory', { skip: linuxOnly }, async () => {
+  const fs = new MemFs()
+  const parent = fs.addDir(fs.root.ino, 'parent')
+  const dir = fs.addDir(parent.ino, 'dir')
+  await withMount(fs, async ({ path }) => {
+    // inodes of "." and ".." come from readdir's entries, as seen with readdir(3), unlike ones from stat(2)
+    const script = [
+      'import ctypes, sys',
+      'class Dirent(ctypes.Structure):',
+      '  _fields_ = [("ino", ctypes.c_uint64), ("off", ctypes.c_int64), ("reclen", ctypes.c_ushort),',
+      '    ("type", ctypes.c_ubyte), ("name", ctypes.c_char * 256)]',
+      'libc = ctypes.CDLL(None)',
+      'libc.opendir.restype = ctypes.c_void_p',
+      'libc.readdir.argtypes = libc.closedir.argtypes = [ctypes.c_void_p]',
+      'libc.readdir.restype = ctypes.POINTER(Dirent)',
+      'd = libc.opendir(sys.argv[1].encode())',
+      'while entry := libc.readdir(d):',
+      '  print(entry.contents.name.decode(), entry.contents.ino, entry.contents.type)',
+      'libc.closedir(d)',
+    ]
+    const out = await python(script.join('\n'), path('parent', 'dir'))
+    const entries = Object.fromEntries(out.trim().split('\n').map((line) => [line.split(' ')[0], line]))
+    assert.equal(entries['.'], `. ${dir.ino} ${DT_DIR}`)
+    assert.equal(entries['..'], `.. ${parent.ino} ${DT_DIR}`)
+  })
+})
+
+mountTest('gives mkdir mode with umask of process already applied', async () => {
+  const fs = new MemFs()
+  const { mkdir } = fs.callbacks()
+  const seen = []
+  const recordingMkdir = async (e, parent, name, mode, umask) => {
+    seen.push([mode & 0o7777, umask])
+    return mkdir(e, parent, name, mode, umask)
+  }
+  await withMount(fs, { overrides: { mkdir: recordingMkdir } }, async ({ path }) => {
+    await python('import os, sys\nos.umask(0o027)\nos.mkdir(sys.argv[1], 0o777)', path('dir'))
+  })
+  assert.deepEqual(seen, [[0o750, 0o027]])
+})
+
+mountTest('gives attributes of readdirplus entries to kernel, so that stat needs no lookup', async () => {
+  const fs = new MemFs()
+  const names = ['a', 'b', 'c']
+  for (const name of names) {
+    fs.addFile(fs.root.ino, name, name.repeat(3))
+  }
+  // entries without attributes can't be given, and readdir should be used instead for such backends
+  const readdirplus = async (_e, ino, _fh, offset) => {
+    const entries = [...fs.nodes.get(ino).children.values()].map((childIno, i) => {
+      const node = fs.nodes.get(childIno)
+      return { offset: i + 1, name: node.name, attr: fs.attr(node) }
+    })
+    return { type: 'Lst', field0: entries.slice(offset) }
+  }
+  const config = { entryTimeoutMs: 60_000, attrTimeoutMs: 60_000 }
+  await withMount(fs, { overrides: { readdirplus }, config }, async ({ path, root }) => {
+    assert.deepEqual((await readdir(root)).sort(), names)
+    const lookupsBefore = fs.calls.lookup ?? 0
+    const getattrsBefore = fs.calls.getattr ?? 0
+    for (const name of names) {
+      assert.equal((await stat(path(name))).size, 3)
+    }
+    assert.equal(fs.calls.lookup ?? 0, lookupsBefore)
+    assert.equal(fs.calls.getattr ?? 0, getattrsBefore)
+  })
+  assert.equal(fs.calls.readdir, undefined)
+})
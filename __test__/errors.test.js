@@ -0,0 +1,80 @@
+const assert = require('node:assert/strict')
+const { constants } = require('node:os')
+const { stat } = require('node:fs/promises')
+const { test } = require('node:test')
+
+const { Errno } = require('../index.js')
+const { ROOT_INO, MemFs, err, mount, mountTest, run, sleep, waitFor, withMount } = require('./helpers.js')
+
+const { ENOENT, ESTALE } = constants.errno
+
+test('Errno constants match ones of platform', () => {
+  const names = Object.keys(Errno)
+  assert.ok(names.includes('ENOENT'))
+  for (const name of names) {
+    // ENOATTR isn't in node's constants on Linux, where it is ENODATA
+    const expected = constants.errno[name] ?? (name === 'ENOATTR' ? constants.errno.ENODATA : undefined)
+    assert.equal(Errno[name], expected, name)
+  }
+})
+
+mountTest('reports messages of failed operations to onError', async () => {
+  const fs = new MemFs()
+  const reports = []
+  const lookup = async () => ({ type: 'Err', field0: { code: Errno.EIO, message: 'backend is down' } })
+  const config = { onError: (_e, report) => reports.push(report) }
+  await withMount(fs, { overrides: { lookup }, config }, async ({ path }) => {
+    await assert.rejects(stat(path('f')), { code: 'EIO' })
+    await waitFor(() => reports.length > 0)
+  })
+  assert.equal(reports[0].kind, 'fs-error')
+  assert.match(reports[0].message, /backend is down/)
+})
+
+mountTest('translates error codes of js side with errnoMap', async () => {
+  const fs = new MemFs()
+  const lookup = async () => err(ESTALE)
+  const config = { errnoMap: [[ESTALE, ENOENT]] }
+  await withMount(fs, { overrides: { lookup }, config }, async ({ path }) => {
+    await assert.rejects(stat(path('f')), { code: 'ENOENT' })
+  })
+})
+
+mountTest('reports operations, slower than warning threshold, while they are in progress', async () => {
+  const fs = new MemFs()
+  fs.addFile(fs.root.ino, 'f', 'data')
+  const reports = []
+  let reportsBeforeReply
+  const { lookup } = fs.callbacks()
+  const slowLookup = async (...args) => {
+    await sleep(300)
+    reportsBeforeReply = reports.length
+    return lookup(...args)
+  }
+  const config = { slowOpWarningMs: 100, onError: (_e, report) => reports.push(report) }
+  await withMount(fs, { overrides: { lookup: slowLookup }, config }, async ({ path }) => {
+    assert.equal((await stat(path('f'))).size, 4)
+  })
+  // warning comes, while operation still waits for reply
+  assert.equal(reportsBeforeReply, 1)
+  assert.equal(reports.length, 1)
+  assert.equal(reports[0].kind, 'slow-op')
+  assert.equal(reports[0].message, `lookup of inode ${ROOT_INO} is still in progress after 100 ms`)
+})
+
+// umount(8) can unmount only as root, while others need fusermount
+const rootOnLinux = (process.platform !== 'linux' || process.getuid() !== 0) && 'needs root on Linux'
+
+mountTest('reports ending of session without unmount call to onError', { skip: rootOnLinux }, async () => {
+  const fs = new MemFs()
+  const reports = []
+  const mnt = await mount(fs, { config: { onError: (_e, report) => reports.push(report) } })
+  try {
+    await run('umount', [mnt.root])
+    await waitFor(() => reports.length > 0)
+  } finally {
+    await mnt.unmount()
+  }
+  assert.equal(reports[0].kind, 'session-end')
+  assert.match(reports[0].message, /without unmount call/)
+})
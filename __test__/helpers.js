@@ -0,0 +1,410 @@
+// Helpers of tests, that mount an in-memory filesystem.
+//
+// Mounting needs FUSE to be available to the current user, and tests, that mount, are skipped otherwise.
+// JS callbacks run on the same event loop, as tests, hence, tests must touch mounted files only with async calls,
+// like ones of `fs/promises`, or from child processes.
+
+const { accessSync, constants: fsConsts } = require('node:fs')
+const { mkdtemp } = require('node:fs/promises')
+const { tmpdir } = require('node:os')
+const { join } = require('node:path')
+const { execFile } = require('node:child_process')
+const { test } = require('node:test')
+
+const { FSMounter, InodeKind, cleanupMountPoint } = require('../index.js')
+
+const ROOT_INO = 1
+
+const { ENOENT, ENODATA, ENOTEMPTY, ENOTDIR } = require('node:os').constants.errno
+
+function isFuseAvailable() {
+  const device = process.platform === 'darwin' ? '/dev/macfuse0' : '/dev/fuse'
+  try {
+    accessSync(device, fsConsts.R_OK | fsConsts.W_OK)
+    return true
+  } catch {
+    return false
+  }
+}
+
+const fuseAvailable = isFuseAvailable()
+
+/**
+ * Declares a test, that mounts, skipping it, when FUSE isn't available.
+ */
+function mountTest(name, opts, fn) {
+  if (typeof opts === 'function') {
+    fn = opts
+    opts = {}
+  }
+  const skip = opts.skip || (!fuseAvailable && 'FUSE is not available')
+  test(name, { ...opts, skip }, fn)
+}
+
+const err = (code) => ({ type: 'Err', field0: { code } })
+
+/**
+ * In-memory tree of files and directories, which callbacks count their calls in `calls`.
+ */
+class MemFs {
+  constructor() {
+    this.nodes = new Map()
+    this.nextIno = ROOT_INO
+    this.nextFh = 1
+    this.calls = {}
+    this.now = Date.now()
+    // root has no parent
+    this.root = this.addNode(0, '', InodeKind.Directory)
+  }
+
+  addNode(parent, name, kind, content) {
+    const node = {
+      ino: this.nextIno++,
+      parent,
+      name,
+      kind,
+      perm: kind === InodeKind.Directory ? 0o755 : 0o644,
+      data: Buffer.from(content ?? ''),
+      children: new Map(),
+      xattrs: new Map(),
+      mtime: this.now,
+      ctime: this.now,
+      btime: this.now,
+      flags: 0,
+      hasXattrs: undefined,
+    }
+    this.nodes.set(node.ino, node)
+    this.nodes.get(parent)?.children.set(name, node.ino)
+    return node
+  }
+
+  addFile(parent, name, content = '') {
+    return this.addNode(parent, name, InodeKind.File, content)
+  }
+
+  addDir(parent, name) {
+    return this.addNode(parent, name, InodeKind.Directory)
+  }
+
+  child(parent, name) {
+    const ino = this.nodes.get(parent)?.children.get(name)
+    return ino === undefined ? undefined : this.nodes.get(ino)
+  }
+
+  attr(node) {
+    return {
+      ino: node.ino,
+      size: node.data.length,
+      mtime: node.mtime,
+      ctime: node.ctime,
+      btime: node.btime,
+      kind: node.kind,
+      perm: node.perm,
+      uid: process.getuid(),
+      gid: process.getgid(),
+      rdev: 0,
+      flags: node.flags,
+      hasXattrs: node.hasXattrs,
+    }
+  }
+
+  attrOrErr(ino) {
+    const node = this.nodes.get(ino)
+    return node ? { type: 'Attr', field0: this.attr(node) } : err(ENOENT)
+  }
+
+  count(op) {
+    this.calls[op] = (this.calls[op] ?? 0) + 1
+  }
+
+  newEntry(node) {
+    return { type: 'Entry', field0: { attr: this.attr(node), generation: 0 } }
+  }
+
+  writeData(node, offset, data) {
+    const end = offset + data.length
+    if (end > node.data.length) {
+      node.data = Buffer.concat([node.data, Buffer.alloc(end - node.data.length)])
+    }
+    data.copy(node.data, offset)
+    node.mtime = node.ctime = Date.now()
+  }
+
+  /**
+   * Callbacks of this filesystem, keyed by names of `makeAndMount` arguments and of `MountConfig` fields.
+   */
+  callbacks() {
+    const counted = (op, fn) => (...args) => {
+      this.count(op)
+      return fn(...args)
+    }
+    return {
+      init: counted('init', (_e, _ino, info) => {
+        this.initInfo = info
+      }),
+      destroy: counted('destroy', async () => {}),
+      lookup: counted('lookup', async (_e, parent, name) => {
+        const node = this.child(parent, name)
+        return node ? { type: 'Attr', field0: this.attr(node) } : err(ENOENT)
+      }),
+      forget: counted('forget', () => {}),
+      getattr: counted('getattr', async (_e, ino) => this.attrOrErr(ino)),
+      setattr: counted('setattr', async (_e, ino, _fh, changes) => {
+        const node = this.nodes.get(ino)
+        if (!node) {
+          return err(ENOENT)
+        }
+        if (changes.mode !== undefined) {
+          node.perm = changes.mode & 0o7777
+        }
+        if (changes.size !== undefined) {
+          node.data = Buffer.concat([node.data, Buffer.alloc(Math.max(0, changes.size - node.data.length))])
+          node.data = node.data.subarray(0, changes.size)
+          node.mtime = Date.now()
+        }
+        if (changes.mtime !== undefined) {
+          node.mtime = changes.mtime
+        }
+        if (changes.flags !== undefined) {
+          node.flags = changes.flags
+        }
+        node.ctime = Date.now()
+        return { type: 'Attr', field0: this.attr(node) }
+      }),
+      mknod: counted('mknod', async (_e, parent, name, mode) => {
+        const node = this.addFile(parent, name)
+        node.perm = mode & 0o7777
+        return this.newEntry(node)
+      }),
+      mkdir: counted('mkdir', async (_e, parent, name, mode) => {
+        const node = this.addDir(parent, name)
+        node.perm = mode & 0o7777
+        return this.newEntry(node)
+      }),
+      unlink: counted('unlink', async (_e, parent, name) => {
+        const node = this.child(parent, name)
+        if (!node) {
+          return ENOENT
+        }
+        this.nodes.get(parent).children.delete(name)
+        return 0
+      }),
+      rmdir: counted('rmdir', async (_e, parent, name) => {
+        const node = this.child(parent, name)
+        if (!node) {
+          return ENOENT
+        }
+        if (node.kind !== InodeKind.Directory) {
+          return ENOTDIR
+        }
+        if (node.children.size > 0) {
+          return ENOTEMPTY
+        }
+        this.nodes.get(parent).children.delete(name)
+        return 0
+      }),
+      rename: counted('rename', async (_e, parent, name, newParent, newName) => {
+        const node = this.child(parent, name)
+        if (!node) {
+          return ENOENT
+        }
+        this.nodes.get(parent).children.delete(name)
+        this.nodes.get(newParent).children.set(newName, node.ino)
+        node.parent = newParent
+        node.name = newName
+        return 0
+      }),
+      open: counted('open', async () => ({ type: 'Params', field0: { fh: this.nextFh++ } })),
+      read: counted('read', async (_e, ino, _fh, { offset, size }) => {
+        const node = this.nodes.get(ino)
+        return node ? { type: 'Ok', field0: node.data.subarray(offset, offset + size) } : err(ENOENT)
+      }),
+      flush: counted('flush', async () => 0),
+      release: counted('release', async () => {}),
+      fsync: counted('fsync', async () => 0),
+      opendir: counted('opendir', async () => ({ type: 'Params', field0: { fh: this.nextFh++ } })),
+      readdir: counted('readdir', async (_e, ino, _fh, offset) => {
+        const dir = this.nodes.get(ino)
+        if (!dir) {
+          return err(ENOENT)
+        }
+        const entries = [...dir.children.entries()].map(([name, childIno], i) => ({
+          ino: childIno,
+          offset: i + 1,
+          kind: this.nodes.get(childIno).kind,
+          name,
+        }))
+        return { type: 'Lst', field0: entries.slice(offset) }
+      }),
+      releasedir: counted('releasedir', async () => 0),
+      fsyncdir: counted('fsyncdir', async () => 0),
+      getxattr: counted('getxattr', async (_e, ino, name, size) => {
+        const value = this.nodes.get(ino)?.xattrs.get(name)
+        if (!value) {
+          return err(ENODATA)
+        }
+        return size === 0 ? { type: 'Size', field0: value.length } : { type: 'Data', field0: value }
+      }),
+      listxattr: counted('listxattr', async (_e, ino, size) => {
+        const names = [...(this.nodes.get(ino)?.xattrs.keys() ?? [])]
+        const list = Buffer.from(names.map((name) => `${name}\0`).join(''))
+        return size === 0 ? { type: 'Size', field0: list.length } : { type: 'Data', field0: list }
+      }),
+      removexattr: counted('removexattr', async (_e, ino, name) => {
+        return this.nodes.get(ino)?.xattrs.delete(name) ? 0 : ENODATA
+      }),
+      access: counted('access', async () => ({ type: 'Granted' })),
+      write: counted('write', async (_e, ino, _fh, { offset, data, len }) => {
+        const node = this.nodes.get(ino)
+        if (!node) {
+          return err(ENOENT)
+        }
+        this.writeData(node, offset, data.subarray(0, len))
+        return { type: 'Written', field0: len }
+      }),
+    }
+  }
+}
+
+const MOUNT_ARGS = [
+  'init',
+  'destroy',
+  'lookup',
+  'forget',
+  'getattr',
+  'setattr',
+  'mknod',
+  'mkdir',
+  'unlink',
+  'rmdir',
+  'rename',
+  'open',
+  'read',
+  'flush',
+  'release',
+  'fsync',
+  'opendir',
+  'readdir',
+  'releasedir',
+  'fsyncdir',
+  'getxattr',
+  'listxattr',
+  'removexattr',
+  'access',
+]
+
+/**
+ * Mounts given filesystem into a new temporary directory. Callbacks in `overrides` replace ones of the filesystem,
+ * and config's callbacks, which are not in arguments of `makeAndMount`, are taken from both.
+ */
+async function mount(memFs, { overrides = {}, config = {}, fsName = 'napi-fuser-test' } = {}) {
+  const cbs = { ...memFs.callbacks(), ...overrides }
+  const root = await mkdtemp(join(tmpdir(), 'napi-fuser-test-'))
+  const args = MOUNT_ARGS.map((name) => cbs[name])
+  const cfgCbs = Object.fromEntries(
+    Object.entries(overrides).filter(([name]) => !MOUNT_ARGS.includes(name)),
+  )
+  let mounter
+  try {
+    mounter = FSMounter.makeAndMount(root, fsName, ...args, { write: cbs.write, ...cfgCbs, ...config })
+  } catch (exc) {
+    cleanupMountPoint(root)
+    throw exc
+  }
+  return {
+    root,
+    mounter,
+    path: (...names) => join(root, ...names),
+    async unmount() {
+      // kernel sends release after close has returned, hence, files may still look open for a moment
+      try {
+        await waitFor(() => {
+          try {
+            mounter.unmount()
+            return true
+          } catch (exc) {
+            if (exc.message.startsWith('Filesystem is busy')) {
+              return false
+            }
+            throw exc
+          }
+        })
+      } catch (exc) {
+        mounter.unmount(true)
+        throw exc
+      }
+      // unmount completes in the background
+      await waitFor(() => {
+        try {
+          cleanupMountPoint(root)
+          return true
+        } catch {
+          return false
+        }
+      })
+    },
+  }
+}
+
+/**
+ * Mounts, runs given function with the mount, and unmounts.
+ */
+async function withMount(memFs, opts, fn) {
+  if (typeof opts === 'function') {
+    fn = opts
+    opts = {}
+  }
+  const mnt = await mount(memFs, opts)
+  try {
+    return await fn(mnt)
+  } finally {
+    await mnt.unmount()
+  }
+}
+
+async function waitFor(check, timeoutMs = 5000) {
+  const deadline = Date.now() + timeoutMs
+  while (!(await check())) {
+    if (Date.now() > deadline) {
+      throw new Error('Timed out waiting for condition')
+    }
+    await sleep(20)
+  }
+}
+
+const sleep = (ms) => new Promise((resolve) => setTimeout(resolve, ms))
+
+/**
+ * Runs a command, that touches mounted files, in a child process, resolving with its stdout.
+ */
+function run(cmd, args) {
+  return new Promise((resolve, reject) => {
+    execFile(cmd, args, (exc, stdout, stderr) => {
+      if (exc) {
+        exc.stderr = stderr
+        reject(exc)
+      } else {
+        resolve(stdout)
+      }
+    })
+  })
+}
+
+/**
+ * Runs a python3 snippet with given arguments in `sys.argv`, for syscalls, that node doesn't expose.
+ */
+const python = (code, ...args) => run('python3', ['-c', code, ...args.map(String)])
+
+module.exports = {
+  ROOT_INO,
+  MemFs,
+  mountTest,
+  mount,
+  withMount,
+  waitFor,
+  sleep,
+  run,
+  python,
+  err,
+  fuseAvailable,
+}
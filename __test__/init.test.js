@@ -0,0 +1,16 @@
+const assert = require('node:assert/strict')
+
+const { MemFs, mountTest, waitFor, withMount } = require('./helpers.js')
+
+mountTest('reports denied capabilities in init', async () => {
+  const fs = new MemFs()
+  const capabilities = { writebackCache: true, cacheSymlinks: true, noOpenSupport: true, noOpendirSupport: true }
+  await withMount(fs, { config: { capabilities } }, async () => {
+    await waitFor(() => fs.initInfo)
+  })
+  // kernel decides, what to deny, and only requested capabilities can be denied
+  assert.ok(Array.isArray(fs.initInfo.deniedCaps))
+  for (const cap of fs.initInfo.deniedCaps) {
+    assert.ok(cap in capabilities, cap)
+  }
+})
@@ -0,0 +1,108 @@
+const assert = require('node:assert/strict')
+const { open, readFile, stat, writeFile } = require('node:fs/promises')
+
+const { InodeKind } = require('../index.js')
+const { MemFs, mount, mountTest, sleep, waitFor, withMount } = require('./helpers.js')
+
+const { ESTALE } = require('node:os').constants.errno
+
+// dropping of kernel's caches makes it forget inodes, and this needs root on Linux
+const rootOnLinux = (process.platform !== 'linux' || process.getuid() !== 0) && 'needs root on Linux'
+
+const forgetAllInodes = () => writeFile('/proc/sys/vm/drop_caches', '2')
+
+mountTest('calls release_inode once, after all lookups are forgotten', { skip: rootOnLinux }, async () => {
+  const fs = new MemFs()
+  const file = fs.addFile(fs.root.ino, 'f', 'data')
+  const released = []
+  const releaseInode = (_e, ino) => released.push(ino)
+  const config = { releaseInode, entryTimeoutMs: 0, attrTimeoutMs: 0 }
+  await withMount(fs, { config }, async ({ path }) => {
+    for (let i = 0; i < 3; i += 1) {
+      await stat(path('f'))
+    }
+    assert.ok(fs.calls.lookup >= 3)
+    assert.deepEqual(released, [])
+    await forgetAllInodes()
+    await waitFor(() => released.length > 0)
+    await sleep(100)
+  })
+  assert.deepEqual(released, [file.ino])
+})
+
+const linuxOnly = process.platform !== 'linux' && 'Linux only'
+
+mountTest('refuses unforced unmount with open file, and detaches with forced one', { skip: linuxOnly }, async () => {
+  const fs = new MemFs()
+  fs.addFile(fs.root.ino, 'f', 'data')
+  const mnt = await mount(fs)
+  const handle = await open(mnt.path('f'))
+  try {
+    assert.throws(() => mnt.mounter.unmount(), /busy/)
+    assert.equal(await handle.readFile('utf8'), 'data')
+    mnt.mounter.unmount(true)
+    const isMounted = async () => (await readFile('/proc/self/mountinfo', 'utf8')).includes(mnt.root)
+    await waitFor(async () => !(await isMounted()))
+  } finally {
+    await handle.close()
+  }
+  await mnt.unmount()
+})
+
+mountTest('replies ESTALE, when kind of live inode changes, with kind checks on', async () => {
+  const fs = new MemFs()
+  const node = fs.addFile(fs.root.ino, 'x')
+  const config = { checkKindChanges: true, entryTimeoutMs: 0, attrTimeoutMs: 0 }
+  await withMount(fs, { config }, async ({ path }) => {
+    // open file keeps inode known to kernel
+    const handle = await open(path('x'))
+    try {
+      node.kind = InodeKind.Directory
+      // libuv has no name for ESTALE
+      await assert.rejects(stat(path('x')), { errno: -ESTALE })
+    } finally {
+      await handle.close()
+    }
+  })
+})
+
+mountTest('refetches all files under a directory after invalidateSubtree', async () => {
+  const fs = new MemFs()
+  const dir = fs.addDir(fs.root.ino, 'dir')
+  const sub = fs.addDir(dir.ino, 'sub')
+  const files = [fs.addFile(dir.ino, 'a', 'a'), fs.addFile(dir.ino, 'b', 'b'), fs.addFile(sub.ino, 'c', 'c')]
+  const paths = ['dir/a', 'dir/b', 'dir/sub/c']
+  const config = { entryTimeoutMs: 60_000, attrTimeoutMs: 60_000 }
+  await withMount(fs, { config }, async ({ mounter, path }) => {
+    const sizes = () => Promise.all(paths.map(async (p) => (await stat(path(p))).size))
+    assert.deepEqual(await sizes(), [1, 1, 1])
+    for (const file of files) {
+      file.data = Buffer.from('changed')
+    }
+    // kernel keeps cached attributes
+    assert.deepEqual(await sizes(), [1, 1, 1])
+    mounter.invalidateSubtree(dir.ino)
+    assert.deepEqual(await sizes(), [7, 7, 7])
+  })
+})
+
+mountTest('caches entries and failed lookups for configured times', async () => {
+  const fs = new MemFs()
+  fs.addFile(fs.root.ino, 'f', 'data')
+  const lookups = []
+  const { lookup } = fs.callbacks()
+  const countedLookup = async (e, parent, name) => {
+    lookups.push(name)
+    return lookup(e, parent, name)
+  }
+  const config = { entryTimeoutMs: 10_000, attrTimeoutMs: 10_000, negativeTimeoutMs: 10_000 }
+  await withMount(fs, { overrides: { lookup: countedLookup }, config }, async ({ path }) => {
+    await stat(path('f'))
+    await assert.rejects(stat(path('missing')), { code: 'ENOENT' })
+    // wait past default timeout of 1 second
+    await sleep(1500)
+    await stat(path('f'))
+    await assert.rejects(stat(path('missing')), { code: 'ENOENT' })
+  })
+  assert.deepEqual(lookups, ['f', 'missing'])
+})
@@ -0,0 +1,367 @@
+const assert = require('node:assert/strict')
+const { open, readFile, stat, writeFile } = require('node:fs/promises')
+
+const { MemFs, err, mountTest, python, sleep, waitFor, withMount } = require('./helpers.js')
+
+const { EINTR, EINVAL, ENOSPC, ENXIO } = require('node:os').constants.errno
+
+mountTest('creates files with mknod and open, when there is no create callback', async () => {
+  const fs = new MemFs()
+  await withMount(fs, async ({ path }) => {
+    await writeFile(path('new'), 'created')
+    assert.equal(await readFile(path('new'), 'utf8'), 'created')
+  })
+  assert.equal(fs.calls.mknod, 1)
+  assert.ok(fs.calls.open >= 1)
+  assert.equal(fs.child(fs.root.ino, 'new').data.toString(), 'created')
+})
+
+mountTest('reads without open callbacks, when no-open support is granted', async () => {
+  const fs = new MemFs()
+  fs.addFile(fs.root.ino, 'f', 'stateless')
+  const readFhs = []
+  const read = async (_e, ino, fh, { offset, size }) => {
+    readFhs.push(fh)
+    return { type: 'Ok', field0: fs.nodes.get(ino).data.subarray(offset, offset + size) }
+  }
+  const config = { capabilities: { noOpenSupport: true } }
+  await withMount(fs, { overrides: { read }, config }, async ({ path }) => {
+    assert.equal(await readFile(path('f'), 'utf8'), 'stateless')
+    assert.equal(await readFile(path('f'), 'utf8'), 'stateless')
+  })
+  assert.deepEqual(fs.initInfo.deniedCaps, [])
+  assert.equal(fs.calls.open, undefined)
+  assert.ok(readFhs.length > 0)
+  assert.ok(readFhs.every((fh) => fh === 0))
+})
+
+mountTest('reads and writes zero bytes without js callbacks', async () => {
+  const fs = new MemFs()
+  fs.addFile(fs.root.ino, 'f', 'data')
+  await withMount(fs, async ({ path }) => {
+    // Linux itself doesn't pass these to FUSE, while other kernels get replies from Rust side
+    const handle = await open(path('f'), 'r+')
+    try {
+      assert.equal((await handle.read(Buffer.alloc(4), 0, 0, 0)).bytesRead, 0)
+      assert.equal((await handle.write(Buffer.alloc(0), 0, 0, 0)).bytesWritten, 0)
+    } finally {
+      await handle.close()
+    }
+  })
+  assert.equal(fs.calls.read, undefined)
+  assert.equal(fs.calls.write, undefined)
+})
+
+mountTest('sends cached writes to js before fsync with writeback cache', async () => {
+  const fs = new MemFs()
+  const file = fs.addFile(fs.root.ino, 'f')
+  let dataAtFsync
+  const fsync = async () => {
+    dataAtFsync = file.data.toString()
+    return 0
+  }
+  const config = { capabilities: { writebackCache: true } }
+  await withMount(fs, { overrides: { fsync }, config }, async ({ path }) => {
+    const handle = await open(path('f'), 'r+')
+    try {
+      for (const chunk of ['first ', 'second ', 'third']) {
+        await handle.write(chunk)
+      }
+      await handle.sync()
+    } finally {
+      await handle.close()
+    }
+  })
+  assert.deepEqual(fs.initInfo.deniedCaps, [])
+  assert.equal(dataAtFsync, 'first second third')
+})
+
+for (const atomicOTrunc of [true, false]) {
+  mountTest(`truncates ${atomicOTrunc ? 'in open with' : 'with setattr without'} atomic O_TRUNC`, async () => {
+    const fs = new MemFs()
+    const file = fs.addFile(fs.root.ino, 'f', 'old content')
+    const truncateFlags = []
+    const open = async (_e, _ino, _flags, truncate) => {
+      truncateFlags.push(truncate)
+      if (truncate) {
+        file.data = Buffer.alloc(0)
+      }
+      return { type: 'Params', field0: { fh: fs.nextFh++ } }
+    }
+    const config = { capabilities: { atomicOTrunc } }
+    await withMount(fs, { overrides: { open }, config }, async ({ path }) => {
+      await writeFile(path('f'), 'new')
+      assert.equal(await readFile(path('f'), 'utf8'), 'new')
+    })
+    assert.equal(truncateFlags[0], atomicOTrunc)
+    assert.equal(fs.calls.setattr, atomicOTrunc ? undefined : 1)
+  })
+}
+
+mountTest('serves sustained reads from buffers of read pool', async () => {
+  const fs = new MemFs()
+  const content = Buffer.alloc(4 * 1024 * 1024)
+  for (let i = 0; i < content.length; i += 1) {
+    content[i] = i % 251
+  }
+  fs.addFile(fs.root.ino, 'big', content)
+  const slots = []
+  const read = async (_e, ino, _fh, { offset, size, slot }) => {
+    const data = fs.nodes.get(ino).data.subarray(offset, offset + size)
+    slots.push(slot)
+    if (slot == null) {
+      return { type: 'Ok', field0: data }
+    }
+    data.copy(fs.initInfo.readBuffers[slot])
+    return { type: 'InSlot', field0: data.length }
+  }
+  const config = { readBufferPoolSlots: 2 }
+  await withMount(fs, { overrides: { read }, config }, async ({ path }) => {
+    assert.ok((await readFile(path('big'))).equals(content))
+  })
+  // few preallocated buffers are reused by all reads, instead of a new buffer per read
+  const pooled = slots.filter((slot) => slot != null)
+  assert.ok(slots.length > 2)
+  assert.ok(pooled.length > slots.length / 2)
+  assert.ok(pooled.every((slot) => slot === 0 || slot === 1))
+})
+
+mountTest('fills holes between segments of sparse reads with zeros', async () => {
+  const fs = new MemFs()
+  fs.addFile(fs.root.ino, 'sparse', Buffer.alloc(20))
+  const read = async (_e, _ino, _fh, { offset, size }) => {
+    const len = Math.max(0, Math.min(size, 20 - offset))
+    const segments = [
+      { offset: 2, data: Buffer.from('head') },
+      { offset: 12, data: Buffer.from('tail') },
+    ]
+    return { type: 'Sparse', field0: { len, segments } }
+  }
+  await withMount(fs, { overrides: { read } }, async ({ path }) => {
+    const expected = Buffer.alloc(20)
+    expected.write('head', 2)
+    expected.write('tail', 12)
+    assert.deepEqual(await readFile(path('sparse')), expected)
+  })
+})
+
+mountTest('retries reads, rejected as retryable', async () => {
+  const fs = new MemFs()
+  fs.addFile(fs.root.ino, 'f', 'after retries')
+  const { read } = fs.callbacks()
+  let attempts = 0
+  const flakyRead = async (...args) => {
+    attempts += 1
+    if (attempts <= 2) {
+      throw new Error('retryable: store replied 503')
+    }
+    return read(...args)
+  }
+  const config = { jsRetries: 2, jsRetryBackoffMs: 10 }
+  await withMount(fs, { overrides: { read: flakyRead }, config }, async ({ path }) => {
+    assert.equal(await readFile(path('f'), 'utf8'), 'after retries')
+  })
+  assert.equal(attempts, 3)
+})
+
+mountTest('aborts reads and writes, which js side has not replied to in time', async () => {
+  const fs = new MemFs()
+  fs.addFile(fs.root.ino, 'f', 'data')
+  const started = { read: [], write: [] }
+  const pending = new Map()
+  const hanging = (op) => (_e, _ino, _fh, { reqId }) => {
+    started[op].push(reqId)
+    return new Promise((resolve) => pending.set(reqId, resolve))
+  }
+  const aborted = []
+  const abort = (_e, reqId) => {
+    aborted.push(reqId)
+    pending.get(reqId)?.(err(EINTR))
+  }
+  // fuser doesn't pass kernel's interrupts, hence, killing of reading process doesn't abort, and timeout is used
+  const config = { jsReplyTimeoutMs: 300 }
+  const overrides = { read: hanging('read'), write: hanging('write'), abort }
+  await withMount(fs, { overrides, config }, async ({ path }) => {
+    await assert.rejects(readFile(path('f')), { code: 'EIO' })
+    await assert.rejects(writeFile(path('f'), 'new data'), { code: 'EIO' })
+    await waitFor(() => pending.size === aborted.length)
+  })
+  // kernel may retry a failed read
+  assert.ok(started.read.length > 0)
+  assert.equal(started.write.length, 1)
+  assert.deepEqual(aborted, [...started.read, ...started.write])
+})
+
+mountTest('keeps no more operations in js, than maxInFlight', async () => {
+  const fs = new MemFs()
+  for (let i = 0; i < 8; i += 1) {
+    fs.addFile(fs.root.ino, `f${i}`, `data of ${i}`)
+  }
+  let inFlight = 0
+  let maxSeen = 0
+  const read = async (_e, ino, _fh, { offset, size }) => {
+    inFlight += 1
+    maxSeen = Math.max(maxSeen, inFlight)
+    await sleep(20)
+    inFlight -= 1
+    return { type: 'Ok', field0: fs.nodes.get(ino).data.subarray(offset, offset + size) }
+  }
+  await withMount(fs, { overrides: { read }, config: { maxInFlight: 2 } }, async ({ path }) => {
+    const contents = await Promise.all(Array.from({ length: 8 }, (_, i) => readFile(path(`f${i}`), 'utf8')))
+    assert.deepEqual(
+      contents,
+      Array.from({ length: 8 }, (_, i) => `data of ${i}`),
+    )
+  })
+  assert.ok(maxSeen >= 1 && maxSeen <= 2, `${maxSeen} reads were in flight`)
+})
+
+mountTest('has data of the last write at the end of destroy', async () => {
+  const fs = new MemFs()
+  const file = fs.addFile(fs.root.ino, 'f')
+  let flushedInDestroy
+  const destroy = async () => {
+    // like flushing of a write-back buffer to backend
+    await sleep(100)
+    flushedInDestroy = file.data.toString()
+  }
+  await withMount(fs, { overrides: { destroy } }, async ({ path }) => {
+    await writeFile(path('f'), 'first')
+    await writeFile(path('f'), 'last write')
+  })
+  await waitFor(() => flushedInDestroy !== undefined)
+  assert.equal(flushedInDestroy, 'last write')
+})
+
+mountTest('prepares handle once, before its first write', async () => {
+  const fs = new MemFs()
+  fs.addFile(fs.root.ino, 'f')
+  const prepared = []
+  const prepareWrite = async (_e, ino, fh) => {
+    prepared.push([ino, fh])
+    return 0
+  }
+  await withMount(fs, { overrides: { prepareWrite } }, async ({ path }) => {
+    const handle = await open(path('f'), 'r+')
+    try {
+      for (const chunk of ['one ', 'two ', 'three']) {
+        await handle.write(chunk)
+      }
+    } finally {
+      await handle.close()
+    }
+  })
+  assert.equal(prepared.length, 1)
+  assert.equal(fs.child(fs.root.ino, 'f').data.toString(), 'one two three')
+})
+
+mountTest('fails write, when its preparation fails', async () => {
+  const fs = new MemFs()
+  fs.addFile(fs.root.ino, 'f')
+  const prepareWrite = async () => ENOSPC
+  await withMount(fs, { overrides: { prepareWrite } }, async ({ path }) => {
+    await assert.rejects(writeFile(path('f'), 'data'), { code: 'ENOSPC' })
+  })
+  assert.equal(fs.calls.write, undefined)
+})
+
+for (const directIo of [true, false]) {
+  const source = directIo ? 'every time from js' : 'again from page cache'
+  mountTest(`reads ${source} with directIo ${directIo}`, async () => {
+    const fs = new MemFs()
+    fs.addFile(fs.root.ino, 'f', 'live data')
+    const openWithParams = async () => ({ type: 'Params', field0: { fh: fs.nextFh++, directIo, keepCache: true } })
+    await withMount(fs, { overrides: { open: openWithParams } }, async ({ path }) => {
+      const handle = await open(path('f'))
+      try {
+        for (let i = 0; i < 2; i += 1) {
+          const { bytesRead, buffer } = await handle.read(Buffer.alloc(9), 0, 9, 0)
+          assert.equal(buffer.subarray(0, bytesRead).toString(), 'live data')
+        }
+      } finally {
+        await handle.close()
+      }
+    })
+    assert.equal(fs.calls.read, directIo ? 2 : 1)
+  })
+}
+
+mountTest('serves reads of stored data from kernel cache', async () => {
+  const fs = new MemFs()
+  const file = fs.addFile(fs.root.ino, 'f', 'js data')
+  const openKeepingCache = async () => ({ type: 'Params', field0: { fh: fs.nextFh++, keepCache: true } })
+  await withMount(fs, { overrides: { open: openKeepingCache } }, async ({ mounter, path }) => {
+    // kernel keeps data only of inodes, it knows
+    await stat(path('f'))
+    mounter.storeData(file.ino, 0, Buffer.from('stored!'))
+    assert.equal(await readFile(path('f'), 'utf8'), 'stored!')
+  })
+  assert.equal(fs.calls.read, undefined)
+})
+
+const linuxOnly = process.platform !== 'linux' && 'Linux only'
+
+for (const enforceAppend of [true, false]) {
+  const where = enforceAppend ? 'at its end' : 'at size, known to kernel'
+  mountTest(`appends to file, grown on js side, ${where}, with enforceAppend ${enforceAppend}`, async () => {
+    const fs = new MemFs()
+    const file = fs.addFile(fs.root.ino, 'f', 'data')
+    const config = { attrTimeoutMs: 60_000, entryTimeoutMs: 60_000, enforceAppend }
+    await withMount(fs, { config }, async ({ path }) => {
+      await stat(path('f'))
+      // kernel keeps size of 4, while file grows on js side
+      file.data = Buffer.from('data-longer')
+      const script = [
+        'import os, sys',
+        'fd = os.open(sys.argv[1], os.O_WRONLY | os.O_APPEND)',
+        'os.lseek(fd, 0, os.SEEK_SET)',
+        "os.write(fd, b'!')",
+        'os.close(fd)',
+      ]
+      await python(script.join('\n'), path('f'))
+    })
+    assert.equal(file.data.toString(), enforceAppend ? 'data-longer!' : 'data!longer')
+  })
+}
+
+// whence values of lseek(2) on Linux
+const SEEK_DATA = 3
+const SEEK_HOLE = 4
+
+mountTest('finds holes and data of sparse file with lseek callback', { skip: linuxOnly }, async () => {
+  const fs = new MemFs()
+  const file = fs.addFile(fs.root.ino, 'sparse')
+  file.data = Buffer.concat([Buffer.alloc(4096, 1), Buffer.alloc(4096), Buffer.alloc(4096, 2)])
+  const dataRanges = [
+    [0, 4096],
+    [8192, 12288],
+  ]
+  const lseek = async (_e, _ino, _fh, offset, whence) => {
+    if (offset >= file.data.length) {
+      return err(ENXIO)
+    }
+    if (whence === SEEK_DATA) {
+      const range = dataRanges.find(([, end]) => offset < end)
+      return range ? { type: 'Offset', field0: Math.max(offset, range[0]) } : err(ENXIO)
+    }
+    if (whence === SEEK_HOLE) {
+      const range = dataRanges.find(([start, end]) => start <= offset && offset < end)
+      return { type: 'Offset', field0: range ? range[1] : offset }
+    }
+    return err(EINVAL)
+  }
+  await withMount(fs, { overrides: { lseek } }, async ({ path }) => {
+    const script = [
+      'import os, sys',
+      'fd = os.open(sys.argv[1], os.O_RDONLY)',
+      'print(os.lseek(fd, 0, os.SEEK_HOLE), os.lseek(fd, 4096, os.SEEK_DATA), os.lseek(fd, 8192, os.SEEK_HOLE))',
+      'try:',
+      '  os.lseek(fd, 12288, os.SEEK_DATA)',
+      'except OSError as exc:',
+      '  print(exc.errno)',
+      'os.close(fd)',
+    ]
+    const out = await python(script.join('\n'), path('sparse'))
+    assert.deepEqual(out.trim().split(/\s+/).map(Number), [4096, 8192, 12288, ENXIO])
+  })
+})
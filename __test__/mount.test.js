@@ -0,0 +1,93 @@
+const assert = require('node:assert/strict')
+const { mkdtemp, readFile, rm, symlink } = require('node:fs/promises')
+const { tmpdir } = require('node:os')
+const { join } = require('node:path')
+
+const { AtimeMode, cleanupMountPoint } = require('../index.js')
+
+const { MemFs, mount, mountTest, run, withMount } = require('./helpers.js')
+
+const linuxOnly = process.platform !== 'linux' && 'Linux only'
+
+async function isMounted(mountPoint) {
+  const mountinfo = await readFile('/proc/self/mountinfo', 'utf8')
+  return mountinfo.split('\n').some((line) => line.split(' ')[4] === mountPoint)
+}
+
+/**
+ * Returns mount options of a given mount point from Linux mount table.
+ */
+async function mountOptionsOf(mountPoint) {
+  const mountinfo = await readFile('/proc/self/mountinfo', 'utf8')
+  const line = mountinfo.split('\n').find((line) => line.split(' ')[4] === mountPoint)
+  assert.ok(line, `${mountPoint} is not in mount table`)
+  const [perMount, perSuper] = line.split(' - ')
+  return [...perMount.split(' ')[5].split(','), ...perSuper.split(' ')[2].split(',')]
+}
+
+mountTest('passes custom mount options to mount', { skip: linuxOnly }, async () => {
+  const fs = new MemFs()
+  await withMount(fs, { config: { customMountOptions: ['nodiratime'] } }, async ({ root }) => {
+    assert.ok((await mountOptionsOf(root)).includes('nodiratime'))
+  })
+})
+
+mountTest('mounts with noatime, and reads update no atime', { skip: linuxOnly }, async () => {
+  const fs = new MemFs()
+  fs.addFile(fs.root.ino, 'f', 'data')
+  await withMount(fs, { config: { atime: AtimeMode.Never } }, async ({ path, root }) => {
+    assert.ok((await mountOptionsOf(root)).includes('noatime'))
+    for (let i = 0; i < 3; i += 1) {
+      assert.equal(await readFile(path('f'), 'utf8'), 'data')
+    }
+  })
+  assert.ok(fs.calls.read > 0)
+  assert.equal(fs.calls.setattr, undefined)
+})
+
+mountTest('rejects custom mount options, that conflict with managed ones', async () => {
+  const fs = new MemFs()
+  await assert.rejects(mount(fs, { config: { customMountOptions: ['ro'] } }), /conflicts with managed ones/)
+})
+
+// mounting with a given device is done with mount(2), which needs root
+const rootOnLinux = (process.platform !== 'linux' || process.getuid() !== 0) && 'needs root on Linux'
+
+mountTest('mounts with FUSE device at a custom path', { skip: rootOnLinux }, async () => {
+  const fs = new MemFs()
+  fs.addFile(fs.root.ino, 'f', 'via custom device')
+  const dir = await mkdtemp(join(tmpdir(), 'napi-fuser-dev-'))
+  const device = join(dir, 'fuse')
+  await symlink('/dev/fuse', device)
+  try {
+    await withMount(fs, { config: { fuseDevicePath: device } }, async ({ path }) => {
+      assert.equal(await readFile(path('f'), 'utf8'), 'via custom device')
+    })
+  } finally {
+    await rm(dir, { recursive: true })
+  }
+})
+
+mountTest('rejects inaccessible FUSE device', { skip: linuxOnly }, async () => {
+  const fs = new MemFs()
+  await assert.rejects(
+    mount(fs, { config: { fuseDevicePath: '/nonexistent/fuse' } }),
+    /Can't open FUSE device \/nonexistent\/fuse/,
+  )
+})
+
+mountTest('unmounts, when process exits without unmount', { skip: linuxOnly }, async () => {
+  const script = `
+    const { MemFs, mount } = require(${JSON.stringify(join(__dirname, 'helpers.js'))})
+    mount(new MemFs()).then(({ root }) => {
+      console.log(root)
+      process.exit(0)
+    })
+  `
+  const root = (await run(process.execPath, ['-e', script])).trim()
+  try {
+    assert.equal(await isMounted(root), false)
+  } finally {
+    cleanupMountPoint(root)
+  }
+})
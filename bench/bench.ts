@@ -0,0 +1,158 @@
+// Benchmark of lookup/getattr and read throughput through a mounted in-memory filesystem.
+//
+// Run with `npm run bench`, after `npm run build`. Mounting needs FUSE to be available to the current user.
+// With `npm run bench -- --read-pool` reads place data into buffers of read pool, instead of returning new ones.
+
+import { mkdtempSync, rmdirSync, statSync, openSync, readSync, closeSync } from 'node:fs'
+import { tmpdir } from 'node:os'
+import { join } from 'node:path'
+import { performance } from 'node:perf_hooks'
+
+import { FSMounter, InodeKind } from '../index.js'
+
+const ROOT_INO = 1
+const SMALL_FILES = 2000
+const SMALL_FILE_SIZE = 1024
+const BIG_FILE_SIZE = 256 * 1024 * 1024
+const READ_CHUNK = 128 * 1024
+const USE_READ_POOL = process.argv.includes('--read-pool')
+
+interface Node {
+  ino: number
+  name: string
+  kind: InodeKind
+  size: number
+}
+
+const nodes = new Map<number, Node>()
+const children = new Map<string, Node>()
+nodes.set(ROOT_INO, { ino: ROOT_INO, name: '', kind: InodeKind.Directory, size: 0 })
+for (let i = 0; i < SMALL_FILES; i += 1) {
+  const node = { ino: i + 2, name: `f${i}`, kind: InodeKind.File, size: SMALL_FILE_SIZE }
+  nodes.set(node.ino, node)
+  children.set(node.name, node)
+}
+const bigFile = { ino: SMALL_FILES + 2, name: 'big', kind: InodeKind.File, size: BIG_FILE_SIZE }
+nodes.set(bigFile.ino, bigFile)
+children.set(bigFile.name, bigFile)
+
+const content = Buffer.alloc(READ_CHUNK, 7)
+const now = Date.now()
+
+function attrOf(node: Node) {
+  return {
+    ino: node.ino,
+    size: node.size,
+    mtime: now,
+    ctime: now,
+    btime: now,
+    kind: node.kind,
+    perm: node.kind === InodeKind.Directory ? 0o555 : 0o444,
+    uid: process.getuid!(),
+    gid: process.getgid!(),
+    rdev: 0,
+    flags: 0,
+    hasXattrs: false,
+  }
+}
+
+const ENOENT = 2
+const ENOSYS = 38
+
+const mountRoot = mkdtempSync(join(tmpdir(), 'napi-fuser-bench-'))
+
+let readBuffers: Buffer[] | undefined = undefined
+
+const mounter = FSMounter.makeAndMount(
+  mountRoot,
+  'bench',
+  (_err, _rootIno, { readBuffers: buffers }) => {
+    readBuffers = buffers
+  },
+  async () => {},
+  async (_err, parent, name) => {
+    const node = parent === ROOT_INO ? children.get(name) : undefined
+    return node ? { type: 'Attr', field0: attrOf(node) } : { type: 'Err', field0: { code: ENOENT } }
+  },
+  () => {},
+  async (_err, ino) => {
+    const node = nodes.get(ino)
+    return node ? { type: 'Attr', field0: attrOf(node) } : { type: 'Err', field0: { code: ENOENT } }
+  },
+  async () => ({ type: 'Err', field0: { code: ENOSYS } }),
+  async () => ({ type: 'Err', field0: { code: ENOSYS } }),
+  async () => ({ type: 'Err', field0: { code: ENOSYS } }),
+  async () => ENOSYS,
+  async () => ENOSYS,
+  async () => ENOSYS,
+  async (_err, ino) => ({ type: 'Params', field0: { fh: ino } }),
+  async (_err, ino, _fh, { offset, size, slot }) => {
+    const node = nodes.get(ino)!
+    const len = Math.max(0, Math.min(size, node.size - offset, content.length))
+    if (slot !== undefined && readBuffers) {
+      content.copy(readBuffers[slot], 0, 0, len)
+      return { type: 'InSlot', field0: len }
+    }
+    return { type: 'Ok', field0: content.subarray(0, len) }
+  },
+  undefined,
+  async () => {},
+  async () => 0,
+  async (_err, ino) => ({ type: 'Params', field0: { fh: ino } }),
+  async () => ({ type: 'Lst', field0: [] }),
+  async () => 0,
+  async () => 0,
+  async () => ({ type: 'Err', field0: { code: ENOSYS } }),
+  async () => ({ type: 'Size', field0: 0 }),
+  async () => ENOSYS,
+  async () => ({ type: 'Granted' }),
+  USE_READ_POOL ? { readBufferPoolSlots: 4 } : undefined,
+)
+
+function percentile(sorted: number[], p: number): number {
+  return sorted[Math.min(sorted.length - 1, Math.floor(sorted.length * p))]
+}
+
+function benchMetadata() {
+  const latencies: number[] = []
+  const start = performance.now()
+  for (let i = 0; i < SMALL_FILES; i += 1) {
+    const t = performance.now()
+    statSync(join(mountRoot, `f${i}`))
+    latencies.push(performance.now() - t)
+  }
+  const total = performance.now() - start
+  latencies.sort((a, b) => a - b)
+  console.log(
+    `stat of ${SMALL_FILES} small files: ${((SMALL_FILES * 1000) / total).toFixed(0)} ops/s, ` +
+      `p50 ${percentile(latencies, 0.5).toFixed(3)} ms, p99 ${percentile(latencies, 0.99).toFixed(3)} ms`,
+  )
+}
+
+function benchData() {
+  const buf = Buffer.alloc(READ_CHUNK)
+  const fd = openSync(join(mountRoot, bigFile.name), 'r')
+  const start = performance.now()
+  let total = 0
+  for (;;) {
+    const n = readSync(fd, buf, 0, buf.length, null)
+    if (n === 0) {
+      break
+    }
+    total += n
+  }
+  const seconds = (performance.now() - start) / 1000
+  closeSync(fd)
+  console.log(
+    `sequential read of ${total / (1024 * 1024)} MiB${USE_READ_POOL ? ' with read pool' : ''}: ` +
+      `${(total / (1024 * 1024) / seconds).toFixed(1)} MiB/s`,
+  )
+}
+
+try {
+  benchMetadata()
+  benchData()
+} finally {
+  mounter.unmount()
+  setTimeout(() => rmdirSync(mountRoot), 500)
+}
@@ -1,43 +1,343 @@
 /* auto-generated by NAPI-RS */
 /* eslint-disable */
+export declare namespace Errno {
+  export const E2BIG: number
+  export const EACCES: number
+  export const EAGAIN: number
+  export const EBADF: number
+  export const EBUSY: number
+  export const EDQUOT: number
+  export const EEXIST: number
+  export const EFAULT: number
+  export const EFBIG: number
+  export const EINTR: number
+  export const EINVAL: number
+  export const EIO: number
+  export const EISDIR: number
+  export const ELOOP: number
+  export const EMFILE: number
+  export const EMLINK: number
+  export const ENAMETOOLONG: number
+  export const ENFILE: number
+  /** Error for a missing extended attribute, see [`PosixError::enoattr`]. */
+  export const ENOATTR: number
+  export const ENODATA: number
+  export const ENODEV: number
+  export const ENOENT: number
+  export const ENOMEM: number
+  export const ENOSPC: number
+  export const ENOSYS: number
+  export const ENOTDIR: number
+  export const ENOTEMPTY: number
+  export const ENOTSUP: number
+  export const ENXIO: number
+  export const EOPNOTSUPP: number
+  export const EPERM: number
+  export const ERANGE: number
+  export const EROFS: number
+  export const ESPIPE: number
+  export const ESRCH: number
+  export const ESTALE: number
+  export const ETIMEDOUT: number
+  export const ETXTBSY: number
+  export const EXDEV: number
+}
+
 export declare class FSMounter {
-  static makeAndMount(mountRoot: string, fsName: string, init: InitOpCB, destroy: DestroyOpCB, lookup: LookupOpCB, forget: ForgetOpCB, getattr: GetAttrOpCB, setattr: SetAttrOpCB, mknod: MkNodOpCB, mkdir: MkDirOpCB, unlink: UnlinkOpCB, rmdir: RmDirOpCB, rename: RenameOpCB, open: OpenOpCB, read: ReadOpCB, flush: FlushOpCB, release: ReleaseOpCB, fsync: FSyncOpCB, opendir: OpenDirOpCB, readdir: ReadDirOpCB, releasedir: ReleaseDirOpCB, fsyncdir: FSyncDirOpCB, getxattr: GetXAttrOpCB, listxattr: ListXAttrOpCB, removexattr: RemoveXAttrOpCB, access: AccessOpCB): FSMounter
-  unmount(): void
+  static makeAndMount(mountRoot: string, fsName: string, init: InitOpCB, destroy: DestroyOpCB, lookup: LookupOpCB, forget: ForgetOpCB, getattr: GetAttrOpCB, setattr: SetAttrOpCB, mknod: MkNodOpCB, mkdir: MkDirOpCB, unlink: UnlinkOpCB, rmdir: RmDirOpCB, rename: RenameOpCB, open: OpenOpCB, read: ReadOpCB, flush: FlushOpCB | undefined | null, release: ReleaseOpCB, fsync: FSyncOpCB, opendir: OpenDirOpCB, readdir: ReadDirOpCB, releasedir: ReleaseDirOpCB, fsyncdir: FSyncDirOpCB, getxattr: GetXAttrOpCB, listxattr: ListXAttrOpCB, removexattr: RemoveXAttrOpCB, access: AccessOpCB, config?: MountConfig | undefined | null): FSMounter
+  /**
+   * Mounts read-only passthrough of a directory on disk, that is implemented in Rust without JS callbacks. It checks,
+   * that FUSE mounting works, and is a reference of replies, expected from JS callbacks.
+   */
+  static mountPassthrough(mountRoot: string, targetDir: string): FSMounter
+  /** Wakes up kernel's poll of a given file handle, if kernel waits for it. Kernel then polls the file again. */
+  notifyPoll(fh: number): void
+  /**
+   * Invalidates cached data of a directory and of all its descendants, known to kernel, in kernel and in this
+   * module, for a coarse "everything under here has changed" signal, like after a sync with backend. Entries are
+   * then looked up and read again from JS side.
+   *
+   * This should not be called, while JS side has unfinished reads, as kernel may wait for them, while invalidating.
+   */
+  invalidateSubtree(ino: number): void
+  /**
+   * Returns inodes, written since their last successful flush or fsync, so that JS side may flush these ahead of
+   * kernel, like on a timer, when writeback cache delays flushes. With `durabilityTier` other than `None`, writes are
+   * flushed before being replied, and inodes don't stay dirty.
+   */
+  dirtyInodes(): Array<number>
+  /**
+   * Places data into kernel's page cache of a file, like prefetched data, so that reads of it are served by kernel
+   * without calling JS side. Kernel drops cached data of a file, when it is opened without `keepCache` in
+   * `ParamsOfOpened`, and file size grows to the end of stored data, when data goes past it. Stored data should be the
+   * same, as JS side returns in reads, as kernel doesn't check it, and a later write or truncate, that JS side gets,
+   * isn't ordered with a store. Data for an inode, that kernel doesn't know, is silently dropped.
+   *
+   * Retrieval of cached data back from kernel is not available, as fuser doesn't handle kernel's replies to it.
+   */
+  storeData(ino: number, offset: number, data: Uint8Array): void
+  /**
+   * Returns statistics of filesystem, given by statfs callback, with defaults filled in, as kernel sees them. This
+   * lets monitoring check space usage without statvfs(2) on mount point.
+   */
+  statfs(): Promise<StatfsResult>
+  /**
+   * Unmounts filesystem. When process exits without unmount, filesystem is unmounted automatically.
+   * When files or directories are still open, this fails with busy error, unless `force` is
+   * true. Forced unmount is lazy: mount point is detached right away, and session ends, when last open file is
+   * closed. Calls after successful unmount do nothing.
+   */
+  unmount(force?: boolean | undefined | null): void
 }
 export type JsFSMounter = FSMounter
 
-/** access [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust crate. */
+/**
+ * POSIX error codes, values of which differ between platforms.
+ * JS side should take codes from here, instead of hardcoding numbers.
+ */
+export declare class PosixError {
+  /** `ENODATA` of current platform (61 on Linux, 96 on macOS). */
+  static enodata(): number
+  /**
+   * Error for a missing extended attribute.
+   * It is `ENOATTR` (93) on macOS, while on Linux `ENOATTR` is an alias of `ENODATA` (61).
+   */
+  static enoattr(): number
+}
+
+/**
+ * This is called with request id of a long running operation (read, write, readdir, readdirplus), when this module
+ * has given up waiting for js reply (see `MountConfig.jsReplyTimeoutMs`) and has already replied kernel with `EIO`.
+ * JS side may use it to trigger `AbortController`, with which underlying fetch/IO is done, so as to stop useless work.
+ *
+ * Note that kernel's interrupts, like ones after the calling process is killed, are not passed here, as [`fuser`]
+ * doesn't handle FUSE_INTERRUPT.
+ */
+export type AbortOpCB =
+  ((err: Error | null, arg: number) => any)
+
+/**
+ * access [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust crate.
+ *
+ * This is called only with non-zero mask. Existence check with `F_OK` (zero mask) is answered by getattr callback,
+ * or by recent attributes, when getattr coalescing is on.
+ *
+ * Should return `Granted`, or `Denied` with an error code, usually `EACCES`.
+ */
 export type AccessOpCB =
-  ((err: Error | null, arg0: number, arg1: number) => Promise<number>)
+  ((err: Error | null, arg0: number, arg1: number) => Promise<AccessResult>)
+
+export type AccessResult =
+  | { type: 'Granted' }
+  | { type: 'Denied', field0: FsError }
+
+/** Kernel's atime update modes, set with respective mount options. */
+export declare const enum AtimeMode {
+  /** `noatime`: atime is never updated on reads. */
+  Never = 0,
+  /** `relatime`: atime is updated on read, only when it is earlier than mtime or ctime, or is older than a day. */
+  Relative = 1,
+  /** `strictatime`: atime is updated on every read. */
+  Strict = 2
+}
 
 export interface AttrChanges {
   mode?: number
+  size?: number
   uid?: number
   gid?: number
   flags?: number
+  /**
+   * Access time in milliseconds since epoch. Setting to current time, like utimensat(2) with `UTIME_NOW`, comes
+   * already resolved to time of the call.
+   */
+  atime?: number
+  /** Modification time in milliseconds since epoch, with current time resolved, like in `atime`. */
+  mtime?: number
 }
 
+/**
+ * Returns block size, recommended to report in `blksize` of `FileAttr`, for a backing storage with a given typical
+ * size of its I/O, like 512 for HDD, 4096 for SSD, or 65536 for an object store. Applications use `blksize` as a
+ * preferred size of their reads and writes.
+ *
+ * Storage's size is rounded up to a power of two, and is kept between page size of 4 KiB, below which kernel does
+ * read-modify-write of pages anyway, and 128 KiB, above which kernel splits requests with default `max_read` and
+ * `max_write`.
+ */
+export declare function blockSizeForStorage(storageBytesPerIo: number): number
+
 export type BufferOrErr =
   | { type: 'Ok', field0: Buffer }
-  | { type: 'Err', field0: number }
+  | { type: 'OkWithAttr', field0: DataWithAttr }
+  | { type: 'InSlot', field0: number }
+  | { type: 'Sparse', field0: SparseData }
+  | { type: 'Err', field0: FsError }
+
+/** Removes an empty mount point directory, refusing to do so, while something is still mounted on it. */
+export declare function cleanupMountPoint(path: string): void
+
+/**
+ * Arguments of copy_file_range, besides source ino and fh.
+ *
+ * Copying within the same file with overlapping source and destination ranges is invalid (see
+ * copy_file_range(2)). Such requests are replied with `EINVAL` without calling JS side, hence callback never sees
+ * them.
+ */
+export interface CopyFileRangeArgs {
+  offsetIn: number
+  inoOut: number
+  fhOut: number
+  offsetOut: number
+  len: number
+  /** Flags of copy_file_range(2). Currently, kernel defines none. */
+  flags: number
+}
+
+/**
+ * copy_file_range [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser
+ * Rust crate.
+ *
+ * Arguments:
+ * 1. ino of source file
+ * 2. fh of source file
+ * 3. other arguments
+ *
+ * Should return filesystem error code or number of copied bytes.
+ *
+ * Both files are in this filesystem, as kernel does copies between filesystems itself, without coming here. Copy
+ * should be done as one operation of backing store, like a server-side copy, rather than as read and write of data,
+ * else there is no gain over kernel's fallback, which does exactly that, when this callback is absent.
+ */
+export type CopyFileRangeOpCB =
+  ((err: Error | null, arg0: number, arg1: number, arg2: CopyFileRangeArgs) => Promise<WrittenOrErr>)
+
+export interface CreateArgs {
+  /** Mode with umask already applied, see [`MkDirOpCB`]. */
+  mode: number
+  umask: number
+  flags: number
+}
+
+export type CreatedOrErr =
+  | { type: 'Created', field0: CreatedResult }
+  | { type: 'Err', field0: FsError }
+
+export interface CreatedResult {
+  entry: MkNodResult
+  opened: ParamsOfOpened
+}
+
+/**
+ * create [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
+ * crate. Absent this callback, create is replied with `ENOSYS`, and kernel falls back to mknod and open.
+ *
+ * Arguments:
+ * 1. parent inode id
+ * 2. name of a new file
+ * 3. other arguments
+ *
+ * Should return filesystem error code or both new entry and parameters of its opening.
+ */
+export type CreateOpCB =
+  ((err: Error | null, arg0: number, arg1: string, arg2: CreateArgs) => Promise<CreatedOrErr>)
+
+/** Returns group id of this process, to be used as a group of files. */
+export declare function currentGid(): number
+
+/** Returns user id of this process, to be used as an owner of files. */
+export declare function currentUid(): number
+
+export interface DataSegment {
+  offset: number
+  data: Buffer
+}
+
+/** Read data with updated attributes. Like ones of [`WrittenWithAttr`], these answer getattr, that follows read. */
+export interface DataWithAttr {
+  data: Buffer
+  attr: FileAttr
+}
 
 /**
  * destory [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
  * crate.
+ *
+ * Filesystem session ends only after returned promise settles, or after 10 seconds, hence, JS side may use it to
+ * flush its buffers.
  */
 export type DestroyOpCB =
-  ((err: Error | null, ) => any)
+  ((err: Error | null, ) => Promise<undefined>)
+
+/**
+ * Read of a file, opened with `ParamsOfOpened.directIo`, called instead of `ReadOpCB` with the same arguments.
+ * Without kernel's page cache, offset and size come as application has asked, without alignment to pages, and a
+ * size may be bigger than one of cached reads. Absent this callback, such reads go to `ReadOpCB`.
+ */
+export type DirectReadOpCB =
+  ((err: Error | null, arg0: number, arg1: number, arg2: ReadArgs) => Promise<BufferOrErr>)
+
+/**
+ * Write into a file, opened with `ParamsOfOpened.directIo`, called instead of `WriteOpCB` with the same arguments.
+ * Like with `DirectReadOpCB`, offset and size come unaligned, as application has written them. Absent this
+ * callback, such writes go to `WriteOpCB`.
+ */
+export type DirectWriteOpCB =
+  ((err: Error | null, arg0: number, arg1: number, arg2: WriteArgs) => Promise<WrittenOrErr>)
 
 export interface DirEntry {
   ino: number
   offset: number
   kind: InodeKind
   name: string
+  /** Not used by readdir, and is here for symmetry with [`DirEntryPlus`]. */
+  generation?: number
+}
+
+export interface DirEntryPlus {
+  offset: number
+  name: string
+  /**
+   * Attributes are required in every entry. Kernel takes entry with inode number 0 as one without attributes, but
+   * fuser sends inode number of attributes also as inode number of entry, and readdir(3) skips entries with inode
+   * number 0. Backends, that can't cheaply give attributes of some entries, should use readdir instead.
+   */
+  attr: FileAttr
+  /** Generation of inode, like one in lookup. Default is 0. */
+  generation?: number
 }
 
 export type DirListing =
   | { type: 'Lst', field0: Array<DirEntry> }
-  | { type: 'Err', field0: number }
+  | { type: 'Partial', field0: PartialDirListing }
+  | { type: 'Err', field0: FsError }
+
+export type DirListingPlus =
+  | { type: 'Lst', field0: Array<DirEntryPlus> }
+  | { type: 'Err', field0: FsError }
+
+export declare const enum DurabilityTier {
+  None = 0,
+  Flush = 1,
+  Fsync = 2
+}
+
+/** Report of a failure in native part of the filesystem. */
+export interface ErrorReport {
+  /** One of `"mount"`, `"panic"`, `"session-end"`, `"unmount"`, `"fs-error"` or `"slow-op"`. */
+  kind: string
+  message: string
+  /** Present, when backtraces are enabled with `RUST_BACKTRACE` environment variable. */
+  backtrace?: string
+}
+
+/**
+ * This is called with reports of failures, that can't be returned to js side in any other way, like mount errors
+ * after retries, panics in filesystem's thread, and ending of session without unmount call. Errors of operations,
+ * given by js side with a message, are reported here as well, as kernel gets only their codes.
+ */
+export type ErrorReportCB =
+  ((err: Error | null, arg: ErrorReport) => any)
 
 export interface FileAttr {
   ino: number
@@ -46,7 +346,7 @@ export interface FileAttr {
   ctime: number
   btime: number
   kind: InodeKind
-  /** Permissions */
+  /** Permissions. With `PERM_OF_MOUNT`, these are set by `MountConfig.fileMode` or `MountConfig.dirMode`. */
   perm: number
   /** User id */
   uid: number
@@ -56,11 +356,18 @@ export interface FileAttr {
   rdev: number
   /** Flags (macOS only, see chflags(2)) */
   flags: number
+  /**
+   * Set to false for inodes, known to have no extended attributes, so that listxattr is replied without calling
+   * JS side. Default is true.
+   */
+  hasXattrs?: boolean
+  /** Preferred size of I/O, reported in `st_blksize` of stat(2), see `blockSizeForStorage`. Default is 4096. */
+  blksize?: number
 }
 
 export type FileAttrOrErr =
   | { type: 'Attr', field0: FileAttr }
-  | { type: 'Err', field0: number }
+  | { type: 'Err', field0: FsError }
 
 /**
  * flush [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
@@ -70,10 +377,24 @@ export type FileAttrOrErr =
  * 1. ino
  * 2. fh
  * 3. lock_owner
+ *
+ * Not called, and can be omitted, when filesystem is mounted read-only, as there is nothing to flush.
  */
 export type FlushOpCB =
   ((err: Error | null, arg0: number, arg1: number, arg2: number) => Promise<number>)
 
+/**
+ * Many forgets at once, replacing calls of forget callback, when present.
+ *
+ * Arguments:
+ * 1. pairs `[ino, nlookup]`, like arguments of [`ForgetOpCB`], which notes apply here as well.
+ *
+ * Kernel drops many inodes at once, like after a walk over a big tree, and forgets, that arrive before JS side takes
+ * a call, go together in it. Like forget, this isn't called, when lookup counts are kept by this module.
+ */
+export type ForgetManyOpCB =
+  ((err: Error | null, arg: PendingForgets) => any)
+
 /**
  * forget [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
  * crate.
@@ -81,10 +402,28 @@ export type FlushOpCB =
  * Arguments:
  * 1. ino
  * 2. nlookup - count of lookups to drop.
+ *
+ * Not called, when lookup counts are kept by this module, see [`ReleaseInodeCB`].
+ *
+ * Forget has no reply, and kernel doesn't wait for it. Calls are queued to JS side without blocking the filesystem's
+ * thread, and they come in the order, in which kernel sent forgets. But requests, that come after a forget, may reach
+ * JS side before it, hence, inode may be looked up again, before JS side sees its forget. Lookup counts in such
+ * forget are for lookups, done before it, and JS side should subtract them, not reset inode's count to zero.
  */
 export type ForgetOpCB =
   ((err: Error | null, arg0: number, arg1: number) => any)
 
+/** Error of an operation, given by JS side. */
+export interface FsError {
+  /** POSIX error code, that is replied to kernel. */
+  code: number
+  /**
+   * Description of error, like OS-level message of a failed backend call. When present, error is reported to
+   * `MountConfig.onError` with kind `"fs-error"`, as kernel gets only the code.
+   */
+  message?: string
+}
+
 /**
  * fsyncdir [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
  * crate.
@@ -105,17 +444,43 @@ export type FSyncDirOpCB =
  * 1. ino
  * 2. fh
  * 3. datasync flag
+ *
+ * With writeback cache (see `KernelCaps.writebackCache`), kernel first sends its dirty pages of the file as writes,
+ * waits for their replies, and only then sends fsync. Requests are taken from kernel one at a time, and write is
+ * replied only after its promise resolves, hence, when fsync is called, JS side has already received all data,
+ * written before application's fsync(2). Fsync should persist this data, before resolving.
  */
 export type FSyncOpCB =
   ((err: Error | null, arg0: number, arg1: number, arg2: boolean) => Promise<number>)
 
+/**
+ * Returns symbolic name of an errno code, like `"EINVAL"` for 22, for logging of errors in callbacks. Codes without
+ * a known name are described by platform's error message.
+ */
+export declare function fuseErrnoToString(code: number): string
+
+/**
+ * This is called with ino, found in lookup, to get its generation number.
+ * Absent this callback, lookup reports generation 0. Create, mknod and mkdir return generation in their results.
+ *
+ * Pair (ino, generation) must be unique during filesystem's lifetime, i.e. generation should change, when ino
+ * number is reused for another file. This matters for NFS re-export (see `KernelCaps.exportSupport`), where
+ * kernel hands out file handles, made from these pairs, and stale handles are recognized by generation.
+ */
+export type GenerationStoreCB =
+  ((err: Error | null, arg: number) => Promise<number>)
+
 /**
  * getattr [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
  * crate.
  *
  * Arguments:
  * 1. ino
- * 2. fh
+ * 2. fh - present, when kernel asks attributes for an operation on an open file, like lseek(2) to the end of file,
+ *    and absent for stat(2). Note that Linux kernel doesn't give handle in fstat(2). It is a handle, returned by a
+ *    successful open (or create), and not yet released. JS side may return attributes, specific to the handle, for
+ *    example, size of a file with writes, that are not yet committed, while path-based stat gives committed size.
+ *    Handle's state may also keep a stat, saving a query of inode store.
  *
  * Should return filesystem error code or an attributes data.
  */
@@ -125,13 +490,57 @@ export type GetAttrOpCB =
 /**
  * getxattr [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
  * crate.
+ *
+ * When requested attribute doesn't exist, error code should be `PosixError.enoattr()`, which is `ENODATA` on Linux
+ * and `ENOATTR` on macOS.
+ *
+ * Value may be returned whole with any size. With zero size, only its length is given to kernel, and value, longer
+ * than non-zero size, is replied with `ERANGE`. Values over 64 KiB, kernel's limit, are replied with `E2BIG`.
  */
 export type GetXAttrOpCB =
   ((err: Error | null, arg0: number, arg1: string, arg2: number) => Promise<XAttrBytesOrErr>)
 
-/** init [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust crate. */
+/**
+ * getxtimes [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
+ * crate. This is called on macOS only, and is ignored on other platforms.
+ *
+ * Arguments:
+ * 1. ino
+ *
+ * Should return filesystem error code or backup and creation times.
+ */
+export type GetXTimesOpCB =
+  ((err: Error | null, arg: number) => Promise<XTimesOrErr>)
+
+export interface InitInfo {
+  /**
+   * Names of requested capabilities (see `KernelCaps`), which kernel doesn't support. These are not enabled, and
+   * filesystem may want to warn or refuse to serve.
+   */
+  deniedCaps: Array<string>
+  /**
+   * Buffers of write pool, present, when pool is set in mount config. Write with `WriteArgs.slot` has its data at
+   * the start of respective buffer. Buffer must not be used after write's promise is settled.
+   */
+  writeBuffers?: PoolBuffers
+  /**
+   * Buffers of read pool, present, when pool is set in mount config. Read with `ReadArgs.slot` may place its data at
+   * the start of respective buffer, returning only data's length. Buffer must not be used after read's promise is
+   * settled.
+   */
+  readBuffers?: PoolBuffers
+  limits: KernelLimits
+}
+
+/**
+ * init [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust crate.
+ *
+ * Arguments:
+ * 1. ino of root
+ * 2. results of negotiation with kernel
+ */
 export type InitOpCB =
-  ((err: Error | null, arg: number) => any)
+  ((err: Error | null, arg0: number, arg1: InitInfo) => any)
 
 export declare const enum InodeKind {
   Directory = 0,
@@ -139,9 +548,92 @@ export declare const enum InodeKind {
   SymLink = 2
 }
 
+export interface IoctlArgs {
+  flags: number
+  cmd: number
+  inData: Buffer
+  outSize: number
+}
+
+/**
+ * ioctl [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust crate.
+ *
+ * Arguments:
+ * 1. ino
+ * 2. fh
+ * 3. ioctl arguments
+ *
+ * Note that [`fuser`] replies only to restricted ioctls, i.e. there is no way to ask kernel for a retry with
+ * different in/out buffers. Hence, ioctls without fixed-size request/response can't be served.
+ */
+export type IoctlOpCB =
+  ((err: Error | null, arg0: number, arg1: number, arg2: IoctlArgs) => Promise<IoctlOrErr>)
+
+export type IoctlOrErr =
+  | { type: 'Ok', field0: IoctlResult }
+  | { type: 'Err', field0: FsError }
+
+export interface IoctlResult {
+  result: number
+  data: Buffer
+}
+
+/**
+ * Optional kernel capabilities, that can be requested at init.
+ * See [libfuse docs](https://libfuse.github.io/doxygen/fuse__common_8h.html) for respective `FUSE_CAP_*`.
+ */
+export interface KernelCaps {
+  /** Kernel caches writes, sending them later in bigger chunks. Cached writes are sent before fsync and flush. */
+  writebackCache?: boolean
+  /**
+   * Truncation on open is done by open itself, and is not sent as a separate setattr. Open callback gets a flag,
+   * telling when to truncate.
+   */
+  atomicOTrunc?: boolean
+  /**
+   * Filesystem supports lookups of "." and "..", needed for NFS export.
+   * Such filesystem should also give generation numbers, see `MountConfig.generationStore`.
+   */
+  exportSupport?: boolean
+  /** Lookups and readdirs in the same directory may run in parallel. */
+  parallelDirops?: boolean
+  /** Kernel caches symlink targets. */
+  cacheSymlinks?: boolean
+  /** Kernel doesn't invalidate cached pages on attribute changes, waiting for explicit invalidation. */
+  explicitInvalData?: boolean
+  /**
+   * Filesystem keeps no per-open state. When granted, open is replied with `ENOSYS` without calling JS side, after
+   * which kernel stops sending open and release, and reads come with fh 0.
+   */
+  noOpenSupport?: boolean
+  /**
+   * Filesystem keeps no per-opendir state. When granted, opendir is replied with `ENOSYS` without calling JS side,
+   * after which kernel stops sending opendir and releasedir, and readdirs come with fh 0.
+   */
+  noOpendirSupport?: boolean
+}
+
+/** Limits, negotiated with kernel in init. */
+export interface KernelLimits {
+  /**
+   * Readahead in bytes, that kernel has offered, which is also the biggest one it allows. Filesystem may use this
+   * as a hint for `MountConfig.maxReadahead` of later mounts.
+   */
+  kernelMaxReadahead: number
+  /** Readahead in bytes, that is in effect. */
+  maxReadahead: number
+}
+
+export type LinkTargetOrErr =
+  | { type: 'Target', field0: string }
+  | { type: 'Err', field0: FsError }
+
 /**
  * listxattr [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
  * crate.
+ *
+ * Inode without extended attributes has an empty list, which is not an error. Hence, `PosixError.enoattr()` is not
+ * expected here, unlike in getxattr.
  */
 export type ListXAttrOpCB =
   ((err: Error | null, arg0: number, arg1: number) => Promise<XAttrBytesOrErr>)
@@ -160,6 +652,28 @@ export type ListXAttrOpCB =
 export type LookupOpCB =
   ((err: Error | null, arg0: number, arg1: string) => Promise<FileAttrOrErr>)
 
+/**
+ * lseek [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
+ * crate. Kernel sends only `SEEK_DATA` and `SEEK_HOLE` here, as it resolves other whence values itself. Absent
+ * this callback, these are replied with `ENOSYS`, after which kernel treats whole file as data.
+ *
+ * Arguments:
+ * 1. ino
+ * 2. fh
+ * 3. offset
+ * 4. whence
+ *
+ * Should return filesystem error code or resulting offset:
+ * - for `SEEK_DATA`, start of the first data at or after given offset;
+ * - for `SEEK_HOLE`, start of the first hole at or after given offset, where end of file counts as a hole.
+ *
+ * When given offset is at or past end of file, or, for `SEEK_DATA`, there is no data after it, error should be
+ * `ENXIO`. Offsets, that don't fit into JS number exactly, i.e. above 2^53, should be returned as BigInt.
+ * Offset before the given one is a broken reply, and is replied with `EIO`.
+ */
+export type LseekOpCB =
+  ((err: Error | null, arg0: number, arg1: number, arg2: number, arg3: number) => Promise<OffsetOrErr>)
+
 /**
  * mkdir [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
  * crate.
@@ -169,10 +683,21 @@ export type LookupOpCB =
  * 2. name of a new child
  * 3. mode
  * 4. umask
+ *
+ * Kernel applies umask of calling process to mode, before sending it, as this module doesn't ask kernel in init to
+ * leave masking to filesystem (`FUSE_DONT_MASK`). Hence, mode should be used as is, while umask is given only for
+ * information, like for filesystems with default ACLs, and it shouldn't be applied again. The same goes for mknod
+ * and create.
  */
 export type MkDirOpCB =
   ((err: Error | null, arg0: number, arg1: string, arg2: number, arg3: number) => Promise<NewEntryOrErr>)
 
+/**
+ * Creates a new directory with a unique name, made from given path prefix with six random characters, for example,
+ * `mkdtemp(join(tmpdir(), 'fs-'))`. Returns path of created directory, that can be used as a mount point.
+ */
+export declare function mkdtemp(prefix: string): string
+
 /**
  * mknod [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
  * crate.
@@ -180,22 +705,286 @@ export type MkDirOpCB =
  * Arguments:
  * 1. parent inode id
  * 2. name of a new child node
- * 3. mode
+ * 3. mode, with umask already applied, see [`MkDirOpCB`]
  * 4. umask
  * 5. rdev id
  */
 export type MkNodOpCB =
   ((err: Error | null, arg0: number, arg1: string, arg2: number, arg3: number, arg4: number) => Promise<NewEntryOrErr>)
 
+/** New entry, made by mknod, mkdir, symlink or create. */
 export interface MkNodResult {
-  ttl: number
+  /**
+   * Time in milliseconds, for which kernel may cache this entry and its attributes, without asking for them again.
+   * Default is mount's default of 1 second.
+   */
+  ttl?: number
   attr: FileAttr
+  /**
+   * Generation of inode, that together with inode number identifies file over lifetime of filesystem. It matters
+   * only for filesystems, that are exported over NFS, and reuse inode numbers. Others should set 0.
+   */
   generation: number
 }
 
+/**
+ * Optional part of mount setup: callbacks of operations, that not every filesystem needs, and settings.
+ *
+ * When callback of an operation is absent, operation is replied with `ENOSYS`, unless stated otherwise.
+ */
+export interface MountConfig {
+  /** r" Present only with extended-ops feature, like all callbacks marked with it. */
+  ioctl?: IoctlOpCB
+  /** r" extended-ops */
+  copyFileRange?: CopyFileRangeOpCB
+  /** r" extended-ops */
+  lseek?: LseekOpCB
+  /** r" extended-ops */
+  poll?: PollOpCB
+  /** r" Present only with writable feature, like all callbacks marked with it. */
+  create?: CreateOpCB
+  /** r" writable */
+  symlink?: SymlinkOpCB
+  /** r" writable */
+  write?: WriteOpCB
+  /** r" writable */
+  prepareWrite?: PrepareWriteCB
+  /** r" writable */
+  directWrite?: DirectWriteOpCB
+  abort?: AbortOpCB
+  truncate?: TruncateOpCB
+  statfs?: StatfsOpCB
+  generationStore?: GenerationStoreCB
+  readdirplus?: ReadDirPlusOpCB
+  releaseInode?: ReleaseInodeCB
+  forgetMany?: ForgetManyOpCB
+  readlink?: ReadlinkOpCB
+  directRead?: DirectReadOpCB
+  onError?: ErrorReportCB
+  /** macOS only */
+  getxtimes?: GetXTimesOpCB
+  /**
+   * BSD file flags (see chflags(2)), like `UF_IMMUTABLE` or `UF_APPEND`, that are reported for every inode, in
+   * addition to flags from JS side. Attempts to clear these flags with setattr are rejected with `EPERM`.
+   * This is useful for immutable/content-addressed stores.
+   */
+  fileFlags?: number
+  /**
+   * Pairs `(from_uid, to_uid)` to translate uids of inodes, coming from JS side, into uids reported to kernel, for
+   * example, inside of user namespace. Uids in setattr are translated back, before being passed to JS.
+   * Uids without a pair are passed unchanged.
+   */
+  uidMap?: Array<[number, number]>
+  /** Pairs `(from_gid, to_gid)`, used for gids just like [`MountConfig::uid_map`] is used for uids. */
+  gidMap?: Array<[number, number]>
+  /**
+   * Pairs `(from_code, to_code)` to translate error codes, returned by JS side, before they reach applications, like
+   * `ESTALE` into `ENOENT` for tools, that loop on `ESTALE`. Codes without a pair are passed unchanged, and errors,
+   * made on Rust side, aren't translated.
+   */
+  errnoMap?: Array<[number, number]>
+  /** Capabilities to request from kernel in init. Denied ones are listed in an argument of init callback. */
+  capabilities?: KernelCaps
+  /**
+   * Filesystem subtype, shown in mount table as `fuse.<subtype>`. Like filesystem name, it must not contain spaces
+   * or slashes.
+   */
+  subtype?: string
+  /** Number of times mounting is retried after transient errors, like interruption by a signal. Default is 0. */
+  mountRetries?: number
+  /** Pause before the first retry of mounting, doubled on each subsequent one. Default is 100 milliseconds. */
+  mountRetryBackoffMs?: number
+  /**
+   * Number of times idempotent operations (lookup, getattr, read, readdir) are retried, when JS side rejects them
+   * with an error, which message starts with "retryable", e.g. after a transient failure of a network store. Other
+   * rejections and all other operations are never retried. Default is 0.
+   */
+  jsRetries?: number
+  /** Pause before the first retry of an operation, doubled on each subsequent one. Default is 50 milliseconds. */
+  jsRetryBackoffMs?: number
+  /**
+   * Mounts filesystem read-only. Default is false, when module is built with writable feature, and without it
+   * filesystem is always read-only. When read-only, flush is replied without calling JS side.
+   */
+  readOnly?: boolean
+  /**
+   * Maximum length of a file name in bytes, reported in statfs. Longer names are rejected with `ENAMETOOLONG`
+   * without calling JS side. Default is 255.
+   */
+  maxNameLength?: number
+  /** Fragment size, reported in statfs, when statfs callback doesn't give one. Default is block size. */
+  frsize?: number
+  /**
+   * Checks in Rust, with getattr of parent, that caller may write into parent directory, before calling mkdir of JS
+   * side, replying `EACCES` when it can't. This is useful when kernel doesn't check permissions itself, i.e. without
+   * `default_permissions` mount option. Default is false.
+   */
+  checkParentPerms?: boolean
+  /**
+   * Permission bits, reported for non-directory inodes, which JS side gives with `perm` set to `PERM_OF_MOUNT`.
+   * Default is 0o644.
+   */
+  fileMode?: number
+  /**
+   * Permission bits, reported for directories, which JS side gives with `perm` set to `PERM_OF_MOUNT`. Default is
+   * 0o755.
+   */
+  dirMode?: number
+  /** Permission bits to clear in all non-directory inodes, like `fmask` of vfat. */
+  fmask?: number
+  /** Permission bits to clear in all directories, like `dmask` of vfat. */
+  dmask?: number
+  /**
+   * Window in milliseconds, during which repeated getattr requests for the same inode and fh are replied with
+   * attributes from the first one, without calling JS side. It should be much shorter than attributes' TTL.
+   * Attributes' changes with setattr and writes reset the window, and attributes, given by JS side with results of
+   * reads and writes, start a new one. Default is no coalescing.
+   */
+  getattrCoalesceMs?: number
+  /**
+   * Time in milliseconds, for which kernel caches entries of lookup, readdirplus and of new files, without looking
+   * them up again. Attributes, given with these entries, are cached for the same time. New entries may set their own
+   * time. Default is 1 second.
+   */
+  entryTimeoutMs?: number
+  /** Time in milliseconds, for which kernel caches attributes of getattr and setattr. Default is 1 second. */
+  attrTimeoutMs?: number
+  /**
+   * Time in milliseconds, for which kernel remembers, that a name doesn't exist, after lookup has failed with
+   * `ENOENT`, not calling lookup for it again. Default is no such caching.
+   */
+  negativeTimeoutMs?: number
+  /**
+   * Number of pre-allocated 1MiB buffers, given to JS side in init, into which data of writes is copied, instead of
+   * allocating a new buffer for every write. Writes get a new buffer, when all slots are in use. Slot of a write,
+   * that timed out, is not reused. Pool needs runtime with external buffers, which, for example, Electron lacks.
+   * Default is no pool.
+   */
+  writeBufferPoolSlots?: number
+  /**
+   * Number of pre-allocated 1MiB buffers, given to JS side in init, into which JS side may place data of reads,
+   * instead of allocating a new buffer for every read. Reads get no slot, when all slots are in use, or when read is
+   * bigger than a slot. Slot of a read, that timed out, is not reused. Like write pool, this needs runtime with
+   * external buffers. Default is no pool.
+   */
+  readBufferPoolSlots?: number
+  /**
+   * Raw mount options, passed to mount as they are, for example, `"max_read=131072"`. Options, that are set from
+   * other fields, like `ro`, `rw`, `fsname`, `subtype`, `atime` and `noatime`, and permission options `allow_other` and
+   * `allow_root` are rejected, as are options with commas.
+   */
+  customMountOptions?: Array<string>
+  /**
+   * Debug check, that JS side doesn't change kind of an inode, while kernel knows it, for example, returning a file
+   * in lookup for an inode, that was a directory. Lookup and getattr with a changed kind are replied with `ESTALE`.
+   * Default is false.
+   */
+  checkKindChanges?: boolean
+  /**
+   * Keeps listings, read by readdir, while directory is open, so that repeated reading of an open directory, like
+   * after rewinddir(3), doesn't call JS side again. Listings are dropped, when entries of directory are changed
+   * through this filesystem, but changes, made by JS side on its own, aren't seen in cached listings. Default is
+   * false.
+   */
+  cacheDirListings?: boolean
+  /**
+   * After a successful setattr, that changes size, asks kernel to drop cached pages past the new size, as
+   * `fadvise(POSIX_FADV_DONTNEED)` would, so that no stale data is read after truncation, for example, with writeback
+   * cache. Default is false.
+   */
+  evictCacheOnTruncate?: boolean
+  /**
+   * Makes writes to files, opened with `O_APPEND`, go to the end of file, as JS side reports it in getattr, whatever
+   * offset kernel has given. Kernel places appends at the end of file, as it knows it, which may be stale, when file
+   * is changed on JS side, or with direct I/O. Not needed with writeback cache, where kernel owns file size. Each
+   * such write costs an additional getattr, unless coalesced attributes are at hand. Default is false, and JS side
+   * may act on `WriteArgs.append` itself.
+   */
+  enforceAppend?: boolean
+  /**
+   * Filesystem has no extended attributes. All xattr operations are replied with `ENOSYS` without calling JS side,
+   * after which kernel stops sending them, failing them with `EOPNOTSUPP`. This speeds up tools like `cp -a`, `tar`
+   * and `ls`, that probe xattrs of every file. Default is false.
+   */
+  noXattr?: boolean
+  /**
+   * Earliest time, in milliseconds since epoch, that may be set as atime or mtime in setattr. Earlier times are
+   * rejected with `EINVAL` without calling JS side. Default is no restriction.
+   */
+  minTimestampMs?: number
+  /**
+   * Latest time, in milliseconds since epoch, that may be set as atime or mtime in setattr. Later times are
+   * rejected with `EINVAL` without calling JS side. Default is no restriction.
+   */
+  maxTimestampMs?: number
+  /**
+   * Reports mtime as creation time of inodes, which JS side gives with `btime` 0, like ones from object stores,
+   * that have no creation time, so that tools, like Finder, don't show epoch. Kernel gets creation time only on
+   * macOS. Default is false.
+   */
+  btimeFromMtime?: boolean
+  /**
+   * Creation time, in milliseconds since epoch, reported for inodes, which JS side gives with `btime` 0. This takes
+   * precedence over `btimeFromMtime`. Default is none.
+   */
+  defaultBtimeMs?: number
+  /**
+   * Makes every write durable before it is replied, by calling flush, and, with `Fsync`, also fsync (with datasync
+   * flag) of JS side, after write callback. Errors of these are returned by write. `Flush` needs flush callback,
+   * and factory fails without it. Default is `None`, when data is persisted only by explicit flush and fsync.
+   */
+  durabilityTier?: DurabilityTier
+  /**
+   * Way, in which kernel updates atime of files on reads, sending it to JS side in setattr. With `Never` (`noatime`), reads
+   * don't update atime at all, which suits read-only and read-mostly mounts, and with `Strict` (`strictatime`),
+   * every read updates it. Default is mount's default, which usually is `Relative` (`relatime`).
+   */
+  atime?: AtimeMode
+  /**
+   * Path of FUSE device node, for example, in a container, where device is given at a custom path. With it, given
+   * device is opened, and filesystem is mounted directly with mount(2), which needs `CAP_SYS_ADMIN`, as `fusermount`
+   * can't be used with a custom device. Factory fails, when device can't be opened, or mounting fails. Linux only.
+   * Default is `/dev/fuse`, opened by `fuser`.
+   */
+  fuseDevicePath?: string
+  /**
+   * Maximum number of operations, dispatched to JS side at the same time. Operation, coming above the limit, waits
+   * for up to 30 seconds, and is then replied with `EAGAIN`. Forget, destroy and other calls without reply aren't
+   * limited. Default is no limit.
+   */
+  maxInFlight?: number
+  /**
+   * Time in milliseconds, after which an operation, that JS side hasn't yet replied to, is reported to `onError`
+   * with kind `"slow-op"`, giving early warning before the operation times out (see `jsReplyTimeoutMs`). Default
+   * is no warnings.
+   */
+  slowOpWarningMs?: number
+  /**
+   * Time in milliseconds, for which an operation waits for reply of JS side, before it is replied with `EIO`.
+   * Reads, writes and listings, that time out, are then passed to `abort`. Default is 30 seconds.
+   */
+  jsReplyTimeoutMs?: number
+  /**
+   * Readahead in bytes, set in init. It can't be bigger than kernel's, which is given to init callback in
+   * `KernelLimits`. Default is kernel's.
+   */
+  maxReadahead?: number
+}
+
 export type NewEntryOrErr =
   | { type: 'Entry', field0: MkNodResult }
-  | { type: 'Err', field0: number }
+  | { type: 'Err', field0: FsError }
+
+/**
+ * Returns current wall-clock time in milliseconds since epoch, as used for times in `FileAttr`. This is handy for
+ * setting times to now, for example, in setattr.
+ */
+export declare function nowMillis(): number
+
+export type OffsetOrErr =
+  | { type: 'Offset', field0: number }
+  | { type: 'BigOffset', field0: bigint }
+  | { type: 'Err', field0: FsError }
 
 /**
  * opendir [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
@@ -204,34 +993,174 @@ export type NewEntryOrErr =
 export type OpenDirOpCB =
   ((err: Error | null, arg0: number, arg1: number) => Promise<ParamsOfOpenedOrErr>)
 
-/** open [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust crate. */
+/**
+ * open [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust crate.
+ *
+ * Arguments:
+ * 1. ino
+ * 2. flags
+ * 3. truncate flag - true, when file should be truncated within this open. This happens only with atomic
+ *    truncation (see `KernelCaps.atomicOTrunc`), otherwise, truncation comes as a separate setattr/truncate.
+ *
+ * Flags never have both `O_CREAT` and `O_EXCL`, as exclusive creation is done by create callback. Open with them is
+ * replied with `EINVAL` without calling JS side.
+ */
 export type OpenOpCB =
-  ((err: Error | null, arg0: number, arg1: number) => Promise<ParamsOfOpenedOrErr>)
+  ((err: Error | null, arg0: number, arg1: number, arg2: boolean) => Promise<ParamsOfOpenedOrErr>)
 
 export interface ParamsOfOpened {
   fh: number
-  flags: number
+  /** Raw `FOPEN_*` flags. Named flags below, that are true, are added to these, and false ones don't clear them. */
+  flags?: number
+  /**
+   * Bypass kernel page cache for this open, so that every application read reaches read callback. This is needed
+   * for files, which content changes on the other side, like live/streaming files.
+   */
+  directIo?: boolean
+  /** Don't invalidate kernel page cache of the file on this open. */
+  keepCache?: boolean
+  /** File is not seekable. */
+  nonseekable?: boolean
+  /**
+   * For opendir only. Lets kernel cache listing of directory, and, with `keepCache`, keep it across opens, so that
+   * repeated listing doesn't call readdir. Dropped, when kernel's FUSE protocol is older than 7.28.
+   */
+  cacheDir?: boolean
 }
 
 export type ParamsOfOpenedOrErr =
   | { type: 'Params', field0: ParamsOfOpened }
-  | { type: 'Err', field0: number }
+  | { type: 'Err', field0: FsError }
+
+export interface PartialDirListing {
+  lst: Array<DirEntry>
+  token: string
+}
+
+/**
+ * Value of `FileAttr.perm`, with which JS side leaves permission bits to mount's settings, for filesystems, that
+ * don't keep permissions of each inode.
+ */
+export const PERM_OF_MOUNT: number
+
+/** Poll events, named here, as values of `POLL*` constants differ between platforms. */
+export interface PollEvents {
+  /** `POLLIN` */
+  readable: boolean
+  /** `POLLOUT` */
+  writable: boolean
+  /** `POLLERR` */
+  error: boolean
+  /** `POLLHUP` */
+  hangUp: boolean
+}
+
+/**
+ * poll [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
+ * crate. Absent this callback, poll is replied with `ENOSYS`, and kernel treats file as always ready.
+ *
+ * Arguments:
+ * 1. ino
+ * 2. fh
+ * 3. requested events
+ *
+ * Should return filesystem error code or events, that are ready. When nothing is ready, kernel waits for
+ * `FSMounter.notifyPoll(fh)`, after which it polls again.
+ */
+export type PollOpCB =
+  ((err: Error | null, arg0: number, arg1: number, arg2: PollEvents) => Promise<PollOrErr>)
+
+export type PollOrErr =
+  | { type: 'Events', field0: PollEvents }
+  | { type: 'Err', field0: FsError }
+
+/**
+ * This is called before the first write to an open file handle, letting JS side prepare for writes, like copy-up of
+ * a backing object in overlay filesystem, or allocation of space. Error code fails the write, before its data is
+ * passed, and preparation is retried with the next write. After success, it isn't called again for this handle.
+ *
+ * Arguments:
+ * 1. ino
+ * 2. fh
+ */
+export type PrepareWriteCB =
+  ((err: Error | null, arg0: number, arg1: number) => Promise<number>)
+
+/**
+ * Returns umask of this process.
+ * There is no call to only read umask, hence, it is set and immediately restored.
+ */
+export declare function processUmask(): number
 
 export interface ReadArgs {
   offset: number
   size: number
   flags: number
   lockOwner?: number
+  /** Request id, with which this read may be aborted, see [`AbortOpCB`]. */
+  reqId: number
+  /**
+   * Index of read pool's buffer (see `InitInfo`), into which data may be placed, instead of returning a new buffer.
+   * Present, when pool is set in mount config, and has a free buffer, big enough for this read.
+   */
+  slot?: number
 }
 
 /**
  * readdir [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
  * crate.
+ *
+ * Arguments:
+ * 1. ino
+ * 2. fh
+ * 3. offset
+ * 4. request id, with which this readdir may be aborted, see [`AbortOpCB`].
+ * 5. continuation token, given in the previous partial reply for this handle, when kernel continues right after
+ *    it. Backends, that paginate with opaque cursors, should continue with the token instead of the offset.
+ *
+ * Reply may be a partial listing with a continuation token. Entries, that don't fit into kernel's buffer, are kept
+ * on Rust side, and token is passed to JS side, only after they are sent.
+ *
+ * Entries "." and ".." are sent as directories with inodes of this directory and of its parent, as parent was seen
+ * in lookups. Inode of ".." from JS side is used only, when parent isn't known. When the first page of a listing,
+ * read at offset 0, has no "." or "..", missing ones are added before other entries. These take the last two
+ * offsets below 2^63, and kernel's continuation after them comes to JS side with offset 0.
  */
 export type ReadDirOpCB =
-  ((err: Error | null, arg0: number, arg1: number, arg2: number) => Promise<DirListing>)
+  ((err: Error | null, arg0: number, arg1: number, arg2: number, arg3: number, arg4?: string | undefined | null) => Promise<DirListing>)
+
+/**
+ * readdirplus [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
+ * crate. When this callback is given, kernel is asked in init to use readdirplus instead of readdir, and when
+ * kernel denies it, `"readdirplus"` is listed among denied capabilities.
+ *
+ * Arguments:
+ * 1. ino
+ * 2. fh
+ * 3. offset
+ * 4. request id, with which this readdirplus may be aborted, see [`AbortOpCB`].
+ */
+export type ReadDirPlusOpCB =
+  ((err: Error | null, arg0: number, arg1: number, arg2: number, arg3: number) => Promise<DirListingPlus>)
+
+/**
+ * readlink [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
+ * crate. Absent this callback, readlink is replied with `ENOSYS`.
+ *
+ * Arguments:
+ * 1. ino
+ *
+ * Should return filesystem error code or target of the link. Target that doesn't fit `PATH_MAX` is replied with
+ * `ENAMETOOLONG`.
+ */
+export type ReadlinkOpCB =
+  ((err: Error | null, arg: number) => Promise<LinkTargetOrErr>)
 
-/** read [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust crate. */
+/**
+ * read [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust crate.
+ *
+ * Read may be aborted, see [`AbortOpCB`]. Reads of zero bytes are replied without calling JS side.
+ */
 export type ReadOpCB =
   ((err: Error | null, arg0: number, arg1: number, arg2: ReadArgs) => Promise<BufferOrErr>)
 
@@ -248,12 +1177,23 @@ export interface ReleaseArgs {
 export type ReleaseDirOpCB =
   ((err: Error | null, arg0: number, arg1: number, arg2: number) => Promise<number>)
 
+/**
+ * This is called with ino, when kernel forgets all lookups of it, as counted by this module. With this callback
+ * given, forget callback is not called. Like forget, it is queued to JS side without waiting.
+ */
+export type ReleaseInodeCB =
+  ((err: Error | null, arg: number) => any)
+
 /**
  * release [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
  * crate.
+ *
+ * Kernel ignores result of release, as close(2) has already returned by then. Hence, this returns nothing, and
+ * errors, like failure to save file's content, should be returned from flush, which is called before release, and
+ * which result close(2) does return.
  */
 export type ReleaseOpCB =
-  ((err: Error | null, arg0: number, arg1: number, arg2: ReleaseArgs) => Promise<number>)
+  ((err: Error | null, arg0: number, arg1: number, arg2: ReleaseArgs) => Promise<undefined>)
 
 /**
  * removexattr [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
@@ -266,6 +1206,22 @@ export type ReleaseOpCB =
 export type RemoveXAttrOpCB =
   ((err: Error | null, arg0: number, arg1: string) => Promise<number>)
 
+export interface RenameArgs {
+  /** Raw `RENAME_*` flags. Named flags below are taken from these. */
+  flags: number
+  /** `RENAME_NOREPLACE`: rename should fail with `EEXIST`, when new name exists. */
+  noReplace: boolean
+  /** `RENAME_EXCHANGE`: both names exist, and inodes should be swapped between them atomically. */
+  exchange: boolean
+  /**
+   * `RENAME_WHITEOUT`: source name should be left with a whiteout, i.e. a character device with device number
+   * (0, 0), created in the same atomic step, in which file is moved. Overlay filesystems ask for this, when this
+   * filesystem is their upper layer, to hide files of lower layers. Filesystem, that can't do this, should return
+   * `EINVAL`.
+   */
+  whiteout: boolean
+}
+
 /**
  * rename [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
  * crate.
@@ -275,9 +1231,10 @@ export type RemoveXAttrOpCB =
  * 2. name of a child to move
  * 3. newparent inode id
  * 4. newname of a child in new parent
+ * 5. flags of rename, see [`RenameArgs`]
  */
 export type RenameOpCB =
-  ((err: Error | null, arg0: number, arg1: string, arg2: number, arg3: string, arg4: number) => Promise<number>)
+  ((err: Error | null, arg0: number, arg1: string, arg2: number, arg3: string, arg4: RenameArgs) => Promise<number>)
 
 /**
  * rmdir [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
@@ -297,12 +1254,86 @@ export type RmDirOpCB =
  * Arguments:
  * 1. ino
  * 2. fh
+ * 3. changes, with only changed attributes present
  *
  * Should return filesystem error code or updated attributes data.
+ *
+ * When only size changes, and truncate callback is given, truncate is called instead.
  */
 export type SetAttrOpCB =
   ((err: Error | null, arg0: number, arg1: number | undefined | null, arg2: AttrChanges) => Promise<FileAttrOrErr>)
 
+/** Read data, given as segments, with zeros between them. */
+export interface SparseData {
+  /** Length of read data, which is less than requested size, when read goes past the end of file. */
+  len: number
+  /** Segments with offsets in file. Parts of segments outside of read range are ignored. */
+  segments: Array<DataSegment>
+}
+
+/**
+ * statfs [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
+ * crate. Absent this callback, zero counts of blocks and files are reported.
+ *
+ * Note that `f_fsid` can't be set here, as FUSE protocol's statfs reply has no such field. Kernel derives it from
+ * device number of the mount, which is unique among current mounts, but isn't stable across remounts. When stable
+ * id is needed for NFS re-export, set it with `fsid=` option in exports(5).
+ *
+ * Arguments:
+ * 1. ino
+ *
+ * Should return filesystem error code or filesystem statistics.
+ */
+export type StatfsOpCB =
+  ((err: Error | null, arg: number) => Promise<StatfsOrErr>)
+
+export type StatfsOrErr =
+  | { type: 'Stats', field0: StatfsResult }
+  | { type: 'Err', field0: FsError }
+
+export interface StatfsResult {
+  blocks: number
+  bfree: number
+  bavail: number
+  files: number
+  ffree: number
+  /** Block size. Default is 4096. */
+  bsize?: number
+  /** Fragment size, i.e. unit of blocks' counts. Default is the one set in mount config, or block size. */
+  frsize?: number
+}
+
+/**
+ * symlink [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
+ * crate. Absent this callback, symlink is replied with `EPERM`.
+ *
+ * Arguments:
+ * 1. parent inode id
+ * 2. name of a new link
+ * 3. target of the link, which is already checked to fit `PATH_MAX`
+ *
+ * Should return filesystem error code or new entry with attributes of the link, i.e. with `SymLink` kind, usually
+ * with `0o777` permissions, and with size being length of target in bytes. Entry of another kind is replied with
+ * `EIO`.
+ */
+export type SymlinkOpCB =
+  ((err: Error | null, arg0: number, arg1: string, arg2: string) => Promise<NewEntryOrErr>)
+
+/**
+ * Truncation of a file, i.e. a setattr, which changes only size.
+ * Absent this callback, truncation comes to setattr, like any other attribute change.
+ *
+ * Arguments:
+ * 1. ino
+ * 2. fh
+ * 3. new size
+ *
+ * Per POSIX, successful truncate must update file's mtime and ctime, even when size stays the same.
+ * Should return filesystem error code or updated attributes data.
+ */
+export type TruncateOpCB =
+  ((err: Error | null, arg0: number, arg1: number | undefined | null, arg2: number) => Promise<FileAttrOrErr>)
+
 /**
  * unlink [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
  * crate.
@@ -314,7 +1345,68 @@ export type SetAttrOpCB =
 export type UnlinkOpCB =
   ((err: Error | null, arg0: number, arg1: string) => Promise<number>)
 
+export interface WriteArgs {
+  offset: number
+  /** Data of write, when it is not placed in a slot of write buffers pool. */
+  data?: Buffer
+  /** Index of a buffer in write buffers pool, that contains data of this write. */
+  slot?: number
+  /** Length of data. */
+  len: number
+  writeFlags: number
+  flags: number
+  lockOwner?: number
+  /**
+   * File is open with `O_APPEND`, and data should be appended, whatever offset is. With mount's `enforceAppend`,
+   * offset is already set to the end of file.
+   */
+  append: boolean
+  /** Request id, with which this write may be aborted, see [`AbortOpCB`]. */
+  reqId: number
+}
+
+/**
+ * write [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
+ * crate. Absent this callback, write is replied with `ENOSYS`.
+ *
+ * Arguments:
+ * 1. ino
+ * 2. fh
+ * 3. other arguments, with data either in a buffer, or in a slot of write buffers pool (see `InitInfo`)
+ *
+ * Should return filesystem error code or number of written bytes.
+ *
+ * Writes of zero bytes are replied without calling JS side. Write to a file, opened with `O_SYNC` or `O_DSYNC`, is
+ * replied only after it is followed by fsync callback, with datasync flag for `O_DSYNC`.
+ */
+export type WriteOpCB =
+  ((err: Error | null, arg0: number, arg1: number, arg2: WriteArgs) => Promise<WrittenOrErr>)
+
+export type WrittenOrErr =
+  | { type: 'Written', field0: number }
+  | { type: 'WrittenWithAttr', field0: WrittenWithAttr }
+  | { type: 'Err', field0: FsError }
+
+/**
+ * Result of write with updated attributes, like new size and mtime. With `MountConfig.getattrCoalesceMs`, these
+ * answer getattr, that follows write, without calling JS side. Kernel itself drops cached attributes after writes.
+ */
+export interface WrittenWithAttr {
+  written: number
+  attr: FileAttr
+}
+
 export type XAttrBytesOrErr =
   | { type: 'Data', field0: Buffer }
   | { type: 'Size', field0: number }
-  | { type: 'Err', field0: number }
+  | { type: 'Err', field0: FsError }
+
+/** Extended times of macOS. Values are milliseconds since epoch, like other times in [`FileAttr`]. */
+export interface XTimes {
+  bkuptime: number
+  crtime: number
+}
+
+export type XTimesOrErr =
+  | { type: 'Times', field0: XTimes }
+  | { type: 'Err', field0: FsError }
@@ -576,6 +576,19 @@ if (!nativeBinding) {
 }
 
 module.exports = nativeBinding
+module.exports.Errno = nativeBinding.Errno
 module.exports.FSMounter = nativeBinding.FSMounter
 module.exports.JsFSMounter = nativeBinding.JsFSMounter
+module.exports.PosixError = nativeBinding.PosixError
+module.exports.AtimeMode = nativeBinding.AtimeMode
+module.exports.blockSizeForStorage = nativeBinding.blockSizeForStorage
+module.exports.cleanupMountPoint = nativeBinding.cleanupMountPoint
+module.exports.currentGid = nativeBinding.currentGid
+module.exports.currentUid = nativeBinding.currentUid
+module.exports.DurabilityTier = nativeBinding.DurabilityTier
+module.exports.fuseErrnoToString = nativeBinding.fuseErrnoToString
 module.exports.InodeKind = nativeBinding.InodeKind
+module.exports.mkdtemp = nativeBinding.mkdtemp
+module.exports.nowMillis = nativeBinding.nowMillis
+module.exports.PERM_OF_MOUNT = nativeBinding.PERM_OF_MOUNT
+module.exports.processUmask = nativeBinding.processUmask
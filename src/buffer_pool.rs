@@ -0,0 +1,143 @@
+// Copyright(c) 2026 3NSoft Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{ptr, slice, sync::{Arc, Mutex}};
+
+use napi::{bindgen_prelude::*, sys};
+
+/// Pre-allocated memory slots for data of writes or reads. Slots are given to js side once, as external buffers, and
+/// each write passes only index of a slot, into which its data is copied, while each read passes index of a slot, into
+/// which js side places read data. Slot is used by one operation at a time, and is returned to the pool, when js side
+/// replies to the operation.
+pub struct BufferPool {
+  slots: Vec<*mut u8>,
+  slot_size: usize,
+  free: Mutex<Vec<u32>>,
+}
+
+// Memory of a slot is written only by a thread, that has taken slot from the free list, and it is read by js side
+// only until slot is given back.
+unsafe impl Send for BufferPool {}
+unsafe impl Sync for BufferPool {}
+
+impl BufferPool {
+
+  pub fn new(num_of_slots: u32, slot_size: usize) -> Self {
+    let slots = (0..num_of_slots)
+    .map(|_| Box::into_raw(vec![0u8; slot_size].into_boxed_slice()).cast::<u8>())
+    .collect();
+    BufferPool { slots, slot_size, free: Mutex::new((0..num_of_slots).rev().collect()) }
+  }
+
+  pub fn slot_size(&self) -> usize {
+    self.slot_size
+  }
+
+  /// Copies data into a free slot, returning its index, or `None`, when data is too big, or all slots are in use.
+  pub fn take(&self, data: &[u8]) -> Option<u32> {
+    if data.len() > self.slot_size {
+      return None;
+    }
+    let slot = self.free.lock().unwrap().pop()?;
+    unsafe {
+      ptr::copy_nonoverlapping(data.as_ptr(), self.slots[slot as usize], data.len());
+    }
+    Some(slot)
+  }
+
+  /// Takes a free slot for data, that js side will place into it.
+  pub fn take_empty(&self) -> Option<u32> {
+    self.free.lock().unwrap().pop()
+  }
+
+  /// Returns data, placed by js side at the start of a taken slot, or `None`, when length is bigger than slot.
+  pub fn data_in(&self, slot: u32, len: usize) -> Option<&[u8]> {
+    let slot = *self.slots.get(slot as usize)?;
+    (len <= self.slot_size).then(|| unsafe { slice::from_raw_parts(slot, len) })
+  }
+
+  pub fn give_back(&self, slot: u32) {
+    self.free.lock().unwrap().push(slot);
+  }
+
+}
+
+impl Drop for BufferPool {
+  fn drop(&mut self) {
+    for &slot in &self.slots {
+      unsafe {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(slot, self.slot_size)));
+      }
+    }
+  }
+}
+
+/// Slots of [`BufferPool`], that become an array of buffers on js side. Buffers keep pool alive.
+///
+/// This should be converted to js only once per pool, as node doesn't allow different buffers over the same memory.
+pub struct PoolBuffers(pub Arc<BufferPool>);
+
+impl TypeName for PoolBuffers {
+  fn type_name() -> &'static str {
+    "Array<Buffer>"
+  }
+
+  fn value_type() -> ValueType {
+    ValueType::Object
+  }
+}
+
+impl ToNapiValue for PoolBuffers {
+  unsafe fn to_napi_value(raw_env: sys::napi_env, val: Self) -> Result<sys::napi_value> {
+    let env = Env::from_raw(raw_env);
+    let pool = val.0;
+    let mut buffers = env.create_array(pool.slots.len() as u32)?;
+    for (i, &slot) in pool.slots.iter().enumerate() {
+      let buffer = unsafe {
+        BufferSlice::from_external(&env, slot, pool.slot_size, pool.clone(), |_, _| {})?
+      };
+      buffers.set(i as u32, &buffer)?;
+    }
+    Ok(buffers.raw())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::*;
+
+  #[test]
+  fn reuses_slots_given_back() {
+    let pool = BufferPool::new(2, 8);
+    let first = pool.take(b"abc").unwrap();
+    let second = pool.take_empty().unwrap();
+    assert_ne!(first, second);
+    assert!(pool.take_empty().is_none());
+    assert_eq!(pool.data_in(first, 3).unwrap(), b"abc");
+    pool.give_back(first);
+    assert_eq!(pool.take_empty(), Some(first));
+  }
+
+  #[test]
+  fn refuses_data_bigger_than_slot() {
+    let pool = BufferPool::new(1, 4);
+    assert!(pool.take(b"too long").is_none());
+    let slot = pool.take_empty().unwrap();
+    assert!(pool.data_in(slot, 5).is_none());
+    assert!(pool.data_in(7, 1).is_none());
+  }
+
+}
@@ -0,0 +1,151 @@
+// Copyright(c) 2026 3NSoft Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{io, path::Path};
+
+use fuser::{BackgroundSession, Config, Filesystem};
+
+/// Mounts with a given FUSE device node, like a custom one, as [`fuser`] always uses `/dev/fuse`. Device is opened
+/// here, and filesystem is mounted with mount(2), which needs `CAP_SYS_ADMIN`, as no setuid helper like `fusermount`
+/// is given a custom device. Returned session has no mount of its own, and it should be unmounted with [`unmount`].
+#[cfg(target_os = "linux")]
+pub fn spawn_mount_with_device<FS: Filesystem + Send + 'static>(
+  fs_impl: FS, device: &Path, mount_root: &Path, cfg: &Config,
+) -> io::Result<BackgroundSession> {
+  use std::{ffi::CString, fs::{self, OpenOptions}, os::{fd::{AsRawFd, OwnedFd}, unix::{ffi::OsStrExt, fs::PermissionsExt}}};
+  use fuser::{MountOption, Session};
+
+  let dev_file = OpenOptions::new().read(true).write(true).open(device)
+  .map_err(|err| io::Error::new(err.kind(), format!("Can't open FUSE device {}: {err}", device.display())))?;
+  let root_mode = fs::metadata(mount_root)?.permissions().mode() & libc::S_IFMT;
+  let mut data = format!(
+    "fd={},rootmode={root_mode:o},user_id={},group_id={}",
+    dev_file.as_raw_fd(), unsafe { libc::getuid() }, unsafe { libc::getgid() }
+  );
+  // fuser mounts with nodev and nosuid, unless told otherwise, and so do we
+  let mut flags = libc::MS_NODEV | libc::MS_NOSUID;
+  let mut fs_type = "fuse".to_string();
+  let mut source = device.to_string_lossy().into_owned();
+  for opt in &cfg.mount_options {
+    match opt {
+      MountOption::FSName(name) => source = name.clone(),
+      MountOption::Subtype(subtype) => fs_type = format!("fuse.{subtype}"),
+      MountOption::RO => flags |= libc::MS_RDONLY,
+      MountOption::RW => flags &= !libc::MS_RDONLY,
+      MountOption::Dev => flags &= !libc::MS_NODEV,
+      MountOption::NoDev => flags |= libc::MS_NODEV,
+      MountOption::Suid => flags &= !libc::MS_NOSUID,
+      MountOption::NoSuid => flags |= libc::MS_NOSUID,
+      MountOption::Exec => flags &= !libc::MS_NOEXEC,
+      MountOption::NoExec => flags |= libc::MS_NOEXEC,
+      MountOption::Atime => flags &= !libc::MS_NOATIME,
+      MountOption::NoAtime => flags |= libc::MS_NOATIME,
+      MountOption::Sync => flags |= libc::MS_SYNCHRONOUS,
+      MountOption::Async => flags &= !libc::MS_SYNCHRONOUS,
+      MountOption::DirSync => flags |= libc::MS_DIRSYNC,
+      MountOption::DefaultPermissions => data.push_str(",default_permissions"),
+      MountOption::AutoUnmount => return Err(io::Error::new(
+        io::ErrorKind::Unsupported, "auto_unmount needs fusermount, which can't use a custom FUSE device"
+      )),
+      MountOption::CUSTOM(opt) => match custom_option_flag(opt) {
+        Some(flag) => flags |= flag,
+        None => {
+          data.push(',');
+          data.push_str(opt);
+        }
+      },
+    }
+  }
+
+  let to_c_str = |s: &[u8]| CString::new(s).map_err(|_| io::Error::from(io::ErrorKind::InvalidInput));
+  let source = to_c_str(source.as_bytes())?;
+  let target = to_c_str(mount_root.as_os_str().as_bytes())?;
+  let fs_type = to_c_str(fs_type.as_bytes())?;
+  let data = to_c_str(data.as_bytes())?;
+  let res = unsafe {
+    libc::mount(source.as_ptr(), target.as_ptr(), fs_type.as_ptr(), flags, data.as_ptr().cast())
+  };
+  if res != 0 {
+    return Err(io::Error::last_os_error());
+  }
+
+  // kernel sends init right after mount, and session's handshake reads it
+  match Session::from_fd(fs_impl, OwnedFd::from(dev_file), cfg.acl, cfg.clone()).and_then(|s| s.spawn()) {
+    Ok(session) => Ok(session),
+    Err(err) => {
+      let _ = unmount(mount_root, true);
+      Err(err)
+    }
+  }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn spawn_mount_with_device<FS: Filesystem + Send + 'static>(
+  _fs_impl: FS, _device: &Path, _mount_root: &Path, _cfg: &Config,
+) -> io::Result<BackgroundSession> {
+  Err(io::Error::new(io::ErrorKind::Unsupported, "custom FUSE device is supported only on Linux"))
+}
+
+/// Default FUSE device.
+pub const FUSE_DEVICE: &str = "/dev/fuse";
+
+/// Tells, if filesystem should be mounted here, rather than by [`fuser`], which, mounting directly as root, puts custom
+/// options into mount's data, where kernel's FUSE doesn't understand options like `relatime` and `strictatime`, while
+/// `fusermount`, used by non-root, turns these into mount flags.
+#[cfg(target_os = "linux")]
+pub fn needs_own_mount(cfg: &Config) -> bool {
+  use fuser::MountOption;
+  (unsafe { libc::geteuid() } == 0)
+  && cfg.mount_options.iter().any(|opt| matches!(opt, MountOption::CUSTOM(opt) if custom_option_flag(opt).is_some()))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn needs_own_mount(_cfg: &Config) -> bool {
+  false
+}
+
+/// Mount flags, that come as custom options, but aren't understood by FUSE in mount's data.
+#[cfg(target_os = "linux")]
+fn custom_option_flag(opt: &str) -> Option<libc::c_ulong> {
+  match opt {
+    "strictatime" => Some(libc::MS_STRICTATIME),
+    "relatime" => Some(libc::MS_RELATIME),
+    "nodiratime" => Some(libc::MS_NODIRATIME),
+    "lazytime" => Some(libc::MS_LAZYTIME),
+    "noexec" => Some(libc::MS_NOEXEC),
+    "nosuid" => Some(libc::MS_NOSUID),
+    "nodev" => Some(libc::MS_NODEV),
+    "sync" => Some(libc::MS_SYNCHRONOUS),
+    "dirsync" => Some(libc::MS_DIRSYNC),
+    _ => None,
+  }
+}
+
+/// Unmounts filesystem, mounted by [`spawn_mount_with_device`]. Lazy unmount detaches filesystem, leaving it to live,
+/// while there are open files.
+pub fn unmount(mount_root: &Path, lazy: bool) -> io::Result<()> {
+  use std::{ffi::CString, os::unix::ffi::OsStrExt};
+  let target = CString::new(mount_root.as_os_str().as_bytes())
+  .map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))?;
+  #[cfg(target_os = "linux")]
+  let res = unsafe { libc::umount2(target.as_ptr(), if lazy { libc::MNT_DETACH } else { 0 }) };
+  #[cfg(not(target_os = "linux"))]
+  let res = unsafe { libc::unmount(target.as_ptr(), if lazy { libc::MNT_FORCE } else { 0 }) };
+  if res == 0 {
+    Ok(())
+  } else {
+    Err(io::Error::last_os_error())
+  }
+}
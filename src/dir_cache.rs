@@ -0,0 +1,175 @@
+// Copyright(c) 2026 3NSoft Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{collections::HashMap, sync::Mutex};
+
+use fuser::FileType;
+
+/// Entry of a directory listing, as it is given to kernel.
+#[derive(Clone)]
+pub struct CachedDirEntry {
+  pub ino: u64,
+  /// Offset of the next entry.
+  pub offset: u64,
+  pub kind: FileType,
+  pub name: String,
+}
+
+/// Listing of a directory, collected from consecutive readdir replies of js side.
+#[derive(Default)]
+struct Listing {
+  entries: Vec<CachedDirEntry>,
+  /// Set, when js side has replied with an empty page, i.e. there are no entries after collected ones.
+  complete: bool,
+}
+
+/// Directory listings, keyed by (ino, fh), kept while directory handle is open, so that repeated reads of an open
+/// directory, like after rewinddir(3), don't call js side again. Listings of a directory are dropped, when entries
+/// of it are changed through this filesystem.
+#[derive(Default)]
+pub struct DirCache(Mutex<HashMap<(u64, u64), Listing>>);
+
+impl DirCache {
+
+  /// Returns cached entries, starting at a given offset, or `None`, when these should be read from js side.
+  pub fn get(&self, ino: u64, fh: u64, offset: u64) -> Option<Vec<CachedDirEntry>> {
+    let listings = self.0.lock().unwrap();
+    let listing = listings.get(&(ino, fh))?;
+    let start = start_index(&listing.entries, offset)?;
+    if (start < listing.entries.len()) || listing.complete {
+      Some(listing.entries[start..].to_vec())
+    } else {
+      None
+    }
+  }
+
+  /// Adds a page of entries, read from js side at a given offset. Page, that doesn't continue collected entries, is
+  /// not cached.
+  pub fn add(&self, ino: u64, fh: u64, offset: u64, page: &[CachedDirEntry]) {
+    let mut listings = self.0.lock().unwrap();
+    let listing = listings.entry((ino, fh)).or_default();
+    if listing.complete || (start_index(&listing.entries, offset) != Some(listing.entries.len())) {
+      return;
+    }
+    if page.is_empty() {
+      listing.complete = true;
+    } else {
+      listing.entries.extend_from_slice(page);
+    }
+  }
+
+  pub fn release(&self, ino: u64, fh: u64) {
+    self.0.lock().unwrap().remove(&(ino, fh));
+  }
+
+  /// Drops listings of a directory, in which entries have changed.
+  pub fn invalidate(&self, ino: u64) {
+    self.0.lock().unwrap().retain(|(cached_ino, _), _| *cached_ino != ino);
+  }
+
+}
+
+/// Finds index of entry, that comes at a given offset, i.e. after entry with this offset as next one's.
+fn start_index(entries: &[CachedDirEntry], offset: u64) -> Option<usize> {
+  if offset == 0 {
+    Some(0)
+  } else {
+    entries.iter().position(|entry| entry.offset == offset).map(|i| i + 1)
+  }
+}
+
+/// Place in a listing of an open directory, at which js side should continue with its opaque token.
+struct Cursor {
+  /// Offset, with which kernel is expected to continue.
+  offset: u64,
+  token: String,
+  /// Entries of a partial reply, that haven't fit into kernel's buffer, and should go before js side's next page.
+  pending: Vec<CachedDirEntry>,
+}
+
+/// Continuation tokens of partial readdir replies, keyed by (ino, fh), for backends, that paginate with cursors
+/// instead of offsets.
+#[derive(Default)]
+pub struct DirCursors(Mutex<HashMap<(u64, u64), Cursor>>);
+
+impl DirCursors {
+
+  /// Takes token and pending entries, when kernel continues at the expected offset.
+  pub fn take(&self, ino: u64, fh: u64, offset: u64) -> Option<(String, Vec<CachedDirEntry>)> {
+    let mut cursors = self.0.lock().unwrap();
+    if cursors.get(&(ino, fh))?.offset != offset {
+      return None;
+    }
+    cursors.remove(&(ino, fh)).map(|cursor| (cursor.token, cursor.pending))
+  }
+
+  pub fn put(&self, ino: u64, fh: u64, offset: u64, token: String, pending: Vec<CachedDirEntry>) {
+    self.0.lock().unwrap().insert((ino, fh), Cursor { offset, token, pending });
+  }
+
+  pub fn release(&self, ino: u64, fh: u64) {
+    self.0.lock().unwrap().remove(&(ino, fh));
+  }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::*;
+
+  fn entry(ino: u64, offset: u64) -> CachedDirEntry {
+    CachedDirEntry { ino, offset, kind: FileType::RegularFile, name: format!("f{ino}") }
+  }
+
+  #[test]
+  fn gives_collected_pages_of_open_directory() {
+    let cache = DirCache::default();
+    assert!(cache.get(1, 5, 0).is_none());
+    cache.add(1, 5, 0, &[entry(2, 1), entry(3, 2)]);
+    cache.add(1, 5, 2, &[entry(4, 3)]);
+    assert_eq!(cache.get(1, 5, 0).unwrap().len(), 3);
+    assert_eq!(cache.get(1, 5, 2).unwrap()[0].ino, 4);
+    // end of listing isn't known yet
+    assert!(cache.get(1, 5, 3).is_none());
+    cache.add(1, 5, 3, &[]);
+    assert!(cache.get(1, 5, 3).unwrap().is_empty());
+    assert!(cache.get(1, 6, 0).is_none());
+  }
+
+  #[test]
+  fn ignores_pages_that_do_not_continue_listing() {
+    let cache = DirCache::default();
+    cache.add(1, 5, 0, &[entry(2, 1)]);
+    cache.add(1, 5, 7, &[entry(9, 8)]);
+    assert_eq!(cache.get(1, 5, 0).unwrap().len(), 1);
+    assert!(cache.get(1, 5, 7).is_none());
+  }
+
+  #[test]
+  fn drops_listings_on_release_and_invalidation() {
+    let cache = DirCache::default();
+    cache.add(1, 5, 0, &[entry(2, 1)]);
+    cache.add(1, 6, 0, &[entry(2, 1)]);
+    cache.add(7, 8, 0, &[entry(9, 1)]);
+    cache.release(1, 5);
+    assert!(cache.get(1, 5, 0).is_none());
+    assert!(cache.get(1, 6, 0).is_some());
+    cache.invalidate(1);
+    assert!(cache.get(1, 6, 0).is_none());
+    assert!(cache.get(7, 8, 0).is_some());
+  }
+
+}
@@ -0,0 +1,116 @@
+// Copyright(c) 2026 3NSoft Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{backtrace::{Backtrace, BacktraceStatus}, collections::HashMap, io, panic, sync::{LazyLock, Mutex, Once}, thread::{self, ThreadId}, time::Duration};
+
+use napi::threadsafe_function::{ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi_derive::napi;
+
+use crate::posix_errors::fuse_errno_to_string;
+
+/// Name, given by [`fuser`] to its session thread, in which filesystem's methods are invoked.
+const FUSER_THREAD_NAME: &str = "fuser-bg";
+
+/// Report of a failure in native part of the filesystem.
+#[napi(object)]
+pub struct ErrorReport {
+  /// One of `"mount"`, `"panic"`, `"session-end"`, `"unmount"`, `"fs-error"` or `"slow-op"`.
+  pub kind: String,
+  pub message: String,
+  /// Present, when backtraces are enabled with `RUST_BACKTRACE` environment variable.
+  pub backtrace: Option<String>,
+}
+
+/// This is called with reports of failures, that can't be returned to js side in any other way, like mount errors
+/// after retries, panics in filesystem's thread, and ending of session without unmount call. Errors of operations,
+/// given by js side with a message, are reported here as well, as kernel gets only their codes.
+#[napi]
+pub type ErrorReportCB = ThreadsafeFunction<ErrorReport>;
+
+impl ErrorReport {
+
+  pub fn mount(err: &io::Error) -> Self {
+    ErrorReport { kind: "mount".to_string(), message: err.to_string(), backtrace: None }
+  }
+
+  pub fn unmount(err: &io::Error) -> Self {
+    ErrorReport { kind: "unmount".to_string(), message: err.to_string(), backtrace: None }
+  }
+
+  /// Makes report about session, that ended by itself, with a given result of joining its thread.
+  pub fn session_end(fuser_thread: ThreadId, ending: io::Result<()>) -> Self {
+    if let Some(report) = PANICS.lock().unwrap().remove(&fuser_thread) {
+      return report;
+    }
+    let message = match ending {
+      Ok(()) => "Filesystem session ended without unmount call".to_string(),
+      Err(err) => format!("Filesystem session ended with error: {err}"),
+    };
+    ErrorReport { kind: "session-end".to_string(), message, backtrace: None }
+  }
+
+  /// Makes report about an error with a message, that js side has given in reply to an operation.
+  pub fn fs_error(op: &str, code: i32, message: &str) -> Self {
+    ErrorReport {
+      kind: "fs-error".to_string(),
+      message: format!("{op} failed with {}: {message}", fuse_errno_to_string(code)),
+      backtrace: None,
+    }
+  }
+
+  /// Makes report about an operation, that js side hasn't replied to for a given time.
+  pub fn slow_op(op: &str, ino: i64, elapsed: Duration) -> Self {
+    ErrorReport {
+      kind: "slow-op".to_string(),
+      message: format!("{op} of inode {ino} is still in progress after {} ms", elapsed.as_millis()),
+      backtrace: None,
+    }
+  }
+
+  pub fn send_to(self, on_error: Option<&ErrorReportCB>) {
+    if let Some(on_error) = on_error {
+      on_error.call(Ok(self), ThreadsafeFunctionCallMode::NonBlocking);
+    }
+  }
+
+}
+
+/// Panics in [`fuser`]'s thread, that are not yet reported.
+static PANICS: LazyLock<Mutex<HashMap<ThreadId, ErrorReport>>> = LazyLock::new(Default::default);
+
+static PANIC_HOOK: Once = Once::new();
+
+/// Adds to process' panic hook recording of panics in [`fuser`]'s threads, with their messages and backtraces.
+/// Previous hook is still called.
+pub fn record_fuser_panics() {
+  PANIC_HOOK.call_once(|| {
+    let prev_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+      let current = thread::current();
+      if current.name() == Some(FUSER_THREAD_NAME) {
+        let backtrace = Backtrace::capture();
+        let report = ErrorReport {
+          kind: "panic".to_string(),
+          message: info.to_string(),
+          backtrace: (backtrace.status() == BacktraceStatus::Captured).then(|| backtrace.to_string()),
+        };
+        if let Ok(mut panics) = PANICS.lock() {
+          panics.insert(current.id(), report);
+        }
+      }
+      prev_hook(info);
+    }));
+  });
+}
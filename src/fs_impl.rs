@@ -13,31 +13,418 @@
 // You should have received a copy of the GNU Lesser General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::{ffi::OsStr, io, path::Path, sync::mpsc::channel, time::{Duration, SystemTime}};
+use std::{cell::Cell, collections::{HashMap, HashSet}, ffi::OsStr, io, thread, sync::{Arc, Mutex, OnceLock, Weak, atomic::{AtomicBool, AtomicUsize, Ordering}, mpsc::{Receiver, RecvTimeoutError, channel}}, time::{Duration, SystemTime}};
 
-use fuser::{AccessFlags, BsdFileFlags, Errno, FileHandle, Filesystem, FopenFlags, Generation, INodeNo, KernelConfig, LockOwner, OpenFlags, RenameFlags, ReplyAttr, ReplyBmap, ReplyData, ReplyDirectory, ReplyEmpty, ReplyEntry, ReplyLock, ReplyOpen, ReplyStatfs, ReplyXattr, Request, TimeOrNow};
+use fuser::{AccessFlags, BsdFileFlags, Errno, FileHandle, FileType, Filesystem, FopenFlags, Generation, INodeNo, InitFlags, KernelConfig, LockOwner, Notifier, OpenFlags, PollNotifier, ReplyAttr, ReplyData, ReplyDirectory, ReplyDirectoryPlus, ReplyEmpty, ReplyEntry, ReplyOpen, ReplyStatfs, ReplyXattr, Request, TimeOrNow, Version};
+#[cfg(feature = "writable")]
+use std::{os::unix::ffi::OsStrExt, path::Path};
+#[cfg(feature = "writable")]
+use crate::mount_config::DurabilityTier;
+#[cfg(feature = "writable")]
+use fuser::{RenameFlags, ReplyCreate, WriteFlags};
+#[cfg(any(feature = "writable", feature = "extended-ops"))]
+use fuser::ReplyWrite;
+#[cfg(feature = "locking")]
+use fuser::ReplyLock;
+#[cfg(feature = "extended-ops")]
+use fuser::{CopyFileRangeFlags, IoctlFlags, PollFlags, ReplyBmap, ReplyIoctl, ReplyLseek, ReplyPoll};
 use napi::threadsafe_function::ThreadsafeFunctionCallMode;
 
-use crate::js_callbacks::*;
+use crate::{buffer_pool::{BufferPool, PoolBuffers}, dir_cache::{CachedDirEntry, DirCache, DirCursors}, error_reports::ErrorReport, in_flight::InFlightLimit, inode_kinds::InodeKinds, inode_parents::InodeParents, js_callbacks::*, lookup_counts::LookupCounts, mount_config::MountSettings, pending_forgets::PendingForgets, posix_errors::{fuse_errno_to_string, into_fuse_error}, recent_attrs::RecentAttrs};
 
 /// This keeps js functions for providing FUSE implementation that [`fuser`] mounts into OS.
-/// 
+///
 /// This has [`Filesystem`] implemented.
 /// Implemented functions are invoked in [`fuser`]'s thread.
 /// Yet, any callbacks to process returned from js side NAPI values are invoked in NAPI-RS env(ironment).
 /// Such setup adds no additional threads/runtimes.
+///
+/// Clones share the same callbacks and settings. Cloning allows to retry mounting, as [`fuser`] consumes filesystem
+/// even on failed attempt.
+#[derive(Clone)]
 pub struct CallbacksProxy {
-  cbs: CallbacksToJS,
+  cbs: Arc<CallbacksToJS>,
+  settings: Arc<MountSettings>,
+  /// Lookup counts of inodes, known to kernel. With release_inode callback, js side gets release of inodes, when
+  /// counts drop to zero, instead of forgets.
+  lookups: Arc<LookupCounts>,
+  inode_kinds: Option<Arc<InodeKinds>>,
+  /// Present, when back-to-back getattr requests are coalesced.
+  recent_attrs: Option<Arc<RecentAttrs>>,
+  write_pool: Option<Arc<BufferPool>>,
+  read_pool: Option<Arc<BufferPool>>,
+  dir_cache: Option<Arc<DirCache>>,
+  dir_cursors: Arc<DirCursors>,
+  inode_parents: Arc<InodeParents>,
+  in_flight: Arc<InFlightLimit>,
+  /// Handles, for which prepare_write of js side has succeeded.
+  prepared_fhs: Arc<Mutex<HashSet<(u64, u64)>>>,
+  /// Handles, opened with `FOPEN_DIRECT_IO`, kept, when js side has direct read or write callbacks.
+  direct_io_fhs: Arc<Mutex<HashSet<(u64, u64)>>>,
+  poll_notifiers: PollNotifiers,
+  dirty_inodes: DirtyInodes,
+  /// Forgets, queued for forget_many callback of js side.
+  pending_forgets: PendingForgets,
+  /// Sends invalidations to kernel, set, once filesystem is mounted.
+  notifier: Arc<OnceLock<Notifier>>,
+  /// Number of open file and directory handles, shared by all clones of this proxy.
+  open_handles: Arc<AtomicUsize>,
+  /// Set, when js environment is torn down, and js side can't be called anymore.
+  env_exiting: Arc<AtomicBool>,
+  /// Requested capabilities, that kernel has granted in init.
+  granted_caps: InitFlags,
+  /// Set in init, when kernel's protocol knows `FOPEN_CACHE_DIR`.
+  cache_dir_supported: bool,
 }
 
 impl CallbacksProxy {
 
-  pub fn make(cbs: CallbacksToJS) -> CallbacksProxy {
-    CallbacksProxy { cbs }
+  pub fn make(cbs: CallbacksToJS, settings: MountSettings) -> CallbacksProxy {
+    let inode_kinds = settings.check_kind_changes.then(|| Arc::new(InodeKinds::default()));
+    let recent_attrs = settings.getattr_coalescing.map(|window| Arc::new(RecentAttrs::new(window)));
+    let write_pool = (settings.write_pool_slots > 0)
+    .then(|| Arc::new(BufferPool::new(settings.write_pool_slots, POOL_SLOT_SIZE)));
+    let in_flight = Arc::new(InFlightLimit::new(settings.max_in_flight));
+    let dir_cache = settings.cache_dir_listings.then(|| Arc::new(DirCache::default()));
+    let read_pool = (settings.read_pool_slots > 0)
+    .then(|| Arc::new(BufferPool::new(settings.read_pool_slots, POOL_SLOT_SIZE)));
+    CallbacksProxy {
+      cbs: Arc::new(cbs), settings: Arc::new(settings), lookups: Arc::new(LookupCounts::default()), inode_kinds, recent_attrs,
+      write_pool, read_pool, dir_cache,
+      dir_cursors: Arc::new(DirCursors::default()), inode_parents: Arc::new(InodeParents::default()),
+      in_flight, prepared_fhs: Arc::new(Mutex::new(HashSet::new())), direct_io_fhs: Arc::new(Mutex::new(HashSet::new())),
+      poll_notifiers: Arc::new(Mutex::new(HashMap::new())), dirty_inodes: Arc::new(Mutex::new(HashSet::new())),
+      pending_forgets: PendingForgets::default(), notifier: Arc::new(OnceLock::new()), open_handles: Arc::new(AtomicUsize::new(0)),
+      env_exiting: Arc::new(AtomicBool::new(false)),
+      granted_caps: InitFlags::empty(), cache_dir_supported: false,
+    }
+  }
+
+  /// Returns caches, that mounter drops, when js side tells about changes.
+  pub fn caches(&self) -> CachesOfMount {
+    CachesOfMount {
+      inode_parents: self.inode_parents.clone(),
+      recent_attrs: self.recent_attrs.clone(),
+      dir_cache: self.dir_cache.clone(),
+    }
+  }
+
+  /// Returns statistics source for mounter.
+  pub fn stats(&self) -> StatsOfMount {
+    StatsOfMount { cbs: Arc::downgrade(&self.cbs), settings: self.settings.clone() }
+  }
+
+  /// Returns place for notifier of mounted session, shared by all clones of this proxy.
+  pub fn notifier(&self) -> Arc<OnceLock<Notifier>> {
+    self.notifier.clone()
+  }
+
+  /// Returns poll notifiers, which kernel has asked to wake, shared by all clones of this proxy.
+  pub fn poll_notifiers(&self) -> PollNotifiers {
+    self.poll_notifiers.clone()
+  }
+
+  /// Returns set of dirty inodes, shared by all clones of this proxy.
+  pub fn dirty_inodes(&self) -> DirtyInodes {
+    self.dirty_inodes.clone()
+  }
+
+  /// Notes, that data of inode is flushed or synced, when js side reports success.
+  #[cfg(feature = "writable")]
+  fn note_flushed(&self, ino: INodeNo, err_code: i32) {
+    if err_code == 0 {
+      self.dirty_inodes.lock().unwrap().remove(&ino.0);
+    }
+  }
+
+  /// Returns counter of open file and directory handles, shared by all clones of this proxy.
+  pub fn open_handles(&self) -> Arc<AtomicUsize> {
+    self.open_handles.clone()
+  }
+
+  /// Returns flag, that tells all clones of this proxy that js environment is torn down.
+  pub fn env_exiting(&self) -> Arc<AtomicBool> {
+    self.env_exiting.clone()
+  }
+
+  /// Counts an opened file handle, noting, if it bypasses kernel's page cache.
+  fn count_opened_file(&self, ino: INodeNo, fh: u64, flags: FopenFlags) {
+    self.open_handles.fetch_add(1, Ordering::Relaxed);
+    if flags.contains(FopenFlags::FOPEN_DIRECT_IO) && self.has_direct_io_cbs() {
+      self.direct_io_fhs.lock().unwrap().insert((ino.0, fh));
+    }
+  }
+
+  /// Returns coalesced attributes of an open file, when these are at hand.
+  #[cfg(any(feature = "writable", feature = "extended-ops"))]
+  fn recent_attr_of_handle(&self, ino: INodeNo, fh: FileHandle) -> Option<fuser::FileAttr> {
+    self.recent_attrs.as_ref()
+    .and_then(|recent_attrs| recent_attrs.get(ino.0, Some(fh.0)).or_else(|| recent_attrs.get(ino.0, None)))
+  }
+
+  fn has_direct_io_cbs(&self) -> bool {
+    #[cfg(feature = "writable")]
+    let has_direct_write = self.cbs.direct_write.is_some();
+    #[cfg(not(feature = "writable"))]
+    let has_direct_write = false;
+    self.cbs.direct_read.is_some() || has_direct_write
+  }
+
+  fn is_direct_io(&self, ino: INodeNo, fh: FileHandle) -> bool {
+    self.has_direct_io_cbs() && self.direct_io_fhs.lock().unwrap().contains(&(ino.0, fh.0))
+  }
+
+  /// Drops kernel's cached pages of a file past its new size, when mount is set to do so. This is called after reply
+  /// to setattr, as kernel may hold pages of inode, while waiting for it.
+  fn evict_past_size(&self, ino: INodeNo, size: Option<u64>) {
+    if !self.settings.evict_cache_on_truncate {
+      return;
+    }
+    if let Some(size) = size && let Some(notifier) = self.notifier.get() {
+      // length 0 stands for all data till the end
+      let _ = notifier.inval_inode(ino, size as i64, 0);
+    }
   }
 
+  /// Returns time, given by js side for caching of a new entry, or mount's default one.
+  #[cfg(feature = "writable")]
+  fn ttl_of_entry(&self, ttl_ms: Option<i64>) -> Duration {
+    ttl_ms.map_or(self.settings.entry_ttl, |ms| Duration::from_millis(ms.max(0) as u64))
+  }
+
+  fn count_release(&self) {
+    let _ = self.open_handles.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1));
+  }
+
+  /// Converts attributes from js side, applying mount-wide settings.
+  fn attr_to_fuse(&self, attr: FileAttr) -> fuser::FileAttr {
+    self.lookups.note_xattrs_presence(attr.ino as u64, attr.has_xattrs.unwrap_or(true));
+    let mut attr = attr.into_fuse();
+    attr.flags |= self.settings.file_flags;
+    self.settings.missing_btime.fill(&mut attr);
+    attr.perm = if attr.kind == FileType::Directory {
+      self.settings.dir_perms.apply(attr.perm)
+    } else {
+      self.settings.file_perms.apply(attr.perm)
+    };
+    attr.uid = self.settings.uid_map.kernel_id(attr.uid);
+    attr.gid = self.settings.gid_map.kernel_id(attr.gid);
+    attr
+  }
+
+  /// Keeps attributes, that js side gives with results of reading or writing through a given handle, so that
+  /// following getattr is replied without js, when getattr coalescing is on.
+  fn keep_attr_of_io(&self, fh: FileHandle, attr: FileAttr) {
+    let attr = self.attr_to_fuse(attr);
+    if let Some(recent_attrs) = &self.recent_attrs {
+      recent_attrs.put(attr.ino.0, None, attr);
+      recent_attrs.put(attr.ino.0, Some(fh.0), attr);
+    }
+  }
+
+  /// Converts attributes of an inode, that is given to kernel in an entry of a given parent, counting this as a
+  /// lookup.
+  fn attr_of_entry(&self, parent: INodeNo, attr: FileAttr) -> fuser::FileAttr {
+    let has_xattrs = attr.has_xattrs.unwrap_or(true);
+    let attr = self.attr_to_fuse(attr);
+    self.count_lookup(&attr, has_xattrs);
+    self.inode_parents.set(attr.ino.0, parent.0);
+    attr
+  }
+
+  fn count_lookup(&self, attr: &fuser::FileAttr, has_xattrs: bool) {
+    self.lookups.add(attr.ino.0, has_xattrs);
+    if let Some(inode_kinds) = &self.inode_kinds {
+      inode_kinds.add(attr.ino.0, attr.kind);
+    }
+  }
+
+  /// Checks, when enabled in settings, if js side gives a kind of inode, different from one, known to kernel.
+  fn is_kind_changed(&self, attr: &FileAttr) -> bool {
+    self.inode_kinds.as_ref()
+    .is_some_and(|inode_kinds| inode_kinds.is_changed(attr.ino as u64, to_file_type(&attr.kind)))
+  }
+
+  /// Turns error from js side into error for kernel, reporting it, when it has a message.
+  fn fs_error(&self, op: &str, err: FsError) -> Errno {
+    if let Some(message) = &err.message {
+      ErrorReport::fs_error(op, err.code, message).send_to(self.cbs.on_error.as_deref());
+    }
+    self.js_errno(err.code)
+  }
+
+  /// Turns error code from js side into error for kernel, remapping it, as set in mount's config.
+  fn js_errno(&self, code: i32) -> Errno {
+    into_fuse_error(self.settings.errno_map.remap(code))
+  }
+
+  fn send_empty(&self, err_code: i32, reply: ReplyEmpty) {
+    if err_code == 0 {
+      reply.ok();
+    } else {
+      reply.error(self.js_errno(err_code));
+    }
+  }
+
+  /// Sends xattr data, or its size, when kernel asks for size with zero `size`. Data that doesn't fit into requested
+  /// size is replied with `ERANGE`, as per POSIX, instead of truncating it.
+  /// Replies to getxattr and listxattr. Values and lists, bigger than kernel's limit, are replied with `E2BIG`, as
+  /// kernel can't take them in any buffer.
+  fn send_xattr(&self, op: &str, xattr: XAttrBytesOrErr, size: u32, reply: ReplyXattr) {
+    match xattr {
+      XAttrBytesOrErr::Data(data) if data.len() > XATTR_SIZE_MAX => reply.error(Errno::E2BIG),
+      XAttrBytesOrErr::Size(size) if (size as usize) > XATTR_SIZE_MAX => reply.error(Errno::E2BIG),
+      XAttrBytesOrErr::Data(data) if size == 0 => reply.size(data.len() as u32),
+      XAttrBytesOrErr::Data(data) if data.len() > size as usize => reply.error(Errno::ERANGE),
+      XAttrBytesOrErr::Data(data) => reply.data(&data),
+      XAttrBytesOrErr::Size(size) => reply.size(size),
+      XAttrBytesOrErr::Err(err) => reply.error(self.fs_error(op, err)),
+    };
+  }
+
+  /// Converts listing of a directory from js side. Entries "." and ".." are set to be directories with inodes of this
+  /// directory and its parent, when the parent is known.
+  fn to_cached_dir_entries(&self, ino: INodeNo, lst: Vec<DirEntry>) -> Vec<CachedDirEntry> {
+    lst.into_iter().map(|entry| {
+      let (ino, kind) = match entry.name.as_str() {
+        "." => (ino.0, FileType::Directory),
+        ".." => (self.inode_parents.get(ino.0).unwrap_or(entry.ino as u64), FileType::Directory),
+        _ => (entry.ino as u64, to_file_type(&entry.kind)),
+      };
+      CachedDirEntry { ino, offset: entry.offset as u64, kind, name: entry.name }
+    }).collect()
+  }
+
+  /// Adds entries "." and ".." to a listing of a directory, see [`with_dot_entries`].
+  fn with_dot_entries(&self, ino: INodeNo, offset: u64, entries: Vec<CachedDirEntry>) -> Vec<CachedDirEntry> {
+    with_dot_entries(ino.0, self.inode_parents.get(ino.0).unwrap_or(ino.0), offset, entries)
+  }
+
+  /// Sends a page of a listing, that continues with a token of js side, keeping the token, together with entries,
+  /// which haven't fit into kernel's buffer, for the next readdir of this handle.
+  fn send_page_with_cursor(
+    &self, ino: INodeNo, fh: FileHandle, offset: u64, mut entries: Vec<CachedDirEntry>, token: String,
+    reply: ReplyDirectory,
+  ) {
+    let sent = send_dir_entries(&entries, reply);
+    let next_offset = if sent > 0 { entries[sent - 1].offset } else { offset };
+    let pending = entries.split_off(sent);
+    self.dir_cursors.put(ino.0, fh.0, next_offset, token, pending);
+  }
+
+  /// Drops cached listings of a directory, entries of which are changed.
+  #[cfg(feature = "writable")]
+  fn dir_changed(&self, ino: INodeNo) {
+    if let Some(dir_cache) = &self.dir_cache {
+      dir_cache.invalidate(ino.0);
+    }
+  }
+
+  /// Turns name from kernel into a string for js side, checking it against maximum name length of this mount.
+  fn normalize_name(&self, name: &OsStr) -> Result<String, Errno> {
+    if name.len() > self.settings.max_name_length as usize {
+      Err(Errno::ENAMETOOLONG)
+    } else {
+      Ok(name.display().to_string())
+    }
+  }
+
+  /// Waits for reply of js side. When enabled in settings, operation, that takes longer than a threshold, is reported
+  /// as slow, while it is still waited for.
+  fn wait_js_reply<T>(&self, js_fn: &str, ino: i64, rx: &Receiver<T>) -> Result<T, RecvTimeoutError> {
+    let timeout = self.settings.js_reply_timeout;
+    let Some(threshold) = self.settings.slow_op_warning.filter(|threshold| *threshold < timeout) else {
+      return rx.recv_timeout(timeout);
+    };
+    match rx.recv_timeout(threshold) {
+      Err(RecvTimeoutError::Timeout) => {
+        // js function is either a field of callbacks, or a local binding, named after operation
+        let op = js_fn.rsplit('.').next().unwrap_or(js_fn);
+        ErrorReport::slow_op(op, ino, threshold).send_to(self.cbs.on_error.as_deref());
+        rx.recv_timeout(timeout - threshold)
+      },
+      js_reply => js_reply,
+    }
+  }
+
+  /// Tells js side that it may stop working on a request, as it has already been replied with an error.
+  fn abort_js_op(&self, req_id: i64) {
+    if let Some(abort) = &self.cbs.abort {
+      abort.call(Ok(req_id), ThreadsafeFunctionCallMode::NonBlocking);
+    }
+  }
+
+}
+
+/// Caches of this side, shared with mounter, which drops them, when js side tells about changes, that kernel
+/// should see.
+pub struct CachesOfMount {
+  inode_parents: Arc<InodeParents>,
+  recent_attrs: Option<Arc<RecentAttrs>>,
+  dir_cache: Option<Arc<DirCache>>,
+}
+
+impl CachesOfMount {
+
+  /// Drops cached attributes and listings of a directory and of all its known descendants, returning inodes of all
+  /// of them, starting with directory itself.
+  pub fn forget_subtree(&self, dir: u64) -> Vec<u64> {
+    let mut inos = vec![dir];
+    inos.extend(self.inode_parents.descendants(dir));
+    for &ino in &inos {
+      if let Some(recent_attrs) = &self.recent_attrs {
+        recent_attrs.forget(ino);
+      }
+      if let Some(dir_cache) = &self.dir_cache {
+        dir_cache.invalidate(ino);
+      }
+    }
+    inos
+  }
+
+}
+
+/// Sets block and fragment sizes of statistics, that js side has left to defaults.
+fn with_statfs_defaults(settings: &MountSettings, st: StatfsResult) -> StatfsResult {
+  let bsize = st.bsize.unwrap_or(BLOCK_SIZE as u32);
+  let frsize = st.frsize.or(settings.frsize).unwrap_or(bsize);
+  StatfsResult { bsize: Some(bsize), frsize: Some(frsize), ..st }
+}
+
+/// Statistics of filesystem, shared with mounter, that gives them to js side, as kernel would see them. Callbacks are
+/// referenced weakly, so that they are released with the end of session.
+#[derive(Clone)]
+pub struct StatsOfMount {
+  cbs: Weak<CallbacksToJS>,
+  settings: Arc<MountSettings>,
 }
 
+impl StatsOfMount {
+
+  pub async fn statfs(&self) -> napi::Result<StatfsResult> {
+    let Some(cbs) = self.cbs.upgrade() else {
+      return Err(napi::Error::from_reason("Filesystem is not mounted"));
+    };
+    let Some(statfs) = &cbs.statfs else {
+      return Ok(with_statfs_defaults(&self.settings, StatfsResult::default()));
+    };
+    match statfs.call_async(Ok(INodeNo::ROOT.0 as i64)).await?.await? {
+      StatfsOrErr::Stats(st) => Ok(with_statfs_defaults(&self.settings, st)),
+      StatfsOrErr::Err(err) => {
+        let details = err.message.map(|msg| format!(": {msg}")).unwrap_or_default();
+        Err(napi::Error::from_reason(format!("statfs failed with {}{details}", fuse_errno_to_string(err.code))))
+      },
+    }
+  }
+
+}
+
+/// Poll notifiers by file handles, for which kernel waits for readiness notification.
+pub type PollNotifiers = Arc<Mutex<HashMap<u64, PollNotifier>>>;
+
+/// Inodes, written since their last successful flush or fsync.
+pub type DirtyInodes = Arc<Mutex<HashSet<u64>>>;
+
 /// This calls js functions, with following patterns, corresponding to arms of this macro:
 /// - **arm #0** - calling without arguments a sync function.
 ///   This needs only js function. Macro expands into statement.
@@ -46,47 +433,121 @@ impl CallbacksProxy {
 /// - **arm #2** - calling with arguments an async function.
 ///   This needs js function, tuple of arguments, type of return data and a channel to pass data from NAPI side.
 ///   Macro expands into expression of returned data.
-/// - **arm #3** - calling with arguments an async function.
+/// - **arm #3** - same as arm #2, with an additional expression that is evaluated when js side doesn't reply in
+///   time, before replying with `EIO`.
+/// - **idempotent arms** - same as arms #2 and #3, with calls retried after rejections, marked as retryable (see
+///   [`RETRYABLE_REJECTION`]), as set by retry settings of this mount. Arguments should be cloneable.
+/// - **arm #4** - calling with arguments an async function.
 ///   
-/// 
+///
 macro_rules! call_js {
+  ($js_fn:expr, $args:expr, @non-blocking) => {
+    $js_fn.call(Ok($args.into()), ThreadsafeFunctionCallMode::NonBlocking);
+  };
   ($js_fn:expr) => {
     $js_fn.call(Ok(()), ThreadsafeFunctionCallMode::Blocking);
   };
   ($js_fn:expr, $args:expr) => {
     $js_fn.call(Ok($args.into()), ThreadsafeFunctionCallMode::Blocking);
   };
-  ($js_fn:expr, $args:expr, $out_type:ty, $reply:ident, @initial-thread => $with_reply:expr) => {
-    {
-      let (tx_done_signal, rx_done_signal) = channel::<Option<$out_type>>();
-      $js_fn.call_with_return_value(
-        Ok($args.into()),
-        ThreadsafeFunctionCallMode::Blocking,
-        move |js_reply, env| {
-          match js_reply {
-            Ok(js_reply) => {
-              let _ = env.spawn_future(async move {
-                let _ = match js_reply.await {
-                  Ok(js_reply) => tx_done_signal.send(Some(js_reply)),
-                  Err(_) => tx_done_signal.send(None),
-                };
-                Ok(())
-              });
-            },
-            Err(_) => {
-              let _ = tx_done_signal.send(None);
-            }
-          };
-          Ok(())
+  ($fs:expr, $js_fn:expr, $args:expr, $out_type:ty, $reply:ident, @initial-thread => $with_reply:expr) => {
+    call_js!($fs, $js_fn, $args, $out_type, $reply, @initial-thread => $with_reply, @on-timeout => ())
+  };
+  (
+    $fs:expr, $js_fn:expr, $args:expr, $out_type:ty, $reply:ident,
+    @initial-thread => $with_reply:expr, @on-timeout => $on_timeout:expr
+  ) => {
+    'call: {
+      // permit is held only while js side works, as reply may make nested calls
+      let Some(permit) = $fs.in_flight.acquire(Duration::from_secs(30)) else {
+        $reply.error(Errno::EAGAIN);
+        break 'call;
+      };
+      let (tx_done_signal, rx_done_signal) = channel::<Result<$out_type, bool>>();
+      let args = $args;
+      let ino = InodeArg::inode(&args);
+      call_js!(@send-to tx_done_signal, $js_fn, args);
+      let js_reply = $fs.wait_js_reply(stringify!($js_fn), ino, &rx_done_signal);
+      drop(permit);
+      call_js!(@reply js_reply, $reply, $with_reply, $on_timeout);
+    }
+  };
+  (
+    $fs:expr, $js_fn:expr, $args:expr, $out_type:ty, $reply:ident,
+    @idempotent, @initial-thread => $with_reply:expr
+  ) => {
+    call_js!(
+      $fs, $js_fn, $args, $out_type, $reply, @idempotent, @initial-thread => $with_reply, @on-timeout => ()
+    )
+  };
+  (
+    $fs:expr, $js_fn:expr, $args:expr, $out_type:ty, $reply:ident,
+    @idempotent, @initial-thread => $with_reply:expr, @on-timeout => $on_timeout:expr
+  ) => {
+    'call: {
+      let args = $args;
+      let ino = InodeArg::inode(&args);
+      let mut backoff = $fs.settings.js_retry_backoff;
+      let mut attempt = 0;
+      let js_reply = loop {
+        let Some(permit) = $fs.in_flight.acquire(Duration::from_secs(30)) else {
+          $reply.error(Errno::EAGAIN);
+          break 'call;
+        };
+        let (tx_done_signal, rx_done_signal) = channel::<Result<$out_type, bool>>();
+        call_js!(@send-to tx_done_signal, $js_fn, args.clone());
+        let js_reply = $fs.wait_js_reply(stringify!($js_fn), ino, &rx_done_signal);
+        drop(permit);
+        match js_reply {
+          Ok(Err(true)) if attempt < $fs.settings.js_retries => {
+            thread::sleep(backoff);
+            backoff *= 2;
+            attempt += 1;
+          },
+          js_reply => break js_reply,
         }
-      );
-      match rx_done_signal.recv_timeout(Duration::from_secs(30)) {
-        Ok(Some(js_reply)) => ($with_reply)(js_reply),
-        _ => $reply.error(Errno::EIO),
+      };
+      call_js!(@reply js_reply, $reply, $with_reply, $on_timeout);
+    }
+  };
+  (@send-to $tx_done_signal:ident, $js_fn:expr, $args:expr) => {
+    $js_fn.call_with_return_value(
+      Ok($args.into()),
+      ThreadsafeFunctionCallMode::Blocking,
+      move |js_reply, env| {
+        match js_reply {
+          Ok(js_reply) => {
+            let _ = env.spawn_future(async move {
+              let _ = match js_reply.await {
+                Ok(js_reply) => $tx_done_signal.send(Ok(js_reply)),
+                Err(err) => $tx_done_signal.send(Err(is_retryable(&err))),
+              };
+              Ok(())
+            });
+          },
+          Err(err) => {
+            let _ = $tx_done_signal.send(Err(is_retryable(&err)));
+          }
+        };
+        Ok(())
       }
+    );
+  };
+  (@reply $js_reply:ident, $reply:ident, $with_reply:expr, $on_timeout:expr) => {
+    match $js_reply {
+      Ok(Ok(js_reply)) => ($with_reply)(js_reply),
+      Err(RecvTimeoutError::Timeout) => {
+        $on_timeout;
+        $reply.error(Errno::EIO);
+      },
+      _ => $reply.error(Errno::EIO),
     }
   };
-  ($js_fn:expr, $args:expr, $out_type:ty, $reply:ident, @napi-thread => $with_reply:expr) => {
+  ($fs:expr, $js_fn:expr, $args:expr, $out_type:ty, $reply:ident, @napi-thread => $with_reply:expr) => {
+    let Some(permit) = $fs.in_flight.acquire(Duration::from_secs(30)) else {
+      $reply.error(Errno::EAGAIN);
+      return;
+    };
     $js_fn.call_with_return_value(
       Ok($args.into()),
       ThreadsafeFunctionCallMode::Blocking,
@@ -94,7 +555,9 @@ macro_rules! call_js {
         match js_reply {
           Ok(js_reply) => {
             let _ = env.spawn_future(async move {
-              match js_reply.await {
+              let js_reply = js_reply.await;
+              drop(permit);
+              match js_reply {
                 Ok(js_reply) => ($with_reply)(js_reply),
                 Err(_) => $reply.error(Errno::EIO),
               };
@@ -109,71 +572,324 @@ macro_rules! call_js {
   };
 }
 
+/// Tells, if rejection of js side is marked as retryable.
+fn is_retryable(err: &napi::Error) -> bool {
+  // rejection with an Error object comes as its string form, prefixed by name
+  let message = err.reason.strip_prefix("Error: ").unwrap_or(&err.reason);
+  message.starts_with(RETRYABLE_REJECTION)
+}
+
+/// Attributes of a negative entry, which only inode number 0 matters.
+fn negative_entry_attr() -> fuser::FileAttr {
+  fuser::FileAttr {
+    ino: INodeNo(0), size: 0, blocks: 0,
+    atime: SystemTime::UNIX_EPOCH, mtime: SystemTime::UNIX_EPOCH, ctime: SystemTime::UNIX_EPOCH,
+    crtime: SystemTime::UNIX_EPOCH, kind: FileType::RegularFile, perm: 0, nlink: 0, uid: 0, gid: 0, rdev: 0,
+    blksize: 0, flags: 0,
+  }
+}
+
 fn fh_opt_i64(x: Option<FileHandle>) -> Option<i64> {
-  match x { Some(n) => Some(n.0 as i64), _ => None }
+  x.map(|n| n.0 as i64)
 }
 fn lo_opt_i64(x: Option<LockOwner>) -> Option<i64> {
-  match x { Some(n) => Some(n.0 as i64), _ => None }
+  x.map(|n| n.0 as i64)
 }
 fn str_from_os(s: &OsStr) -> String {
   s.to_str().unwrap().to_string()
 }
 fn to_opt_u32(x: Option<BsdFileFlags>) -> Option<u32> {
-  match x { Some(n) => Some(n.bits()), _ => None }
+  x.map(|n| n.bits())
+}
+fn millis_of(time: TimeOrNow) -> i64 {
+  match time {
+    TimeOrNow::SpecificTime(time) => millis_from(time),
+    TimeOrNow::Now => millis_from(SystemTime::now()),
+  }
 }
 
-fn send_xattr(xattr: XAttrBytesOrErr, reply: ReplyXattr) {
-  match xattr {
-    XAttrBytesOrErr::Data(data) => reply.data(&data),
-    XAttrBytesOrErr::Size(size) => reply.size(size),
-    XAttrBytesOrErr::Err(code) => reply.error(Errno::from_i32(code)),
-  };
+/// Inode, that is the first argument of js callbacks, taken for reports about operations.
+trait InodeArg {
+  fn inode(&self) -> i64;
+}
+
+impl InodeArg for i64 {
+  fn inode(&self) -> i64 { *self }
+}
+impl<B> InodeArg for (i64, B) {
+  fn inode(&self) -> i64 { self.0 }
+}
+impl<B, C> InodeArg for (i64, B, C) {
+  fn inode(&self) -> i64 { self.0 }
+}
+impl<B, C, D> InodeArg for (i64, B, C, D) {
+  fn inode(&self) -> i64 { self.0 }
+}
+impl<B, C, D, E> InodeArg for (i64, B, C, D, E) {
+  fn inode(&self) -> i64 { self.0 }
+}
+
+/// Checks that path, with terminating null, fits into `PATH_MAX`.
+fn fits_path_max(path: &[u8]) -> bool {
+  path.len() < (libc::PATH_MAX as usize)
 }
 
-fn send_empty(err_code: i32, reply: ReplyEmpty) {
-  if err_code == 0 {
-    reply.ok();
+/// Checks that user may add entries into directory, i.e. has write and search permissions on it.
+/// Supplementary groups of the user are not known here, hence, only primary group is checked.
+#[cfg(feature = "writable")]
+fn may_add_into_dir(dir: &fuser::FileAttr, uid: u32, gid: u32) -> bool {
+  if uid == 0 {
+    return true;
+  }
+  let perm = if uid == dir.uid {
+    dir.perm >> 6
+  } else if gid == dir.gid {
+    dir.perm >> 3
   } else {
-    reply.error(Errno::from_i32(err_code));
+    dir.perm
+  };
+  (perm & 0o3) == 0o3
+}
+
+/// Offsets of entries "." and "..", added to listings, in which js side doesn't give them. These are the last ones
+/// below 2^63, as kernel's offsets are signed.
+const DOT_OFFSET: u64 = (i64::MAX - 1) as u64;
+const DOTDOT_OFFSET: u64 = i64::MAX as u64;
+
+/// Sends entries, that fit into kernel's buffer, returning their number. Entry, that doesn't fit, isn't added to
+/// reply, and kernel's next readdir comes with offset of the last sent entry, hence, it starts with the unsent one.
+fn send_dir_entries(entries: &[CachedDirEntry], mut reply: ReplyDirectory) -> usize {
+  let mut sent = 0;
+  for entry in entries {
+    let buffer_full = reply.add(INodeNo(entry.ino), entry.offset, entry.kind, OsStr::new(&entry.name));
+    if buffer_full {
+      break;
+    }
+    sent += 1;
+  }
+  reply.ok();
+  sent
+}
+
+/// Adds entries "." and "..", that are missing in the first page of a listing, as POSIX requires them. Added
+/// entries go before ones from js side, which is read at offset 0, when kernel continues after added entries.
+fn with_dot_entries(ino: u64, parent: u64, offset: u64, mut entries: Vec<CachedDirEntry>) -> Vec<CachedDirEntry> {
+  let has_entry = |name: &str| entries.iter().any(|entry| entry.name == name);
+  let dots = [(".", ino, DOT_OFFSET), ("..", parent, DOTDOT_OFFSET)]
+  .into_iter()
+  .filter(|(name, _, dot_offset)| (*dot_offset > offset) && !has_entry(name))
+  .map(|(name, ino, offset)| CachedDirEntry { ino, offset, kind: FileType::Directory, name: name.to_string() })
+  .collect::<Vec<_>>();
+  if !dots.is_empty() {
+    entries.splice(0..0, dots);
   }
+  entries
+}
+
+/// Tells, if flags of setattr clear any of BSD file flags, that are set to all inodes of this mount.
+fn clears_mount_flags(mount_flags: u32, flags: Option<BsdFileFlags>) -> bool {
+  flags.is_some_and(|flags| (mount_flags & !flags.bits()) != 0)
+}
+
+#[cfg(feature = "extended-ops")]
+fn ranges_overlap(offset_a: u64, offset_b: u64, len: u64) -> bool {
+  (offset_a < offset_b.saturating_add(len)) && (offset_b < offset_a.saturating_add(len))
 }
 
-const TTL: Duration = Duration::from_secs(1);
+// methods, that call js side, are placed after call_js! macro
+impl CallbacksProxy {
+
+  /// Replies to a write, after flush and fsync of written data, as set by durability tier of this mount. Writes to
+  /// files, opened with `O_SYNC` or `O_DSYNC`, are always followed by fsync, as POSIX requires.
+  #[cfg(feature = "writable")]
+  fn make_durable(
+    &self, ino: INodeNo, fh: FileHandle, flags: OpenFlags, lock_owner: Option<LockOwner>, size: u32,
+    reply: ReplyWrite
+  ) {
+    // O_SYNC includes bit of O_DSYNC
+    let sync = (flags.0 & libc::O_DSYNC) != 0;
+    if (self.settings.durability == DurabilityTier::None) && !sync {
+      self.dirty_inodes.lock().unwrap().insert(ino.0);
+      reply.written(size);
+      return;
+    }
+    let fsync_and_reply = |reply: ReplyWrite| if (self.settings.durability == DurabilityTier::Fsync) || sync {
+      let datasync = (flags.0 & libc::O_SYNC) != libc::O_SYNC;
+      call_js!(
+        self, self.cbs.fsync, (ino.0 as i64, fh.0 as i64, datasync), i32, reply,
+        @initial-thread => |err_code| if err_code == 0 {
+          self.dirty_inodes.lock().unwrap().remove(&ino.0);
+          reply.written(size);
+        } else {
+          reply.error(self.js_errno(err_code));
+        }
+      );
+    } else {
+      reply.written(size);
+    };
+    let flush = self.cbs.flush.as_ref().filter(|_| self.settings.durability != DurabilityTier::None);
+    let Some(flush) = flush else {
+      fsync_and_reply(reply);
+      return;
+    };
+    call_js!(
+      self, flush, (ino.0 as i64, fh.0 as i64, lock_owner.map_or(0, |lo| lo.0 as i64)), i32, reply,
+      @initial-thread => |err_code| if err_code == 0 {
+        fsync_and_reply(reply);
+      } else {
+        reply.error(self.js_errno(err_code));
+      }
+    );
+  }
+
+}
+
+/// Size of slots in write and read buffers pools. With write pool, it is also set as maximum size of writes. Reads
+/// can't be limited the same way, and bigger reads go without read pool's slots.
+const POOL_SLOT_SIZE: usize = 1024 * 1024;
+
+/// Kernel's limit on size of an extended attribute's value, and of a list of attributes' names.
+const XATTR_SIZE_MAX: usize = 64 * 1024;
+
+/// Beginning of rejection's message, or of rejected string, with which js side tells that idempotent operation
+/// (lookup, getattr, read, readdir) may be retried.
+const RETRYABLE_REJECTION: &str = "retryable";
+
+/// Time, given to js side to finish its work in destroy.
+const DESTROY_TIMEOUT: Duration = Duration::from_secs(10);
+
 
 impl Filesystem for CallbacksProxy {
 
-  fn init(&mut self, _req: &Request, _config: &mut KernelConfig) -> io::Result<()> {
-    call_js!(self.cbs.init, (INodeNo::ROOT.0 as i64));
+  fn init(&mut self, _req: &Request, config: &mut KernelConfig) -> io::Result<()> {
+    let (granted_caps, mut denied_caps) = self.settings.caps.negotiate(|cap| config.add_capabilities(cap).is_ok());
+    self.granted_caps |= granted_caps;
+    if self.cbs.readdirplus.is_some() && config.add_capabilities(InitFlags::FUSE_DO_READDIRPLUS).is_err() {
+      denied_caps.push("readdirplus".to_string());
+    }
+    let write_buffers = self.write_pool.as_ref().map(|pool| {
+      let _ = config.set_max_write(pool.slot_size() as u32);
+      PoolBuffers(pool.clone())
+    });
+    self.cache_dir_supported = config.kernel_abi() >= Version(7, 28);
+    let read_buffers = self.read_pool.as_ref().map(|pool| PoolBuffers(pool.clone()));
+    // kernel's value can't be read directly, but setting a too big one fails with it
+    let kernel_max_readahead = config.set_max_readahead(u32::MAX).err().unwrap_or(u32::MAX);
+    let max_readahead = match self.settings.max_readahead {
+      Some(max_readahead) => match config.set_max_readahead(max_readahead.min(kernel_max_readahead)) {
+        Ok(_) => max_readahead.min(kernel_max_readahead),
+        Err(_) => kernel_max_readahead,
+      },
+      None => kernel_max_readahead,
+    };
+    let limits = KernelLimits { kernel_max_readahead, max_readahead };
+    call_js!(
+      self.cbs.init, (INodeNo::ROOT.0 as i64, InitInfo { denied_caps, write_buffers, read_buffers, limits })
+    );
     Ok(())
   }
 
   fn destroy(&mut self) {
-    call_js!(self.cbs.destroy);
+    if self.env_exiting.load(Ordering::Relaxed) {
+      // js side can't run, while its environment is torn down
+      return;
+    }
+    // session ends, when this returns, hence, js side is given time to finish its work, like flushing of buffers
+    let (tx_done_signal, rx_done_signal) = channel::<()>();
+    self.cbs.destroy.call_with_return_value(
+      Ok(()),
+      ThreadsafeFunctionCallMode::Blocking,
+      move |js_reply, env| {
+        match js_reply {
+          Ok(js_reply) => {
+            let _ = env.spawn_future(async move {
+              let _ = js_reply.await;
+              let _ = tx_done_signal.send(());
+              Ok(())
+            });
+          },
+          Err(_) => {
+            let _ = tx_done_signal.send(());
+          }
+        };
+        Ok(())
+      }
+    );
+    let _ = rx_done_signal.recv_timeout(DESTROY_TIMEOUT);
   }
 
   fn lookup(&self, _req: &Request, parent: INodeNo, name: &OsStr, reply: ReplyEntry) {
+    let name_str = match self.normalize_name(name) {
+      Ok(name) => name,
+      Err(err) => return reply.error(err),
+    };
     call_js!(
-      self.cbs.lookup, (parent.0 as i64, str_from_os(name)), FileAttrOrErr, reply,
+      self, self.cbs.lookup, (parent.0 as i64, name_str), FileAttrOrErr, reply, @idempotent,
       @initial-thread => |js_reply| {
         match js_reply {
-          FileAttrOrErr::Attr(attrs) => reply.entry(&TTL, &attrs.into_fuse(), Generation(0)),
-          FileAttrOrErr::Err(code) => reply.error(Errno::from_i32(code)),
+          FileAttrOrErr::Attr(attrs) if self.is_kind_changed(&attrs) => reply.error(Errno::ESTALE),
+          FileAttrOrErr::Attr(attrs) => match &self.cbs.generation_store {
+            Some(generation_store) => call_js!(
+              self, generation_store, attrs.ino, i64, reply,
+              @initial-thread => |generation| {
+                reply.entry(&self.settings.entry_ttl, &self.attr_of_entry(parent, attrs), Generation(generation as u64));
+              }
+            ),
+            None => reply.entry(&self.settings.entry_ttl, &self.attr_of_entry(parent, attrs), Generation(0)),
+          },
+          FileAttrOrErr::Err(err) => match self.settings.negative_ttl {
+            // entry with zero inode tells kernel to cache absence of name
+            Some(ttl) if self.settings.errno_map.remap(err.code) == libc::ENOENT => {
+              reply.entry(&ttl, &negative_entry_attr(), Generation(0))
+            },
+            _ => reply.error(self.fs_error("lookup", err)),
+          },
         }
       }
     );
   }
 
   fn forget(&self, _req: &Request, ino: INodeNo, nlookup: u64) {
-    call_js!(self.cbs.forget, (ino.0 as i64, nlookup as i64));
+    if let Some(inode_kinds) = &self.inode_kinds {
+      inode_kinds.forget(ino.0, nlookup);
+    }
+    let released = self.lookups.forget(ino.0, nlookup);
+    if released {
+      // partial forget leaves inode known to kernel, together with its parent
+      self.inode_parents.forget(ino.0);
+    }
+    if let Some(release_inode) = &self.cbs.release_inode {
+      if released {
+        call_js!(release_inode, ino.0 as i64, @non-blocking);
+      }
+    } else if let Some(forget_many) = &self.cbs.forget_many {
+      if self.pending_forgets.push(ino.0, nlookup) {
+        call_js!(forget_many, self.pending_forgets.clone(), @non-blocking);
+      }
+    } else {
+      call_js!(self.cbs.forget, (ino.0 as i64, nlookup as i64), @non-blocking);
+    }
   }
 
   fn getattr(&self, _req: &Request, ino: INodeNo, fh: Option<FileHandle>, reply: ReplyAttr) {
+    let fh_u64 = fh.map(|fh| fh.0);
+    if let Some(recent_attrs) = &self.recent_attrs && let Some(attr) = recent_attrs.get(ino.0, fh_u64) {
+      reply.attr(&self.settings.attr_ttl, &attr);
+      return;
+    }
     call_js!(
-      self.cbs.getattr, (ino.0 as i64, fh_opt_i64(fh)), FileAttrOrErr, reply,
+      self, self.cbs.getattr, (ino.0 as i64, fh_opt_i64(fh)), FileAttrOrErr, reply, @idempotent,
       @initial-thread => |js_reply| {
         match js_reply {
-          FileAttrOrErr::Attr(attrs) => reply.attr(&TTL, &attrs.into_fuse()),
-          FileAttrOrErr::Err(code) => reply.error(Errno::from_i32(code)),
+          FileAttrOrErr::Attr(attrs) if self.is_kind_changed(&attrs) => reply.error(Errno::ESTALE),
+          FileAttrOrErr::Attr(attrs) => {
+            let attr = self.attr_to_fuse(attrs);
+            if let Some(recent_attrs) = &self.recent_attrs {
+              recent_attrs.put(ino.0, fh_u64, attr);
+            }
+            reply.attr(&self.settings.attr_ttl, &attr);
+          },
+          FileAttrOrErr::Err(err) => reply.error(self.fs_error("getattr", err)),
         }
       }
     );
@@ -186,9 +902,9 @@ impl Filesystem for CallbacksProxy {
     mode: Option<u32>,
     uid: Option<u32>,
     gid: Option<u32>,
-    _size: Option<u64>,
-    _atime: Option<TimeOrNow>,
-    _mtime: Option<TimeOrNow>,
+    size: Option<u64>,
+    atime: Option<TimeOrNow>,
+    mtime: Option<TimeOrNow>,
     _ctime: Option<SystemTime>,
     fh: Option<FileHandle>,
     _crtime: Option<SystemTime>,
@@ -197,153 +913,427 @@ impl Filesystem for CallbacksProxy {
     flags: Option<BsdFileFlags>,
     reply: ReplyAttr,
   ) {
-    let changes = AttrChanges { mode, uid, gid, flags: to_opt_u32(flags) };
+    if clears_mount_flags(self.settings.file_flags, flags) {
+      reply.error(Errno::EPERM);
+      return;
+    }
+    let out_of_range = [atime, mtime].into_iter().any(|time| match time {
+      Some(TimeOrNow::SpecificTime(time)) => !self.settings.timestamps.contains(time),
+      _ => false,
+    });
+    if out_of_range {
+      reply.error(Errno::EINVAL);
+      return;
+    }
+    if let Some(recent_attrs) = &self.recent_attrs {
+      recent_attrs.forget(ino.0);
+    }
+    if let Some(truncate) = &self.cbs.truncate && let Some(size) = size
+    && mode.is_none() && uid.is_none() && gid.is_none() && atime.is_none() && flags.is_none() {
+      // kernel may send mtime along with size, but it is implied by truncate anyway
+      call_js!(
+        self, truncate, (ino.0 as i64, fh_opt_i64(fh), size as i64), FileAttrOrErr, reply,
+        @initial-thread => |js_reply| {
+          match js_reply {
+            FileAttrOrErr::Attr(attrs) => {
+              reply.attr(&self.settings.attr_ttl, &self.attr_to_fuse(attrs));
+              self.evict_past_size(ino, Some(size));
+            },
+            FileAttrOrErr::Err(err) => reply.error(self.fs_error("setattr", err)),
+          }
+        }
+      );
+      return;
+    }
+    let changes = AttrChanges {
+      mode,
+      size: size.map(|size| size as i64),
+      uid: uid.map(|uid| self.settings.uid_map.js_id(uid)),
+      gid: gid.map(|gid| self.settings.gid_map.js_id(gid)),
+      flags: to_opt_u32(flags),
+      atime: atime.map(millis_of),
+      mtime: mtime.map(millis_of),
+    };
     call_js!(
-      self.cbs.setattr, (ino.0 as i64, fh_opt_i64(fh), changes), FileAttrOrErr, reply,
+      self, self.cbs.setattr, (ino.0 as i64, fh_opt_i64(fh), changes), FileAttrOrErr, reply,
       @initial-thread => |js_reply| {
         match js_reply {
-          FileAttrOrErr::Attr(attrs) => reply.attr(&TTL, &attrs.into_fuse()),
-          FileAttrOrErr::Err(code) => reply.error(Errno::from_i32(code)),
+          FileAttrOrErr::Attr(attrs) => {
+            reply.attr(&self.settings.attr_ttl, &self.attr_to_fuse(attrs));
+            self.evict_past_size(ino, size);
+          },
+          FileAttrOrErr::Err(err) => reply.error(self.fs_error("setattr", err)),
         }
       }
     );
   }
 
-  fn readlink(&self, _req: &Request, _ino: INodeNo, reply: ReplyData) {
-    reply.error(Errno::ENOSYS);
+  fn readlink(&self, _req: &Request, ino: INodeNo, reply: ReplyData) {
+    let Some(readlink) = &self.cbs.readlink else {
+      reply.error(Errno::ENOSYS);
+      return;
+    };
+    call_js!(
+      self, readlink, ino.0 as i64, LinkTargetOrErr, reply,
+      @initial-thread => |js_reply| {
+        match js_reply {
+          LinkTargetOrErr::Target(target) if fits_path_max(target.as_bytes()) => reply.data(target.as_bytes()),
+          LinkTargetOrErr::Target(_) => reply.error(Errno::ENAMETOOLONG),
+          LinkTargetOrErr::Err(err) => reply.error(self.fs_error("readlink", err)),
+        }
+      }
+    );
   }
 
+  #[cfg(feature = "writable")]
   fn mknod(
     &self, _req: &Request, parent: INodeNo, name: &OsStr, mode: u32, umask: u32, rdev: u32, reply: ReplyEntry
   ) {
-    let name_str = name.display().to_string();
+    let name_str = match self.normalize_name(name) {
+      Ok(name) => name,
+      Err(err) => return reply.error(err),
+    };
+    self.dir_changed(parent);
     call_js!(
-      self.cbs.mknod, (parent.0 as i64, name_str, mode, umask, rdev), NewEntryOrErr, reply,
+      self, self.cbs.mknod, (parent.0 as i64, name_str, mode, umask, rdev), NewEntryOrErr, reply,
       @initial-thread => |js_reply| {
         match js_reply {
           NewEntryOrErr::Entry(r) => reply.entry(
-            &Duration::from_millis(r.ttl as u64), &r.attr.into_fuse(), Generation(r.generation as u64)
+            &self.ttl_of_entry(r.ttl), &self.attr_of_entry(parent, r.attr), Generation(r.generation as u64)
           ),
-          NewEntryOrErr::Err(code) => reply.error(Errno::from_i32(code)),
+          NewEntryOrErr::Err(err) => reply.error(self.fs_error("mknod", err)),
         }
       }
     )
   }
 
+  #[cfg(feature = "writable")]
   fn mkdir(
-    &self, _req: &Request, parent: INodeNo, name: &OsStr, mode: u32, umask: u32, reply: ReplyEntry
+    &self, req: &Request, parent: INodeNo, name: &OsStr, mode: u32, umask: u32, reply: ReplyEntry
   ) {
-    let name_str = name.display().to_string();
-    call_js!(
-      self.cbs.mkdir, (parent.0 as i64, name_str, mode, umask), NewEntryOrErr, reply,
+    let name_str = match self.normalize_name(name) {
+      Ok(name) => name,
+      Err(err) => return reply.error(err),
+    };
+    self.dir_changed(parent);
+    let mkdir_in_js = |reply: ReplyEntry| call_js!(
+      self, self.cbs.mkdir, (parent.0 as i64, name_str, mode, umask), NewEntryOrErr, reply,
       @initial-thread => |js_reply| {
         match js_reply {
           NewEntryOrErr::Entry(r) => reply.entry(
-            &Duration::from_millis(r.ttl as u64), &r.attr.into_fuse(), Generation(r.generation as u64)
+            &self.ttl_of_entry(r.ttl), &self.attr_of_entry(parent, r.attr), Generation(r.generation as u64)
           ),
-          NewEntryOrErr::Err(code) => reply.error(Errno::from_i32(code)),
+          NewEntryOrErr::Err(err) => reply.error(self.fs_error("mkdir", err)),
         }
       }
-    )
+    );
+    if !self.settings.check_parent_perms {
+      mkdir_in_js(reply);
+      return;
+    }
+    let (uid, gid) = (req.uid(), req.gid());
+    call_js!(
+      self, self.cbs.getattr, (parent.0 as i64, None), FileAttrOrErr, reply,
+      @initial-thread => |js_reply| {
+        match js_reply {
+          FileAttrOrErr::Attr(attrs) => if may_add_into_dir(&self.attr_to_fuse(attrs), uid, gid) {
+            mkdir_in_js(reply);
+          } else {
+            reply.error(Errno::EACCES);
+          },
+          FileAttrOrErr::Err(err) => reply.error(self.fs_error("mkdir", err)),
+        }
+      }
+    );
   }
 
+  #[cfg(feature = "writable")]
   fn unlink(&self, _req: &Request, parent: INodeNo, name: &OsStr, reply: ReplyEmpty) {
-    let name_str = name.display().to_string();
+    let name_str = match self.normalize_name(name) {
+      Ok(name) => name,
+      Err(err) => return reply.error(err),
+    };
+    self.dir_changed(parent);
     call_js!(
-      self.cbs.unlink, (parent.0 as i64, name_str), i32, reply,
-      @initial-thread => |err_code| { send_empty(err_code, reply); }
+      self, self.cbs.unlink, (parent.0 as i64, name_str), i32, reply,
+      @initial-thread => |err_code| { self.send_empty(err_code, reply); }
     );
   }
 
+  #[cfg(feature = "writable")]
   fn rmdir(&self, _req: &Request, parent: INodeNo, name: &OsStr, reply: ReplyEmpty) {
-    let name_str = name.display().to_string();
+    let name_str = match self.normalize_name(name) {
+      Ok(name) => name,
+      Err(err) => return reply.error(err),
+    };
+    self.dir_changed(parent);
     call_js!(
-      self.cbs.rmdir, (parent.0 as i64, name_str), i32, reply,
-      @initial-thread => |err_code| { send_empty(err_code, reply); }
+      self, self.cbs.rmdir, (parent.0 as i64, name_str), i32, reply,
+      @initial-thread => |err_code| { self.send_empty(err_code, reply); }
     );
   }
 
-  /// We don't do symbolic linking.
-  fn symlink(&self, _req: &Request, _parent: INodeNo, _link_name: &OsStr, _target: &Path, reply: ReplyEntry) {
-    reply.error(Errno::EPERM);
+  /// Without js callback, we don't do symbolic linking.
+  #[cfg(feature = "writable")]
+  fn symlink(&self, _req: &Request, parent: INodeNo, link_name: &OsStr, target: &Path, reply: ReplyEntry) {
+    let Some(symlink) = &self.cbs.symlink else {
+      reply.error(Errno::EPERM);
+      return;
+    };
+    let name_str = match self.normalize_name(link_name) {
+      Ok(name) => name,
+      Err(err) => return reply.error(err),
+    };
+    if !fits_path_max(target.as_os_str().as_bytes()) {
+      reply.error(Errno::ENAMETOOLONG);
+      return;
+    }
+    let target_str = target.display().to_string();
+    self.dir_changed(parent);
+    call_js!(
+      self, symlink, (parent.0 as i64, name_str, target_str), NewEntryOrErr, reply,
+      @initial-thread => |js_reply| {
+        match js_reply {
+          // js side must not make an inode of another kind in place of a link
+          NewEntryOrErr::Entry(r) if !matches!(r.attr.kind, InodeKind::SymLink) => reply.error(Errno::EIO),
+          NewEntryOrErr::Entry(r) => reply.entry(
+            &self.ttl_of_entry(r.ttl), &self.attr_of_entry(parent, r.attr), Generation(r.generation as u64)
+          ),
+          NewEntryOrErr::Err(err) => reply.error(self.fs_error("symlink", err)),
+        }
+      }
+    );
   }
 
+  #[cfg(feature = "writable")]
   fn rename(
     &self, _req: &Request, parent: INodeNo, name: &OsStr, newparent: INodeNo, newname: &OsStr,
     flags: RenameFlags, reply: ReplyEmpty,
   ) {
-    let name_str = name.display().to_string();
-    let newname_str = newname.display().to_string();
+    let name_str = match self.normalize_name(name) {
+      Ok(name) => name,
+      Err(err) => return reply.error(err),
+    };
+    let newname_str = match self.normalize_name(newname) {
+      Ok(name) => name,
+      Err(err) => return reply.error(err),
+    };
+    self.dir_changed(parent);
+    self.dir_changed(newparent);
+    let args = RenameArgs::from(flags);
     call_js!(
-      self.cbs.rename, (parent.0 as i64, name_str, newparent.0 as i64, newname_str, flags.bits()), i32, reply,
-      @initial-thread => |err_code| { send_empty(err_code, reply); }
+      self, self.cbs.rename, (parent.0 as i64, name_str, newparent.0 as i64, newname_str, args), i32, reply,
+      @initial-thread => |err_code| { self.send_empty(err_code, reply); }
     );
   }
 
   /// We don't do linking.
+  #[cfg(feature = "writable")]
   fn link(&self, _req: &Request, _ino: INodeNo, _newparent: INodeNo, _newname: &OsStr, reply: ReplyEntry) {
     reply.error(Errno::EPERM);
   }
 
   fn open(&self, _req: &Request, ino: INodeNo, flags: OpenFlags, reply: ReplyOpen) {
+    if self.granted_caps.contains(InitFlags::FUSE_NO_OPEN_SUPPORT) {
+      reply.error(Errno::ENOSYS);
+      return;
+    }
+    // exclusive creation is done by create, and kernel doesn't pass these flags to open
+    let excl_create = libc::O_CREAT | libc::O_EXCL;
+    if (flags.0 & excl_create) == excl_create {
+      reply.error(Errno::EINVAL);
+      return;
+    }
+    // kernel passes O_TRUNC to open only with atomic truncation, otherwise, it sends setattr with size
+    let truncate = self.granted_caps.contains(InitFlags::FUSE_ATOMIC_O_TRUNC) && ((flags.0 & libc::O_TRUNC) != 0);
+    if truncate && let Some(recent_attrs) = &self.recent_attrs {
+      recent_attrs.forget(ino.0);
+    }
     call_js!(
-      self.cbs.open, (ino.0 as i64, flags.0), ParamsOfOpenedOrErr, reply,
+      self, self.cbs.open, (ino.0 as i64, flags.0, truncate), ParamsOfOpenedOrErr, reply,
       @initial-thread => |js_reply| {
         match js_reply {
-          ParamsOfOpenedOrErr::Params(params) => match FopenFlags::from_bits(params.flags) {
-            Some(flags) => reply.opened(FileHandle(params.fh as u64), flags),
+          ParamsOfOpenedOrErr::Params(params) => match params.fopen_flags() {
+            Some(flags) => {
+              self.count_opened_file(ino, params.fh as u64, flags);
+              reply.opened(FileHandle(params.fh as u64), flags);
+            },
             None => reply.error(Errno::EIO)
           },
-          ParamsOfOpenedOrErr::Err(code) => reply.error(Errno::from_i32(code)),
+          ParamsOfOpenedOrErr::Err(err) => reply.error(self.fs_error("open", err)),
         }
       }
     );
   }
 
   fn read(
-    &self, _req: &Request, ino: INodeNo, fh: FileHandle, offset: u64, size: u32, flags: OpenFlags,
+    &self, req: &Request, ino: INodeNo, fh: FileHandle, offset: u64, size: u32, flags: OpenFlags,
     lock_owner: Option<LockOwner>, reply: ReplyData,
   ) {
+    // zero-length read succeeds without data, and js side needn't see it
+    if size == 0 {
+      reply.data(&[]);
+      return;
+    }
+    let req_id = req.unique().0 as i64;
+    let slot = self.read_pool.as_ref()
+    .filter(|pool| (size as usize) <= pool.slot_size())
+    .and_then(|pool| pool.take_empty());
     let args = ReadArgs {
       offset: offset as i64,
       size,
       flags: flags.0,
-      lock_owner: lo_opt_i64(lock_owner)
+      lock_owner: lo_opt_i64(lock_owner),
+      req_id,
+      slot,
     };
+    let timed_out = Cell::new(false);
+    let read = self.cbs.direct_read.as_ref().filter(|_| self.is_direct_io(ino, fh)).unwrap_or(&self.cbs.read);
     call_js!(
-      self.cbs.read, (ino.0 as i64, fh.0 as i64, args), BufferOrErr, reply,
+      self, read, (ino.0 as i64, fh.0 as i64, args), BufferOrErr, reply, @idempotent,
       @initial-thread => |js_reply| {
         match js_reply {
           BufferOrErr::Ok(data) => reply.data(&data),
-          BufferOrErr::Err(code) => reply.error(Errno::from_i32(code)),
+          BufferOrErr::OkWithAttr(DataWithAttr { data, attr }) => {
+            self.keep_attr_of_io(fh, attr);
+            reply.data(&data);
+          },
+          BufferOrErr::InSlot(len) => {
+            let data = slot.zip(self.read_pool.as_ref()).and_then(|(slot, pool)| pool.data_in(slot, len as usize));
+            match data {
+              Some(data) => reply.data(data),
+              None => reply.error(Errno::EIO),
+            }
+          },
+          BufferOrErr::Sparse(sparse) if sparse.len > size => reply.error(Errno::EIO),
+          BufferOrErr::Sparse(sparse) => reply.data(&sparse.assemble(offset)),
+          BufferOrErr::Err(err) => reply.error(self.fs_error("read", err)),
         }
+      },
+      @on-timeout => {
+        timed_out.set(true);
+        self.abort_js_op(req_id)
       }
     );
+    // js side may still write into slot of a timed out read, hence, such slot is not reused
+    if let Some(slot) = slot && !timed_out.get() && let Some(pool) = &self.read_pool {
+      pool.give_back(slot);
+    }
   }
 
-  // fn write(
-  //   &mut self,
-  //   _req: &Request<'_>,
-  //   ino: u64,
-  //   fh: u64,
-  //   offset: i64,
-  //   data: &[u8],
-  //   write_flags: u32,
-  //   flags: i32,
-  //   lock_owner: Option<u64>,
-  //   reply: ReplyWrite,
-  // ) {
-  //   let data_len = data.len();
-  //   js_call!(self.cbs.test, "write", {
-  //     println!("🧐 fuser.write(ino: {ino:#x?}, fh: {fh}, offset: {offset}, data.len(): {}, write_flags: {write_flags:#x?}, flags: {flags:#x?}, lock_owner: {lock_owner:?})", data_len);
-  //     send_err!(ENOSYS);
-  //   });
-  // }
+  #[cfg(feature = "writable")]
+  fn write(
+    &self, req: &Request, ino: INodeNo, fh: FileHandle, offset: u64, data: &[u8], write_flags: WriteFlags,
+    flags: OpenFlags, lock_owner: Option<LockOwner>, reply: ReplyWrite,
+  ) {
+    let direct_write = self.cbs.direct_write.as_ref().filter(|_| self.is_direct_io(ino, fh));
+    let Some(write) = direct_write.or(self.cbs.write.as_ref()) else {
+      reply.error(Errno::ENOSYS);
+      return;
+    };
+    // zero-length write succeeds without writing, and js side needn't see it
+    if data.is_empty() {
+      reply.written(0);
+      return;
+    }
+    let append = (flags.0 & libc::O_APPEND) != 0;
+    let req_id = req.unique().0 as i64;
+    let write_in_js = |offset: u64, reply: ReplyWrite| {
+      let slot = self.write_pool.as_ref().and_then(|pool| pool.take(data));
+      let args = WriteArgs {
+        offset: offset as i64,
+        data: if slot.is_none() { Some(data.to_vec().into()) } else { None },
+        slot,
+        len: data.len() as u32,
+        write_flags: write_flags.bits(),
+        flags: flags.0,
+        lock_owner: lo_opt_i64(lock_owner),
+        append,
+        req_id,
+      };
+      let timed_out = Cell::new(false);
+      call_js!(
+        self, write, (ino.0 as i64, fh.0 as i64, args), WrittenOrErr, reply,
+        @initial-thread => |js_reply| {
+          match js_reply {
+            WrittenOrErr::Written(size) => {
+              if let Some(recent_attrs) = &self.recent_attrs {
+                recent_attrs.forget(ino.0);
+              }
+              self.make_durable(ino, fh, flags, lock_owner, size, reply);
+            },
+            WrittenOrErr::WrittenWithAttr(WrittenWithAttr { written, attr }) => {
+              self.keep_attr_of_io(fh, attr);
+              self.make_durable(ino, fh, flags, lock_owner, written, reply);
+            },
+            WrittenOrErr::Err(err) => reply.error(self.fs_error("write", err)),
+          }
+        },
+        @on-timeout => {
+          timed_out.set(true);
+          self.abort_js_op(req_id);
+        }
+      );
+      // js side may still read slot of a timed out write, hence, such slot is not reused
+      if let Some(slot) = slot && !timed_out.get() && let Some(pool) = &self.write_pool {
+        pool.give_back(slot);
+      }
+    };
+    // with writeback cache, kernel owns file size, and its offsets of appends are right
+    let enforce_append = append && self.settings.enforce_append
+      && !self.granted_caps.contains(InitFlags::FUSE_WRITEBACK_CACHE);
+    let write_now = |reply: ReplyWrite| {
+      if !enforce_append {
+        write_in_js(offset, reply);
+      } else if let Some(attr) = self.recent_attr_of_handle(ino, fh) {
+        write_in_js(attr.size, reply);
+      } else {
+        call_js!(
+          self, self.cbs.getattr, (ino.0 as i64, Some(fh.0 as i64)), FileAttrOrErr, reply,
+          @initial-thread => |js_reply| {
+            match js_reply {
+              FileAttrOrErr::Attr(attrs) => write_in_js(attrs.size as u64, reply),
+              FileAttrOrErr::Err(err) => reply.error(self.fs_error("write", err)),
+            }
+          }
+        );
+      }
+    };
+    let Some(prepare_write) = &self.cbs.prepare_write else {
+      write_now(reply);
+      return;
+    };
+    if self.prepared_fhs.lock().unwrap().contains(&(ino.0, fh.0)) {
+      write_now(reply);
+      return;
+    }
+    call_js!(
+      self, prepare_write, (ino.0 as i64, fh.0 as i64), i32, reply,
+      @initial-thread => |err_code| if err_code == 0 {
+        self.prepared_fhs.lock().unwrap().insert((ino.0, fh.0));
+        write_now(reply);
+      } else {
+        reply.error(self.js_errno(err_code));
+      }
+    );
+  }
 
+  #[cfg(feature = "writable")]
   fn flush(&self, _req: &Request, ino: INodeNo, fh: FileHandle, lock_owner: LockOwner, reply: ReplyEmpty) {
+    if self.settings.read_only {
+      reply.ok();
+      return;
+    }
+    let Some(flush) = &self.cbs.flush else {
+      reply.error(Errno::ENOSYS);
+      return;
+    };
     call_js!(
-      self.cbs.flush, (ino.0 as i64, fh.0 as i64, lock_owner.0 as i64), i32, reply,
-      @initial-thread => |err_code| { send_empty(err_code, reply); }
+      self, flush, (ino.0 as i64, fh.0 as i64, lock_owner.0 as i64), i32, reply,
+      @initial-thread => |err_code| {
+        self.note_flushed(ino, err_code);
+        self.send_empty(err_code, reply);
+      }
     );
   }
 
@@ -351,73 +1341,142 @@ impl Filesystem for CallbacksProxy {
     &self, _req: &Request, ino: INodeNo, fh: FileHandle, flags: OpenFlags,
     lock_owner: Option<LockOwner>, flush: bool, reply: ReplyEmpty,
   ) {
+    self.poll_notifiers.lock().unwrap().remove(&fh.0);
+    self.prepared_fhs.lock().unwrap().remove(&(ino.0, fh.0));
+    self.direct_io_fhs.lock().unwrap().remove(&(ino.0, fh.0));
+    self.count_release();
     let args = ReleaseArgs {
       flags: flags.0, flush, lock_owner: lo_opt_i64(lock_owner)
     };
     call_js!(
-      self.cbs.release, (ino.0 as i64, fh.0 as i64, args), i32, reply,
-      @initial-thread => |err_code| { send_empty(err_code, reply); }
+      self, self.cbs.release, (ino.0 as i64, fh.0 as i64, args), (), reply,
+      @initial-thread => |_| reply.ok()
     );
   }
 
+  #[cfg(feature = "writable")]
   fn fsync(&self, _req: &Request, ino: INodeNo, fh: FileHandle, datasync: bool, reply: ReplyEmpty) {
     call_js!(
-      self.cbs.fsync, (ino.0 as i64, fh.0 as i64, datasync), i32, reply,
-      @initial-thread => |err_code| { send_empty(err_code, reply); }
+      self, self.cbs.fsync, (ino.0 as i64, fh.0 as i64, datasync), i32, reply,
+      @initial-thread => |err_code| {
+        self.note_flushed(ino, err_code);
+        self.send_empty(err_code, reply);
+      }
     );
   }
 
   fn opendir(&self, _req: &Request, ino: INodeNo, flags: OpenFlags, reply: ReplyOpen) {
+    if self.granted_caps.contains(InitFlags::FUSE_NO_OPENDIR_SUPPORT) {
+      reply.error(Errno::ENOSYS);
+      return;
+    }
     call_js!(
-      self.cbs.opendir, (ino.0 as i64, flags.0), ParamsOfOpenedOrErr, reply,
+      self, self.cbs.opendir, (ino.0 as i64, flags.0), ParamsOfOpenedOrErr, reply,
       @initial-thread => |js_reply| {
         match js_reply {
-          ParamsOfOpenedOrErr::Params(params) => match FopenFlags::from_bits(params.flags) {
-            Some(flags) => reply.opened(FileHandle(params.fh as u64), flags),
+          ParamsOfOpenedOrErr::Params(params) => match params.fopen_flags() {
+            Some(mut flags) => {
+              if !self.cache_dir_supported {
+                flags.remove(FopenFlags::FOPEN_CACHE_DIR);
+              }
+              self.open_handles.fetch_add(1, Ordering::Relaxed);
+              reply.opened(FileHandle(params.fh as u64), flags);
+            },
             None => reply.error(Errno::EIO)
           }
-          ParamsOfOpenedOrErr::Err(code) => reply.error(Errno::from_i32(code)),
+          ParamsOfOpenedOrErr::Err(err) => reply.error(self.fs_error("opendir", err)),
         }
       }
     );
   }
 
-  fn readdir(&self, _req: &Request, ino: INodeNo, fh: FileHandle, offset: u64, mut reply: ReplyDirectory) {
+  fn readdir(&self, req: &Request, ino: INodeNo, fh: FileHandle, offset: u64, reply: ReplyDirectory) {
+    if let Some(dir_cache) = &self.dir_cache && let Some(entries) = dir_cache.get(ino.0, fh.0, offset) {
+      send_dir_entries(&entries, reply);
+      return;
+    }
+    let token = match self.dir_cursors.take(ino.0, fh.0, offset) {
+      Some((token, pending)) if !pending.is_empty() => {
+        self.send_page_with_cursor(ino, fh, offset, pending, token, reply);
+        return;
+      },
+      Some((token, _)) => Some(token),
+      None => None,
+    };
+    // listing continues after added "." and ".." with the first entry of js side
+    let at_start = (offset == 0) || (offset >= DOT_OFFSET);
+    let js_offset = if at_start { 0 } else { offset as i64 };
+    let req_id = req.unique().0 as i64;
     call_js!(
-      self.cbs.readdir, (ino.0 as i64, fh.0 as i64, offset as i64), DirListing, reply,
-      @initial-thread => |js_reply| {
+      self, self.cbs.readdir, (ino.0 as i64, fh.0 as i64, js_offset, req_id, token), DirListing, reply,
+      @idempotent, @initial-thread => |js_reply| {
         match js_reply {
           DirListing::Lst(lst) => {
+            let mut entries = self.to_cached_dir_entries(ino, lst);
+            if at_start {
+              entries = self.with_dot_entries(ino, offset, entries);
+            }
+            if let Some(dir_cache) = &self.dir_cache {
+              dir_cache.add(ino.0, fh.0, offset, &entries);
+            }
+            send_dir_entries(&entries, reply);
+          },
+          DirListing::Partial(PartialDirListing { lst, token }) => {
+            let mut entries = self.to_cached_dir_entries(ino, lst);
+            if at_start {
+              entries = self.with_dot_entries(ino, offset, entries);
+            }
+            if let Some(dir_cache) = &self.dir_cache {
+              dir_cache.add(ino.0, fh.0, offset, &entries);
+            }
+            self.send_page_with_cursor(ino, fh, offset, entries, token, reply);
+          },
+          DirListing::Err(err) => reply.error(self.fs_error("readdir", err)),
+        }
+      },
+      @on-timeout => self.abort_js_op(req_id)
+    );
+  }
+
+  fn readdirplus(
+    &self, req: &Request, ino: INodeNo, fh: FileHandle, offset: u64, mut reply: ReplyDirectoryPlus
+  ) {
+    let Some(readdirplus) = &self.cbs.readdirplus else {
+      reply.error(Errno::ENOSYS);
+      return;
+    };
+    let req_id = req.unique().0 as i64;
+    call_js!(
+      self, readdirplus, (ino.0 as i64, fh.0 as i64, offset as i64, req_id), DirListingPlus, reply,
+      @initial-thread => |js_reply| {
+        match js_reply {
+          DirListingPlus::Lst(lst) => {
             for entry in lst {
+              let has_xattrs = entry.attr.has_xattrs.unwrap_or(true);
+              let attr = self.attr_to_fuse(entry.attr);
               let buffer_full = reply.add(
-                INodeNo(entry.ino as u64), entry.offset as u64, to_file_type(&entry.kind), OsStr::new(&entry.name)
+                attr.ino, entry.offset as u64, OsStr::new(&entry.name), &self.settings.entry_ttl, &attr,
+                Generation(entry.generation.unwrap_or(0) as u64)
               );
+              // entry, that doesn't fit, isn't added, and will be the first one in the next readdirplus
               if buffer_full {
                 break;
               }
+              // kernel doesn't count lookups of entries "." and ".."
+              if (entry.name != ".") && (entry.name != "..") {
+                self.count_lookup(&attr, has_xattrs);
+                self.inode_parents.set(attr.ino.0, ino.0);
+              }
             }
             reply.ok();
           },
-          DirListing::Err(code) => reply.error(Errno::from_i32(code)),
+          DirListingPlus::Err(err) => reply.error(self.fs_error("readdirplus", err)),
         }
-      }
+      },
+      @on-timeout => self.abort_js_op(req_id)
     );
   }
 
-  // fn readdirplus(
-  //   &mut self,
-  //   _req: &Request<'_>,
-  //   ino: u64,
-  //   fh: u64,
-  //   offset: i64,
-  //   reply: ReplyDirectoryPlus,
-  // ) {
-  //   js_call!(self.cbs.test, "readdirplus", {
-  //     println!("🧐 fuser.readdirplus(ino: {ino:#x?}, fh: {fh}, offset: {offset})");
-  //     send_err!(ENOSYS);
-  //   });
-  // }
-
   fn releasedir(
     &self,
     _req: &Request,
@@ -426,21 +1485,46 @@ impl Filesystem for CallbacksProxy {
     flags: OpenFlags,
     reply: ReplyEmpty,
   ) {
+    self.count_release();
+    if let Some(dir_cache) = &self.dir_cache {
+      dir_cache.release(ino.0, fh.0);
+    }
+    self.dir_cursors.release(ino.0, fh.0);
     call_js!(
-      self.cbs.releasedir, (ino.0 as i64, fh.0 as i64, flags.0), i32, reply,
-      @initial-thread => |err_code| { send_empty(err_code, reply); }
+      self, self.cbs.releasedir, (ino.0 as i64, fh.0 as i64, flags.0), i32, reply,
+      @initial-thread => |err_code| { self.send_empty(err_code, reply); }
     );
   }
 
   fn fsyncdir(&self, _req: &Request, ino: INodeNo, fh: FileHandle, datasync: bool, reply: ReplyEmpty) {
     call_js!(
-      self.cbs.fsyncdir, (ino.0 as i64, fh.0 as i64, datasync), i32, reply,
-      @initial-thread => |err_code| { send_empty(err_code, reply); }
+      self, self.cbs.fsyncdir, (ino.0 as i64, fh.0 as i64, datasync), i32, reply,
+      @initial-thread => |err_code| { self.send_empty(err_code, reply); }
     );
   }
 
-  fn statfs(&self, _req: &Request, _ino: INodeNo, reply: ReplyStatfs) {
-    reply.statfs(0, 0, 0, 0, 0, BLOCK_SIZE as u32, 255, BLOCK_SIZE as u32);
+  fn statfs(&self, _req: &Request, ino: INodeNo, reply: ReplyStatfs) {
+    let namelen = self.settings.max_name_length;
+    let send_stats = |st: StatfsResult, reply: ReplyStatfs| {
+      let st = with_statfs_defaults(&self.settings, st);
+      reply.statfs(
+        st.blocks as u64, st.bfree as u64, st.bavail as u64, st.files as u64, st.ffree as u64,
+        st.bsize.unwrap_or_default(), namelen, st.frsize.unwrap_or_default()
+      );
+    };
+    let Some(statfs) = &self.cbs.statfs else {
+      send_stats(StatfsResult::default(), reply);
+      return;
+    };
+    call_js!(
+      self, statfs, ino.0 as i64, StatfsOrErr, reply,
+      @initial-thread => |js_reply| {
+        match js_reply {
+          StatfsOrErr::Stats(st) => send_stats(st, reply),
+          StatfsOrErr::Err(err) => reply.error(self.fs_error("statfs", err)),
+        }
+      }
+    );
   }
 
   // fn setxattr(
@@ -461,51 +1545,110 @@ impl Filesystem for CallbacksProxy {
   // }
 
   fn getxattr(&self, _req: &Request, ino: INodeNo, name: &OsStr, size: u32, reply: ReplyXattr) {
+    if self.settings.no_xattr {
+      return reply.error(Errno::ENOSYS);
+    }
     call_js!(
-      self.cbs.getxattr, (ino.0 as i64, str_from_os(name), size), XAttrBytesOrErr, reply,
-      @initial-thread => |js_reply| { send_xattr(js_reply, reply); }
+      self, self.cbs.getxattr, (ino.0 as i64, str_from_os(name), size), XAttrBytesOrErr, reply,
+      @initial-thread => |js_reply| { self.send_xattr("getxattr", js_reply, size, reply); }
     );
   }
 
   fn listxattr(&self, _req: &Request, ino: INodeNo, size: u32, reply: ReplyXattr) {
+    if self.settings.no_xattr {
+      return reply.error(Errno::ENOSYS);
+    }
+    if self.lookups.is_without_xattrs(ino.0) {
+      if size == 0 {
+        reply.size(0);
+      } else {
+        reply.data(&[]);
+      }
+      return;
+    }
     call_js!(
-      self.cbs.listxattr, (ino.0 as i64, size), XAttrBytesOrErr, reply,
-      @initial-thread => |js_reply| { send_xattr(js_reply, reply); }
+      self, self.cbs.listxattr, (ino.0 as i64, size), XAttrBytesOrErr, reply,
+      @initial-thread => |js_reply| { self.send_xattr("listxattr", js_reply, size, reply); }
     );
   }
 
   fn removexattr(&self, _req: &Request, ino: INodeNo, name: &OsStr, reply: ReplyEmpty) {
+    if self.settings.no_xattr {
+      return reply.error(Errno::ENOSYS);
+    }
     let name_str = name.display().to_string();
     call_js!(
-      self.cbs.removexattr, (ino.0 as i64, name_str), i32, reply,
-      @initial-thread => |js_reply| { send_empty(js_reply, reply); }
+      self, self.cbs.removexattr, (ino.0 as i64, name_str), i32, reply,
+      @initial-thread => |js_reply| { self.send_empty(js_reply, reply); }
     );
   }
 
   fn access(&self, _req: &Request, ino: INodeNo, mask: AccessFlags, reply: ReplyEmpty) {
+    // F_OK only checks existence, which getattr tells as well
+    if mask.is_empty() {
+      if let Some(recent_attrs) = &self.recent_attrs && recent_attrs.get(ino.0, None).is_some() {
+        reply.ok();
+        return;
+      }
+      call_js!(
+        self, self.cbs.getattr, (ino.0 as i64, None), FileAttrOrErr, reply,
+        @initial-thread => |js_reply| {
+          match js_reply {
+            FileAttrOrErr::Attr(_) => reply.ok(),
+            FileAttrOrErr::Err(err) => reply.error(self.fs_error("access", err)),
+          }
+        }
+      );
+      return;
+    }
     call_js!(
-      self.cbs.access, (ino.0 as i64, mask.bits()), i32, reply,
-      @initial-thread => |err_code| { send_empty(err_code, reply); }
+      self, self.cbs.access, (ino.0 as i64, mask.bits()), AccessResult, reply,
+      @initial-thread => |js_reply| {
+        match js_reply {
+          AccessResult::Granted => reply.ok(),
+          AccessResult::Denied(err) => reply.error(self.fs_error("access", err)),
+        }
+      }
     );
   }
 
-  // fn create(
-  //   &mut self,
-  //   _req: &Request<'_>,
-  //   parent: u64,
-  //   name: &OsStr,
-  //   mode: u32,
-  //   umask: u32,
-  //   flags: i32,
-  //   reply: ReplyCreate,
-  // ) {
-  //   let name_str = name.display().to_string();
-  //   js_call!(self.cbs.test, "create", {
-  //     println!("🧐 fuser.create(parent: {parent:#x?}, name: {name_str:?}, mode: {mode}, umask: {umask:#x?}, flags: {flags:#x?})");
-  //     send_err!(ENOSYS);
-  //   });
-  // }
+  #[cfg(feature = "writable")]
+  fn create(
+    &self, _req: &Request, parent: INodeNo, name: &OsStr, mode: u32, umask: u32, flags: i32, reply: ReplyCreate,
+  ) {
+    // kernel retries with mknod and open, when create isn't implemented
+    let Some(create) = &self.cbs.create else {
+      reply.error(Errno::ENOSYS);
+      return;
+    };
+    let name_str = match self.normalize_name(name) {
+      Ok(name) => name,
+      Err(err) => return reply.error(err),
+    };
+    self.dir_changed(parent);
+    let args = CreateArgs { mode, umask, flags };
+    call_js!(
+      self, create, (parent.0 as i64, name_str, args), CreatedOrErr, reply,
+      @initial-thread => |js_reply| {
+        match js_reply {
+          CreatedOrErr::Created(CreatedResult { entry, opened }) => match opened.fopen_flags() {
+            Some(flags) => {
+              let attr = self.attr_of_entry(parent, entry.attr);
+              self.count_opened_file(attr.ino, opened.fh as u64, flags);
+              reply.created(
+                &self.ttl_of_entry(entry.ttl), &attr,
+                Generation(entry.generation as u64), FileHandle(opened.fh as u64), flags
+              );
+            },
+            None => reply.error(Errno::EIO)
+          },
+          CreatedOrErr::Err(err) => reply.error(self.fs_error("create", err)),
+        }
+      }
+    );
+  }
 
+  #[cfg(feature = "locking")]
   fn getlk(
     &self, _req: &Request, _ino: INodeNo, _fh: FileHandle, _lock_owner: LockOwner,
     _start: u64, _end: u64, _typ: i32, _pid: u32, reply: ReplyLock,
@@ -513,6 +1656,7 @@ impl Filesystem for CallbacksProxy {
     reply.error(Errno::ENOSYS);
   }
 
+  #[cfg(feature = "locking")]
   fn setlk(
     &self, _req: &Request, _ino: INodeNo, _fh: FileHandle, _lock_owner: LockOwner,
     _start: u64, _end: u64, _typ: i32, _pid: u32, _sleep: bool, reply: ReplyEmpty,
@@ -520,43 +1664,65 @@ impl Filesystem for CallbacksProxy {
     reply.error(Errno::ENOSYS);
   }
 
+  #[cfg(feature = "extended-ops")]
   fn bmap(&self, _req: &Request, _ino: INodeNo, _blocksize: u32, _idx: u64, reply: ReplyBmap) {
     reply.error(Errno::ENOSYS);
   }
 
-  // fn ioctl(
-  //   &mut self,
-  //   _req: &Request<'_>,
-  //   ino: u64,
-  //   fh: u64,
-  //   flags: u32,
-  //   cmd: u32,
-  //   in_data: &[u8],
-  //   out_size: u32,
-  //   reply: ReplyIoctl,
-  // ) {
-  //   let in_data_len = in_data.len();
-  //   js_call!(self.cbs.test, "bmap", {
-  //     println!("🧐 fuser.ioctl(ino: {ino:#x?}, fh: {fh}, flags: {flags}, cmd: {cmd}, in_data.len(): {}, out_size: {out_size})", in_data_len);
-  //     send_err!(ENOSYS);
-  //   });
-  // }
+  #[cfg(feature = "extended-ops")]
+  fn ioctl(
+    &self, _req: &Request, ino: INodeNo, fh: FileHandle, flags: IoctlFlags, cmd: u32, in_data: &[u8],
+    out_size: u32, reply: ReplyIoctl,
+  ) {
+    // close-on-exec is a flag of process' descriptor, and some libc implementations issue these ioctls on open,
+    // failing opens, if filesystem returns an error
+    if (cmd == libc::FIOCLEX as u32) || (cmd == libc::FIONCLEX as u32) {
+      reply.ioctl(0, &[]);
+      return;
+    }
+    let Some(ioctl) = &self.cbs.ioctl else {
+      reply.error(Errno::ENOSYS);
+      return;
+    };
+    let args = IoctlArgs {
+      flags: flags.bits(),
+      cmd,
+      in_data: in_data.to_vec().into(),
+      out_size,
+    };
+    call_js!(
+      self, ioctl, (ino.0 as i64, fh.0 as i64, args), IoctlOrErr, reply,
+      @initial-thread => |js_reply| {
+        match js_reply {
+          IoctlOrErr::Ok(res) => reply.ioctl(res.result, &res.data),
+          IoctlOrErr::Err(err) => reply.error(self.fs_error("ioctl", err)),
+        }
+      }
+    );
+  }
 
-  // fn poll(
-  //   &mut self,
-  //   _req: &Request<'_>,
-  //   ino: u64,
-  //   fh: u64,
-  //   ph: PollHandle,
-  //   events: u32,
-  //   flags: u32,
-  //   reply: ReplyPoll,
-  // ) {
-  //   js_call!(self.cbs.test, "poll", {
-  //     println!("🧐 fuser.poll(ino: {ino:#x?}, fh: {fh}, ph: {ph:?}, events: {events}, flags: {flags})");
-  //     send_err!(ENOSYS);
-  //   });
-  // }
+  #[cfg(feature = "extended-ops")]
+  fn poll(
+    &self, _req: &Request, ino: INodeNo, fh: FileHandle, ph: PollNotifier, events: fuser::PollEvents,
+    flags: PollFlags, reply: ReplyPoll,
+  ) {
+    let Some(poll) = &self.cbs.poll else {
+      reply.error(Errno::ENOSYS);
+      return;
+    };
+    if flags.contains(PollFlags::FUSE_POLL_SCHEDULE_NOTIFY) {
+      self.poll_notifiers.lock().unwrap().insert(fh.0, ph);
+    }
+    call_js!(
+      self, poll, (ino.0 as i64, fh.0 as i64, PollEvents::from_fuse(events)), PollOrErr, reply,
+      @initial-thread => |js_reply| {
+        match js_reply {
+          PollOrErr::Events(events) => reply.poll(events.into_fuse()),
+          PollOrErr::Err(err) => reply.error(self.fs_error("poll", err)),
+        }
+      }
+    );
+  }
 
   // fn fallocate(
   //   &mut self,
@@ -574,42 +1740,158 @@ impl Filesystem for CallbacksProxy {
   //   });
   // }
 
-  // fn lseek(
-  //   &mut self,
-  //   _req: &Request<'_>,
-  //   ino: u64,
-  //   fh: u64,
-  //   offset: i64,
-  //   whence: i32,
-  //   reply: ReplyLseek,
-  // ) {
-  //   js_call!(self.cbs.test, "lseek", {
-  //     println!("🧐 fuser.lseek(ino: {ino:#x?}, fh: {fh}, offset: {offset}, whence: {whence})");
-  //     send_err!(ENOSYS);
-  //   });
-  // }
+  #[cfg(feature = "extended-ops")]
+  fn lseek(
+    &self, _req: &Request, ino: INodeNo, fh: FileHandle, offset: i64, whence: i32, reply: ReplyLseek
+  ) {
+    let Some(lseek) = &self.cbs.lseek else {
+      reply.error(Errno::ENOSYS);
+      return;
+    };
+    call_js!(
+      self, lseek, (ino.0 as i64, fh.0 as i64, offset, whence), OffsetOrErr, reply,
+      @initial-thread => |js_reply| {
+        let found = match js_reply {
+          OffsetOrErr::Offset(found) => found,
+          OffsetOrErr::BigOffset(found) => match found.get_i64() {
+            (found, true) => found,
+            _ => {
+              reply.error(Errno::EOVERFLOW);
+              return;
+            },
+          },
+          OffsetOrErr::Err(err) => {
+            reply.error(self.fs_error("lseek", err));
+            return;
+          },
+        };
+        // data and holes are searched only forward
+        if found < offset {
+          reply.error(Errno::EIO);
+        } else {
+          reply.offset(found);
+        }
+      }
+    );
+  }
 
-  // fn copy_file_range(
-  //   &mut self,
-  //   _req: &Request<'_>,
-  //   ino_in: u64,
-  //   fh_in: u64,
-  //   offset_in: i64,
-  //   ino_out: u64,
-  //   fh_out: u64,
-  //   offset_out: i64,
-  //   len: u64,
-  //   flags: u32,
-  //   reply: ReplyWrite,
-  // ) {
-  //   js_call!(self.cbs.test, "copy_file_range", {
-  //     println!("🧐 fuser.copy_file_range(ino_in: {ino_in:#x?}, fh_in: {fh_in}, offset_in: {offset_in}, ino_out: {ino_out:#x?}, fh_out: {fh_out}, offset_out: {offset_out}, len: {len}, flags: {flags})");
-  //     send_err!(ENOSYS);
-  //   });
-  // }
+  #[cfg(feature = "extended-ops")]
+  fn copy_file_range(
+    &self, _req: &Request, ino_in: INodeNo, fh_in: FileHandle, offset_in: u64, ino_out: INodeNo,
+    fh_out: FileHandle, offset_out: u64, len: u64, flags: CopyFileRangeFlags, reply: ReplyWrite,
+  ) {
+    let Some(copy_file_range) = &self.cbs.copy_file_range else {
+      reply.error(Errno::ENOSYS);
+      return;
+    };
+    if (ino_in == ino_out) && ranges_overlap(offset_in, offset_out, len) {
+      reply.error(Errno::EINVAL);
+      return;
+    }
+    let args = CopyFileRangeArgs {
+      offset_in: offset_in as i64,
+      ino_out: ino_out.0 as i64,
+      fh_out: fh_out.0 as i64,
+      offset_out: offset_out as i64,
+      len: len as i64,
+      flags: flags.bits() as i64,
+    };
+    call_js!(
+      self, copy_file_range, (ino_in.0 as i64, fh_in.0 as i64, args), WrittenOrErr, reply,
+      @initial-thread => |js_reply| {
+        match js_reply {
+          WrittenOrErr::Written(size) => {
+            if let Some(recent_attrs) = &self.recent_attrs {
+              recent_attrs.forget(ino_out.0);
+            }
+            reply.written(size);
+          },
+          WrittenOrErr::WrittenWithAttr(WrittenWithAttr { written, attr }) => {
+            self.keep_attr_of_io(fh_out, attr);
+            reply.written(written);
+          },
+          WrittenOrErr::Err(err) => reply.error(self.fs_error("copy_file_range", err)),
+        }
+      }
+    );
+  }
+
+  #[cfg(target_os = "macos")]
+  fn getxtimes(&self, _req: &Request, ino: INodeNo, reply: fuser::ReplyXTimes) {
+    let Some(getxtimes) = &self.cbs.getxtimes else {
+      reply.error(Errno::ENOSYS);
+      return;
+    };
+    call_js!(
+      self, getxtimes, (ino.0 as i64), XTimesOrErr, reply,
+      @initial-thread => |js_reply| {
+        match js_reply {
+          XTimesOrErr::Times(t) => reply.xtimes(system_time_from(t.bkuptime), system_time_from(t.crtime)),
+          XTimesOrErr::Err(err) => reply.error(self.fs_error("getxtimes", err)),
+        }
+      }
+    );
+  }
 
   #[cfg(target_os = "macos")]
   fn setvolname(&self, _req: &Request, _name: &OsStr, reply: ReplyEmpty) {
     reply.error(Errno::EPERM);
   }
 }
+
+#[cfg(test)]
+mod tests {
+
+  use super::*;
+
+  fn entry(name: &str, ino: u64, offset: u64) -> CachedDirEntry {
+    CachedDirEntry { ino, offset, kind: FileType::Directory, name: name.to_string() }
+  }
+
+  fn names_and_offsets(entries: &[CachedDirEntry]) -> Vec<(&str, u64)> {
+    entries.iter().map(|entry| (entry.name.as_str(), entry.offset)).collect()
+  }
+
+  #[test]
+  fn adds_dot_entries_with_own_offsets_before_listing() {
+    let entries = with_dot_entries(5, 3, 0, vec![entry("a", 6, 1), entry("b", 7, 2)]);
+    assert_eq!(names_and_offsets(&entries), vec![(".", DOT_OFFSET), ("..", DOTDOT_OFFSET), ("a", 1), ("b", 2)]);
+    assert_eq!((entries[0].ino, entries[1].ino), (5, 3));
+  }
+
+  #[test]
+  fn adds_only_dot_entries_not_yet_listed() {
+    // kernel continues after "." with offset of it
+    let entries = with_dot_entries(5, 3, DOT_OFFSET, vec![entry("a", 6, 1)]);
+    assert_eq!(names_and_offsets(&entries), vec![("..", DOTDOT_OFFSET), ("a", 1)]);
+    let entries = with_dot_entries(5, 3, DOTDOT_OFFSET, vec![entry("a", 6, 1)]);
+    assert_eq!(names_and_offsets(&entries), vec![("a", 1)]);
+    // js side may give these itself
+    let entries = with_dot_entries(5, 3, 0, vec![entry(".", 5, 1), entry("..", 3, 2)]);
+    assert_eq!(names_and_offsets(&entries), vec![(".", 1), ("..", 2)]);
+  }
+
+  #[test]
+  fn refuses_clearing_of_mount_flags() {
+    // values of UF_IMMUTABLE and UF_APPEND on macOS
+    let (immutable, append) = (0x2, 0x4);
+    assert!(clears_mount_flags(immutable, Some(BsdFileFlags::from_bits_retain(0))));
+    assert!(clears_mount_flags(immutable | append, Some(BsdFileFlags::from_bits_retain(immutable))));
+    assert!(!clears_mount_flags(immutable, Some(BsdFileFlags::from_bits_retain(immutable | append))));
+    assert!(!clears_mount_flags(immutable, None));
+    assert!(!clears_mount_flags(0, Some(BsdFileFlags::from_bits_retain(0))));
+  }
+
+  #[cfg(feature = "extended-ops")]
+  #[test]
+  fn finds_overlap_of_copied_ranges() {
+    assert!(ranges_overlap(0, 0, 1));
+    assert!(ranges_overlap(0, 99, 100));
+    assert!(ranges_overlap(99, 0, 100));
+    assert!(!ranges_overlap(0, 100, 100));
+    assert!(!ranges_overlap(100, 0, 100));
+    assert!(!ranges_overlap(5, 5, 0));
+    assert!(ranges_overlap(u64::MAX - 1, u64::MAX - 2, u64::MAX));
+  }
+
+}
@@ -0,0 +1,92 @@
+// Copyright(c) 2026 3NSoft Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{sync::{Condvar, Mutex}, time::{Duration, Instant}};
+
+/// Limit of operations, that are dispatched to js side at the same time.
+pub struct InFlightLimit {
+  max: usize,
+  count: Mutex<usize>,
+  released: Condvar,
+}
+
+impl InFlightLimit {
+
+  /// Makes limit with a given maximum, or without limit for `None`.
+  pub fn new(max: Option<u32>) -> Self {
+    InFlightLimit {
+      max: max.map_or(usize::MAX, |max| max.max(1) as usize),
+      count: Mutex::new(0),
+      released: Condvar::new(),
+    }
+  }
+
+  /// Waits for an operation slot, returning `None`, when there is still no slot after timeout.
+  pub fn acquire(&self, timeout: Duration) -> Option<InFlightPermit<'_>> {
+    let deadline = Instant::now() + timeout;
+    let mut count = self.count.lock().unwrap();
+    while *count >= self.max {
+      let left = deadline.checked_duration_since(Instant::now())?;
+      count = self.released.wait_timeout(count, left).unwrap().0;
+    }
+    *count += 1;
+    Some(InFlightPermit(self))
+  }
+
+}
+
+/// Slot of an operation, that is given back, when permit is dropped.
+pub struct InFlightPermit<'a>(&'a InFlightLimit);
+
+impl Drop for InFlightPermit<'_> {
+  fn drop(&mut self) {
+    *self.0.count.lock().unwrap() -= 1;
+    self.0.released.notify_one();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+
+  use std::{sync::Arc, thread};
+
+  use super::*;
+
+  #[test]
+  fn times_out_when_all_slots_are_taken() {
+    let limit = InFlightLimit::new(Some(2));
+    let _first = limit.acquire(Duration::ZERO).unwrap();
+    let second = limit.acquire(Duration::ZERO).unwrap();
+    let started = Instant::now();
+    assert!(limit.acquire(Duration::from_millis(50)).is_none());
+    assert!(started.elapsed() >= Duration::from_millis(50));
+    drop(second);
+    assert!(limit.acquire(Duration::ZERO).is_some());
+  }
+
+  #[test]
+  fn gives_released_slot_to_waiting_operation() {
+    let limit = Arc::new(InFlightLimit::new(Some(1)));
+    let permit = limit.acquire(Duration::ZERO).unwrap();
+    let waiting = {
+      let limit = limit.clone();
+      thread::spawn(move || limit.acquire(Duration::from_secs(10)).is_some())
+    };
+    thread::sleep(Duration::from_millis(20));
+    drop(permit);
+    assert!(waiting.join().unwrap());
+  }
+
+}
@@ -0,0 +1,71 @@
+// Copyright(c) 2026 3NSoft Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{collections::HashMap, sync::Mutex};
+
+use fuser::FileType;
+
+/// Kinds of inodes, with which these were given to kernel, kept until kernel forgets inodes. Kind of a live inode
+/// shouldn't change, and a change usually points to a bug on js side.
+#[derive(Default)]
+pub struct InodeKinds(Mutex<HashMap<u64, (FileType, u64)>>);
+
+impl InodeKinds {
+
+  /// Counts a lookup of an inode with a given kind.
+  pub fn add(&self, ino: u64, kind: FileType) {
+    let mut kinds = self.0.lock().unwrap();
+    let (known_kind, count) = kinds.entry(ino).or_insert((kind, 0));
+    *known_kind = kind;
+    *count += 1;
+  }
+
+  /// Checks that given kind differs from one, with which inode is known to kernel.
+  pub fn is_changed(&self, ino: u64, kind: FileType) -> bool {
+    self.0.lock().unwrap().get(&ino).is_some_and(|(known_kind, _)| *known_kind != kind)
+  }
+
+  pub fn forget(&self, ino: u64, nlookup: u64) {
+    let mut kinds = self.0.lock().unwrap();
+    if let Some((_, count)) = kinds.get_mut(&ino) {
+      *count = count.saturating_sub(nlookup);
+      if *count == 0 {
+        kinds.remove(&ino);
+      }
+    }
+  }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::*;
+
+  #[test]
+  fn flags_kind_changes_of_known_inodes() {
+    let kinds = InodeKinds::default();
+    assert!(!kinds.is_changed(5, FileType::Directory));
+    kinds.add(5, FileType::RegularFile);
+    kinds.add(5, FileType::RegularFile);
+    assert!(!kinds.is_changed(5, FileType::RegularFile));
+    assert!(kinds.is_changed(5, FileType::Directory));
+    kinds.forget(5, 1);
+    assert!(kinds.is_changed(5, FileType::Directory));
+    kinds.forget(5, 1);
+    assert!(!kinds.is_changed(5, FileType::Directory));
+  }
+
+}
@@ -0,0 +1,116 @@
+// Copyright(c) 2026 3NSoft Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{collections::{HashMap, HashSet}, sync::Mutex};
+
+use fuser::INodeNo;
+
+/// Parents of inodes, as last seen in entries, given to kernel, like replies to lookup and mkdir. With these, entry
+/// ".." of a listing gets inode of an actual parent directory, and descendants of a directory can be found. Inodes,
+/// moved by rename, keep the old parent, till these are looked up again, and hard links keep only the last parent.
+/// Parent is dropped, when lookup count of inode drops to zero, hence, only inodes, known to kernel, are here.
+#[derive(Default)]
+pub struct InodeParents(Mutex<HashMap<u64, u64>>);
+
+impl InodeParents {
+
+  pub fn set(&self, ino: u64, parent: u64) {
+    self.0.lock().unwrap().insert(ino, parent);
+  }
+
+  /// Returns parent of an inode. Root is its own parent.
+  pub fn get(&self, ino: u64) -> Option<u64> {
+    if ino == INodeNo::ROOT.0 {
+      Some(INodeNo::ROOT.0)
+    } else {
+      self.0.lock().unwrap().get(&ino).copied()
+    }
+  }
+
+  pub fn forget(&self, ino: u64) {
+    self.0.lock().unwrap().remove(&ino);
+  }
+
+  /// Returns all known descendants of a directory, in order from nearest ones.
+  pub fn descendants(&self, dir: u64) -> Vec<u64> {
+    let mut children: HashMap<u64, Vec<u64>> = HashMap::new();
+    for (&ino, &parent) in self.0.lock().unwrap().iter() {
+      if ino != parent {
+        children.entry(parent).or_default().push(ino);
+      }
+    }
+    let mut found = vec![];
+    let mut seen = HashSet::from([dir]);
+    let mut next = 0;
+    let mut current = dir;
+    loop {
+      for &child in children.get(&current).into_iter().flatten() {
+        if seen.insert(child) {
+          found.push(child);
+        }
+      }
+      let Some(&child) = found.get(next) else {
+        break;
+      };
+      current = child;
+      next += 1;
+    }
+    found
+  }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::*;
+
+  #[test]
+  fn finds_descendants_from_nearest_ones() {
+    let parents = InodeParents::default();
+    parents.set(2, 1);
+    parents.set(3, 2);
+    parents.set(4, 3);
+    parents.set(5, 2);
+    parents.set(6, 1);
+    let found = parents.descendants(2);
+    assert_eq!(found.len(), 3);
+    assert_eq!(found[2], 4);
+    assert!(found[..2].contains(&3) && found[..2].contains(&5));
+    assert_eq!(parents.descendants(4), Vec::<u64>::new());
+  }
+
+  #[test]
+  fn stops_on_cycles_of_stale_parents() {
+    // inodes, moved by rename, keep old parents, and may look like own ancestors
+    let parents = InodeParents::default();
+    parents.set(2, 3);
+    parents.set(3, 2);
+    let mut found = parents.descendants(2);
+    found.sort();
+    assert_eq!(found, vec![3]);
+  }
+
+  #[test]
+  fn gives_root_as_its_own_parent() {
+    let parents = InodeParents::default();
+    parents.set(2, 1);
+    assert_eq!(parents.get(1), Some(1));
+    assert_eq!(parents.get(2), Some(1));
+    parents.forget(2);
+    assert_eq!(parents.get(2), None);
+  }
+
+}
@@ -13,20 +13,33 @@
 // You should have received a copy of the GNU Lesser General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::time::{Duration, SystemTime};
+use std::{sync::Arc, time::{Duration, SystemTime}};
 
-use fuser::{FileType, INodeNo};
+use fuser::{FileType, FopenFlags, INodeNo};
+#[cfg(feature = "writable")]
+use fuser::RenameFlags;
+#[cfg(feature = "extended-ops")]
+use napi::bindgen_prelude::BigInt;
 use napi::{bindgen_prelude::{Buffer, FnArgs, Promise}, threadsafe_function::ThreadsafeFunction};
 use napi_derive::napi;
 
+use crate::{buffer_pool::PoolBuffers, error_reports::ErrorReportCB, pending_forgets::PendingForgets};
+
 /// init [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust crate.
+/// 
+/// Arguments:
+/// 1. ino of root
+/// 2. results of negotiation with kernel
 #[napi]
-pub type InitOpCB = ThreadsafeFunction<i64>;
+pub type InitOpCB = ThreadsafeFunction<FnArgs<(i64, InitInfo)>>;
 
 /// destory [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
 /// crate.
+/// 
+/// Filesystem session ends only after returned promise settles, or after 10 seconds, hence, JS side may use it to
+/// flush its buffers.
 #[napi]
-pub type DestroyOpCB = ThreadsafeFunction<()>;
+pub type DestroyOpCB = ThreadsafeFunction<(), Promise<()>>;
 
 /// lookup [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
 /// crate.
@@ -46,15 +59,36 @@ pub type LookupOpCB = ThreadsafeFunction<FnArgs<(i64, String)>, Promise<FileAttr
 /// Arguments:
 /// 1. ino
 /// 2. nlookup - count of lookups to drop.
+/// 
+/// Not called, when lookup counts are kept by this module, see [`ReleaseInodeCB`].
+/// 
+/// Forget has no reply, and kernel doesn't wait for it. Calls are queued to JS side without blocking the filesystem's
+/// thread, and they come in the order, in which kernel sent forgets. But requests, that come after a forget, may reach
+/// JS side before it, hence, inode may be looked up again, before JS side sees its forget. Lookup counts in such
+/// forget are for lookups, done before it, and JS side should subtract them, not reset inode's count to zero.
 #[napi]
 pub type ForgetOpCB = ThreadsafeFunction<FnArgs<(i64, i64)>>;
 
+/// Many forgets at once, replacing calls of forget callback, when present.
+/// 
+/// Arguments:
+/// 1. pairs `[ino, nlookup]`, like arguments of [`ForgetOpCB`], which notes apply here as well.
+/// 
+/// Kernel drops many inodes at once, like after a walk over a big tree, and forgets, that arrive before JS side takes
+/// a call, go together in it. Like forget, this isn't called, when lookup counts are kept by this module.
+#[napi]
+pub type ForgetManyOpCB = ThreadsafeFunction<PendingForgets>;
+
 /// getattr [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
 /// crate.
 /// 
 /// Arguments:
 /// 1. ino
-/// 2. fh
+/// 2. fh - present, when kernel asks attributes for an operation on an open file, like lseek(2) to the end of file,
+///    and absent for stat(2). Note that Linux kernel doesn't give handle in fstat(2). It is a handle, returned by a
+///    successful open (or create), and not yet released. JS side may return attributes, specific to the handle, for
+///    example, size of a file with writes, that are not yet committed, while path-based stat gives committed size.
+///    Handle's state may also keep a stat, saving a query of inode store.
 /// 
 /// Should return filesystem error code or an attributes data.
 #[napi]
@@ -66,8 +100,11 @@ pub type GetAttrOpCB = ThreadsafeFunction<FnArgs<(i64, Option<i64>)>, Promise<Fi
 /// Arguments:
 /// 1. ino
 /// 2. fh
+/// 3. changes, with only changed attributes present
 /// 
 /// Should return filesystem error code or updated attributes data.
+/// 
+/// When only size changes, and truncate callback is given, truncate is called instead.
 #[napi]
 pub type SetAttrOpCB = ThreadsafeFunction<FnArgs<(i64, Option<i64>, AttrChanges)>, Promise<FileAttrOrErr>>;
 
@@ -77,9 +114,10 @@ pub type SetAttrOpCB = ThreadsafeFunction<FnArgs<(i64, Option<i64>, AttrChanges)
 /// Arguments:
 /// 1. parent inode id
 /// 2. name of a new child node
-/// 3. mode
+/// 3. mode, with umask already applied, see [`MkDirOpCB`]
 /// 4. umask
 /// 5. rdev id
+#[cfg(feature = "writable")]
 #[napi]
 pub type MkNodOpCB = ThreadsafeFunction<FnArgs<(i64, String, u32, u32, u32)>, Promise<NewEntryOrErr>>;
 
@@ -91,6 +129,12 @@ pub type MkNodOpCB = ThreadsafeFunction<FnArgs<(i64, String, u32, u32, u32)>, Pr
 /// 2. name of a new child
 /// 3. mode
 /// 4. umask
+/// 
+/// Kernel applies umask of calling process to mode, before sending it, as this module doesn't ask kernel in init to
+/// leave masking to filesystem (`FUSE_DONT_MASK`). Hence, mode should be used as is, while umask is given only for
+/// information, like for filesystems with default ACLs, and it shouldn't be applied again. The same goes for mknod
+/// and create.
+#[cfg(feature = "writable")]
 #[napi]
 pub type MkDirOpCB = ThreadsafeFunction<FnArgs<(i64, String, u32, u32)>, Promise<NewEntryOrErr>>;
 
@@ -100,6 +144,7 @@ pub type MkDirOpCB = ThreadsafeFunction<FnArgs<(i64, String, u32, u32)>, Promise
 /// Arguments:
 /// 1. parent inode id
 /// 2. name of a child to remove
+#[cfg(feature = "writable")]
 #[napi]
 pub type UnlinkOpCB = ThreadsafeFunction<FnArgs<(i64, String)>, Promise<i32>>;
 
@@ -109,6 +154,7 @@ pub type UnlinkOpCB = ThreadsafeFunction<FnArgs<(i64, String)>, Promise<i32>>;
 /// Arguments:
 /// 1. parent inode id
 /// 2. name of a child folder to remove
+#[cfg(feature = "writable")]
 #[napi]
 pub type RmDirOpCB = ThreadsafeFunction<FnArgs<(i64, String)>, Promise<i32>>;
 
@@ -120,21 +166,38 @@ pub type RmDirOpCB = ThreadsafeFunction<FnArgs<(i64, String)>, Promise<i32>>;
 /// 2. name of a child to move
 /// 3. newparent inode id
 /// 4. newname of a child in new parent
+/// 5. flags of rename, see [`RenameArgs`]
+#[cfg(feature = "writable")]
 #[napi]
-pub type RenameOpCB = ThreadsafeFunction<FnArgs<(i64, String, i64, String, u32)>, Promise<i32>>;
+pub type RenameOpCB = ThreadsafeFunction<FnArgs<(i64, String, i64, String, RenameArgs)>, Promise<i32>>;
 
 /// open [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust crate.
+/// 
+/// Arguments:
+/// 1. ino
+/// 2. flags
+/// 3. truncate flag - true, when file should be truncated within this open. This happens only with atomic
+///    truncation (see `KernelCaps.atomicOTrunc`), otherwise, truncation comes as a separate setattr/truncate.
+/// 
+/// Flags never have both `O_CREAT` and `O_EXCL`, as exclusive creation is done by create callback. Open with them is
+/// replied with `EINVAL` without calling JS side.
 #[napi]
-pub type OpenOpCB = ThreadsafeFunction<FnArgs<(i64, i32)>, Promise<ParamsOfOpenedOrErr>>;
+pub type OpenOpCB = ThreadsafeFunction<FnArgs<(i64, i32, bool)>, Promise<ParamsOfOpenedOrErr>>;
 
 /// read [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust crate.
+/// 
+/// Read may be aborted, see [`AbortOpCB`]. Reads of zero bytes are replied without calling JS side.
 #[napi]
 pub type ReadOpCB = ThreadsafeFunction<FnArgs<(i64, i64, ReadArgs)>, Promise<BufferOrErr>>;
 
 /// release [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
 /// crate.
+/// 
+/// Kernel ignores result of release, as close(2) has already returned by then. Hence, this returns nothing, and
+/// errors, like failure to save file's content, should be returned from flush, which is called before release, and
+/// which result close(2) does return.
 #[napi]
-pub type ReleaseOpCB = ThreadsafeFunction<FnArgs<(i64, i64, ReleaseArgs)>, Promise<i32>>;
+pub type ReleaseOpCB = ThreadsafeFunction<FnArgs<(i64, i64, ReleaseArgs)>, Promise<()>>;
 
 /// flush [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
 /// crate.
@@ -143,6 +206,9 @@ pub type ReleaseOpCB = ThreadsafeFunction<FnArgs<(i64, i64, ReleaseArgs)>, Promi
 /// 1. ino
 /// 2. fh
 /// 3. lock_owner
+/// 
+/// Not called, and can be omitted, when filesystem is mounted read-only, as there is nothing to flush.
+#[cfg(feature = "writable")]
 #[napi]
 pub type FlushOpCB = ThreadsafeFunction<FnArgs<(i64, i64, i64)>, Promise<i32>>;
 
@@ -153,6 +219,12 @@ pub type FlushOpCB = ThreadsafeFunction<FnArgs<(i64, i64, i64)>, Promise<i32>>;
 /// 1. ino
 /// 2. fh
 /// 3. datasync flag
+/// 
+/// With writeback cache (see `KernelCaps.writebackCache`), kernel first sends its dirty pages of the file as writes,
+/// waits for their replies, and only then sends fsync. Requests are taken from kernel one at a time, and write is
+/// replied only after its promise resolves, hence, when fsync is called, JS side has already received all data,
+/// written before application's fsync(2). Fsync should persist this data, before resolving.
+#[cfg(feature = "writable")]
 #[napi]
 pub type FSyncOpCB = ThreadsafeFunction<FnArgs<(i64, i64, bool)>, Promise<i32>>;
 
@@ -163,8 +235,36 @@ pub type OpenDirOpCB = ThreadsafeFunction<FnArgs<(i64, i32)>, Promise<ParamsOfOp
 
 /// readdir [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
 /// crate.
+/// 
+/// Arguments:
+/// 1. ino
+/// 2. fh
+/// 3. offset
+/// 4. request id, with which this readdir may be aborted, see [`AbortOpCB`].
+/// 5. continuation token, given in the previous partial reply for this handle, when kernel continues right after
+///    it. Backends, that paginate with opaque cursors, should continue with the token instead of the offset.
+/// 
+/// Reply may be a partial listing with a continuation token. Entries, that don't fit into kernel's buffer, are kept
+/// on Rust side, and token is passed to JS side, only after they are sent.
+/// 
+/// Entries "." and ".." are sent as directories with inodes of this directory and of its parent, as parent was seen
+/// in lookups. Inode of ".." from JS side is used only, when parent isn't known. When the first page of a listing,
+/// read at offset 0, has no "." or "..", missing ones are added before other entries. These take the last two
+/// offsets below 2^63, and kernel's continuation after them comes to JS side with offset 0.
+#[napi]
+pub type ReadDirOpCB = ThreadsafeFunction<FnArgs<(i64, i64, i64, i64, Option<String>)>, Promise<DirListing>>;
+
+/// readdirplus [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
+/// crate. When this callback is given, kernel is asked in init to use readdirplus instead of readdir, and when
+/// kernel denies it, `"readdirplus"` is listed among denied capabilities.
+/// 
+/// Arguments:
+/// 1. ino
+/// 2. fh
+/// 3. offset
+/// 4. request id, with which this readdirplus may be aborted, see [`AbortOpCB`].
 #[napi]
-pub type ReadDirOpCB = ThreadsafeFunction<FnArgs<(i64, i64, i64)>, Promise<DirListing>>;
+pub type ReadDirPlusOpCB = ThreadsafeFunction<FnArgs<(i64, i64, i64, i64)>, Promise<DirListingPlus>>;
 
 /// releasedir [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
 /// crate.
@@ -183,11 +283,20 @@ pub type FSyncDirOpCB = ThreadsafeFunction<FnArgs<(i64, i64, bool)>, Promise<i32
 
 /// getxattr [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
 /// crate.
+/// 
+/// When requested attribute doesn't exist, error code should be `PosixError.enoattr()`, which is `ENODATA` on Linux
+/// and `ENOATTR` on macOS.
+/// 
+/// Value may be returned whole with any size. With zero size, only its length is given to kernel, and value, longer
+/// than non-zero size, is replied with `ERANGE`. Values over 64 KiB, kernel's limit, are replied with `E2BIG`.
 #[napi]
 pub type GetXAttrOpCB = ThreadsafeFunction<FnArgs<(i64, String, u32)>, Promise<XAttrBytesOrErr>>;
 
 /// listxattr [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
 /// crate.
+/// 
+/// Inode without extended attributes has an empty list, which is not an error. Hence, `PosixError.enoattr()` is not
+/// expected here, unlike in getxattr.
 #[napi]
 pub type ListXAttrOpCB = ThreadsafeFunction<FnArgs<(i64, u32)>, Promise<XAttrBytesOrErr>>;
 
@@ -201,8 +310,217 @@ pub type ListXAttrOpCB = ThreadsafeFunction<FnArgs<(i64, u32)>, Promise<XAttrByt
 pub type RemoveXAttrOpCB = ThreadsafeFunction<FnArgs<(i64, String)>, Promise<i32>>;
 
 /// access [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust crate.
+/// 
+/// This is called only with non-zero mask. Existence check with `F_OK` (zero mask) is answered by getattr callback,
+/// or by recent attributes, when getattr coalescing is on.
+/// 
+/// Should return `Granted`, or `Denied` with an error code, usually `EACCES`.
+#[napi]
+pub type AccessOpCB = ThreadsafeFunction<FnArgs<(i64, i32)>, Promise<AccessResult>>;
+
+/// ioctl [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust crate.
+/// 
+/// Arguments:
+/// 1. ino
+/// 2. fh
+/// 3. ioctl arguments
+/// 
+/// Note that [`fuser`] replies only to restricted ioctls, i.e. there is no way to ask kernel for a retry with
+/// different in/out buffers. Hence, ioctls without fixed-size request/response can't be served.
+#[cfg(feature = "extended-ops")]
+#[napi]
+pub type IoctlOpCB = ThreadsafeFunction<FnArgs<(i64, i64, IoctlArgs)>, Promise<IoctlOrErr>>;
+
+/// copy_file_range [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser
+/// Rust crate.
+/// 
+/// Arguments:
+/// 1. ino of source file
+/// 2. fh of source file
+/// 3. other arguments
+/// 
+/// Should return filesystem error code or number of copied bytes.
+/// 
+/// Both files are in this filesystem, as kernel does copies between filesystems itself, without coming here. Copy
+/// should be done as one operation of backing store, like a server-side copy, rather than as read and write of data,
+/// else there is no gain over kernel's fallback, which does exactly that, when this callback is absent.
+#[cfg(feature = "extended-ops")]
 #[napi]
-pub type AccessOpCB = ThreadsafeFunction<FnArgs<(i64, i32)>, Promise<i32>>;
+pub type CopyFileRangeOpCB = ThreadsafeFunction<FnArgs<(i64, i64, CopyFileRangeArgs)>, Promise<WrittenOrErr>>;
+
+/// lseek [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
+/// crate. Kernel sends only `SEEK_DATA` and `SEEK_HOLE` here, as it resolves other whence values itself. Absent
+/// this callback, these are replied with `ENOSYS`, after which kernel treats whole file as data.
+/// 
+/// Arguments:
+/// 1. ino
+/// 2. fh
+/// 3. offset
+/// 4. whence
+/// 
+/// Should return filesystem error code or resulting offset:
+/// - for `SEEK_DATA`, start of the first data at or after given offset;
+/// - for `SEEK_HOLE`, start of the first hole at or after given offset, where end of file counts as a hole.
+/// 
+/// When given offset is at or past end of file, or, for `SEEK_DATA`, there is no data after it, error should be
+/// `ENXIO`. Offsets, that don't fit into JS number exactly, i.e. above 2^53, should be returned as BigInt.
+/// Offset before the given one is a broken reply, and is replied with `EIO`.
+#[cfg(feature = "extended-ops")]
+#[napi]
+pub type LseekOpCB = ThreadsafeFunction<FnArgs<(i64, i64, i64, i32)>, Promise<OffsetOrErr>>;
+
+/// write [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
+/// crate. Absent this callback, write is replied with `ENOSYS`.
+/// 
+/// Arguments:
+/// 1. ino
+/// 2. fh
+/// 3. other arguments, with data either in a buffer, or in a slot of write buffers pool (see `InitInfo`)
+/// 
+/// Should return filesystem error code or number of written bytes.
+/// 
+/// Writes of zero bytes are replied without calling JS side. Write to a file, opened with `O_SYNC` or `O_DSYNC`, is
+/// replied only after it is followed by fsync callback, with datasync flag for `O_DSYNC`.
+#[cfg(feature = "writable")]
+#[napi]
+pub type WriteOpCB = ThreadsafeFunction<FnArgs<(i64, i64, WriteArgs)>, Promise<WrittenOrErr>>;
+
+/// This is called before the first write to an open file handle, letting JS side prepare for writes, like copy-up of
+/// a backing object in overlay filesystem, or allocation of space. Error code fails the write, before its data is
+/// passed, and preparation is retried with the next write. After success, it isn't called again for this handle.
+/// 
+/// Arguments:
+/// 1. ino
+/// 2. fh
+#[cfg(feature = "writable")]
+#[napi]
+pub type PrepareWriteCB = ThreadsafeFunction<FnArgs<(i64, i64)>, Promise<i32>>;
+
+/// Read of a file, opened with `ParamsOfOpened.directIo`, called instead of `ReadOpCB` with the same arguments.
+/// Without kernel's page cache, offset and size come as application has asked, without alignment to pages, and a
+/// size may be bigger than one of cached reads. Absent this callback, such reads go to `ReadOpCB`.
+#[napi]
+pub type DirectReadOpCB = ThreadsafeFunction<FnArgs<(i64, i64, ReadArgs)>, Promise<BufferOrErr>>;
+
+/// Write into a file, opened with `ParamsOfOpened.directIo`, called instead of `WriteOpCB` with the same arguments.
+/// Like with `DirectReadOpCB`, offset and size come unaligned, as application has written them. Absent this
+/// callback, such writes go to `WriteOpCB`.
+#[cfg(feature = "writable")]
+#[napi]
+pub type DirectWriteOpCB = ThreadsafeFunction<FnArgs<(i64, i64, WriteArgs)>, Promise<WrittenOrErr>>;
+
+/// poll [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
+/// crate. Absent this callback, poll is replied with `ENOSYS`, and kernel treats file as always ready.
+/// 
+/// Arguments:
+/// 1. ino
+/// 2. fh
+/// 3. requested events
+/// 
+/// Should return filesystem error code or events, that are ready. When nothing is ready, kernel waits for
+/// `FSMounter.notifyPoll(fh)`, after which it polls again.
+#[cfg(feature = "extended-ops")]
+#[napi]
+pub type PollOpCB = ThreadsafeFunction<FnArgs<(i64, i64, PollEvents)>, Promise<PollOrErr>>;
+
+/// Truncation of a file, i.e. a setattr, which changes only size.
+/// Absent this callback, truncation comes to setattr, like any other attribute change.
+/// 
+/// Arguments:
+/// 1. ino
+/// 2. fh
+/// 3. new size
+/// 
+/// Per POSIX, successful truncate must update file's mtime and ctime, even when size stays the same.
+/// Should return filesystem error code or updated attributes data.
+#[napi]
+pub type TruncateOpCB = ThreadsafeFunction<FnArgs<(i64, Option<i64>, i64)>, Promise<FileAttrOrErr>>;
+
+/// This is called with request id of a long running operation (read, write, readdir, readdirplus), when this module
+/// has given up waiting for js reply (see `MountConfig.jsReplyTimeoutMs`) and has already replied kernel with `EIO`.
+/// JS side may use it to trigger `AbortController`, with which underlying fetch/IO is done, so as to stop useless work.
+/// 
+/// Note that kernel's interrupts, like ones after the calling process is killed, are not passed here, as [`fuser`]
+/// doesn't handle FUSE_INTERRUPT.
+#[napi]
+pub type AbortOpCB = ThreadsafeFunction<i64>;
+
+/// getxtimes [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
+/// crate. This is called on macOS only, and is ignored on other platforms.
+/// 
+/// Arguments:
+/// 1. ino
+/// 
+/// Should return filesystem error code or backup and creation times.
+#[napi]
+pub type GetXTimesOpCB = ThreadsafeFunction<i64, Promise<XTimesOrErr>>;
+
+/// create [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
+/// crate. Absent this callback, create is replied with `ENOSYS`, and kernel falls back to mknod and open.
+/// 
+/// Arguments:
+/// 1. parent inode id
+/// 2. name of a new file
+/// 3. other arguments
+/// 
+/// Should return filesystem error code or both new entry and parameters of its opening.
+#[cfg(feature = "writable")]
+#[napi]
+pub type CreateOpCB = ThreadsafeFunction<FnArgs<(i64, String, CreateArgs)>, Promise<CreatedOrErr>>;
+
+/// This is called with ino, found in lookup, to get its generation number.
+/// Absent this callback, lookup reports generation 0. Create, mknod and mkdir return generation in their results.
+/// 
+/// Pair (ino, generation) must be unique during filesystem's lifetime, i.e. generation should change, when ino
+/// number is reused for another file. This matters for NFS re-export (see `KernelCaps.exportSupport`), where
+/// kernel hands out file handles, made from these pairs, and stale handles are recognized by generation.
+#[napi]
+pub type GenerationStoreCB = ThreadsafeFunction<i64, Promise<i64>>;
+
+/// This is called with ino, when kernel forgets all lookups of it, as counted by this module. With this callback
+/// given, forget callback is not called. Like forget, it is queued to JS side without waiting.
+#[napi]
+pub type ReleaseInodeCB = ThreadsafeFunction<i64>;
+
+/// readlink [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
+/// crate. Absent this callback, readlink is replied with `ENOSYS`.
+/// 
+/// Arguments:
+/// 1. ino
+/// 
+/// Should return filesystem error code or target of the link. Target that doesn't fit `PATH_MAX` is replied with
+/// `ENAMETOOLONG`.
+#[napi]
+pub type ReadlinkOpCB = ThreadsafeFunction<i64, Promise<LinkTargetOrErr>>;
+
+/// symlink [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
+/// crate. Absent this callback, symlink is replied with `EPERM`.
+/// 
+/// Arguments:
+/// 1. parent inode id
+/// 2. name of a new link
+/// 3. target of the link, which is already checked to fit `PATH_MAX`
+/// 
+/// Should return filesystem error code or new entry with attributes of the link, i.e. with `SymLink` kind, usually
+/// with `0o777` permissions, and with size being length of target in bytes. Entry of another kind is replied with
+/// `EIO`.
+#[cfg(feature = "writable")]
+#[napi]
+pub type SymlinkOpCB = ThreadsafeFunction<FnArgs<(i64, String, String)>, Promise<NewEntryOrErr>>;
+
+/// statfs [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html) using fuser Rust
+/// crate. Absent this callback, zero counts of blocks and files are reported.
+/// 
+/// Note that `f_fsid` can't be set here, as FUSE protocol's statfs reply has no such field. Kernel derives it from
+/// device number of the mount, which is unique among current mounts, but isn't stable across remounts. When stable
+/// id is needed for NFS re-export, set it with `fsid=` option in exports(5).
+/// 
+/// Arguments:
+/// 1. ino
+/// 
+/// Should return filesystem error code or filesystem statistics.
+#[napi]
+pub type StatfsOpCB = ThreadsafeFunction<i64, Promise<StatfsOrErr>>;
 
 /// This contains JavaScript callbacks to perform
 /// [FUSE operation](https://libfuse.github.io/doxygen/structfuse__lowlevel__ops.html), structured by [`fuser`].
@@ -218,15 +536,22 @@ pub struct CallbacksToJS {
   pub forget: ForgetOpCB,
   pub getattr: GetAttrOpCB,
   pub setattr: SetAttrOpCB,
+  #[cfg(feature = "writable")]
   pub mknod: MkNodOpCB,
+  #[cfg(feature = "writable")]
   pub mkdir: MkDirOpCB,
+  #[cfg(feature = "writable")]
   pub unlink: UnlinkOpCB,
+  #[cfg(feature = "writable")]
   pub rmdir: RmDirOpCB,
+  #[cfg(feature = "writable")]
   pub rename: RenameOpCB,
   pub open: OpenOpCB,
   pub read: ReadOpCB,
-  pub flush: FlushOpCB,
+  #[cfg(feature = "writable")]
+  pub flush: Option<FlushOpCB>,
   pub release: ReleaseOpCB,
+  #[cfg(feature = "writable")]
   pub fsync: FSyncOpCB,
   pub opendir: OpenDirOpCB,
   pub readdir: ReadDirOpCB,
@@ -236,6 +561,61 @@ pub struct CallbacksToJS {
   pub listxattr: ListXAttrOpCB,
   pub removexattr: RemoveXAttrOpCB,
   pub access: AccessOpCB,
+  #[cfg(feature = "extended-ops")]
+  pub ioctl: Option<IoctlOpCB>,
+  #[cfg(feature = "extended-ops")]
+  pub copy_file_range: Option<CopyFileRangeOpCB>,
+  #[cfg(feature = "extended-ops")]
+  pub lseek: Option<LseekOpCB>,
+  pub abort: Option<AbortOpCB>,
+  pub truncate: Option<TruncateOpCB>,
+  pub statfs: Option<StatfsOpCB>,
+  #[cfg(feature = "writable")]
+  pub create: Option<CreateOpCB>,
+  pub generation_store: Option<GenerationStoreCB>,
+  pub readdirplus: Option<ReadDirPlusOpCB>,
+  pub release_inode: Option<ReleaseInodeCB>,
+  pub forget_many: Option<ForgetManyOpCB>,
+  pub readlink: Option<ReadlinkOpCB>,
+  #[cfg(feature = "writable")]
+  pub symlink: Option<SymlinkOpCB>,
+  #[cfg(feature = "writable")]
+  pub write: Option<WriteOpCB>,
+  #[cfg(feature = "extended-ops")]
+  pub poll: Option<PollOpCB>,
+  #[cfg(feature = "writable")]
+  pub prepare_write: Option<PrepareWriteCB>,
+  pub direct_read: Option<DirectReadOpCB>,
+  #[cfg(feature = "writable")]
+  pub direct_write: Option<DirectWriteOpCB>,
+  #[cfg(target_os = "macos")]
+  pub getxtimes: Option<GetXTimesOpCB>,
+  pub on_error: Option<Arc<ErrorReportCB>>,
+}
+
+#[napi(object, object_from_js = false)]
+pub struct InitInfo {
+  /// Names of requested capabilities (see `KernelCaps`), which kernel doesn't support. These are not enabled, and
+  /// filesystem may want to warn or refuse to serve.
+  pub denied_caps: Vec<String>,
+  /// Buffers of write pool, present, when pool is set in mount config. Write with `WriteArgs.slot` has its data at
+  /// the start of respective buffer. Buffer must not be used after write's promise is settled.
+  pub write_buffers: Option<PoolBuffers>,
+  /// Buffers of read pool, present, when pool is set in mount config. Read with `ReadArgs.slot` may place its data at
+  /// the start of respective buffer, returning only data's length. Buffer must not be used after read's promise is
+  /// settled.
+  pub read_buffers: Option<PoolBuffers>,
+  pub limits: KernelLimits,
+}
+
+/// Limits, negotiated with kernel in init.
+#[napi(object)]
+pub struct KernelLimits {
+  /// Readahead in bytes, that kernel has offered, which is also the biggest one it allows. Filesystem may use this
+  /// as a hint for `MountConfig.maxReadahead` of later mounts.
+  pub kernel_max_readahead: u32,
+  /// Readahead in bytes, that is in effect.
+  pub max_readahead: u32,
 }
 
 #[napi(object)]
@@ -246,7 +626,7 @@ pub struct FileAttr {
   pub ctime: i64,
   pub btime: i64,
   pub kind: InodeKind,
-  /// Permissions
+  /// Permissions. With `PERM_OF_MOUNT`, these are set by `MountConfig.fileMode` or `MountConfig.dirMode`.
   pub perm: u16,
   /// User id
   pub uid: u32,
@@ -256,12 +636,27 @@ pub struct FileAttr {
   pub rdev: u32,
   /// Flags (macOS only, see chflags(2))
   pub flags: u32,
+  /// Set to false for inodes, known to have no extended attributes, so that listxattr is replied without calling
+  /// JS side. Default is true.
+  pub has_xattrs: Option<bool>,
+  /// Preferred size of I/O, reported in `st_blksize` of stat(2), see `blockSizeForStorage`. Default is 4096.
+  pub blksize: Option<u32>,
+}
+
+/// Error of an operation, given by JS side.
+#[napi(object)]
+pub struct FsError {
+  /// POSIX error code, that is replied to kernel.
+  pub code: i32,
+  /// Description of error, like OS-level message of a failed backend call. When present, error is reported to
+  /// `MountConfig.onError` with kind `"fs-error"`, as kernel gets only the code.
+  pub message: Option<String>,
 }
 
 #[napi]
 pub enum FileAttrOrErr {
   Attr(FileAttr),
-  Err(i32)
+  Err(FsError)
 }
 
 #[napi]
@@ -279,6 +674,11 @@ pub fn to_file_type(kind: &InodeKind) -> FileType {
   }
 }
 
+/// Value of `FileAttr.perm`, with which JS side leaves permission bits to mount's settings, for filesystems, that
+/// don't keep permissions of each inode.
+#[napi]
+pub const PERM_OF_MOUNT: u16 = 0xFFFF;
+
 pub const BLOCK_SIZE: u64 = 4096;
 fn blocks_in(size: u64) -> u64 {
   let d = size / BLOCK_SIZE;
@@ -291,7 +691,7 @@ fn blocks_in(size: u64) -> u64 {
 }
 
 impl FileAttr {
-  pub fn into_fuse(&self) -> fuser::FileAttr {
+  pub fn into_fuse(self) -> fuser::FileAttr {
     let mtime = system_time_from(self.mtime);
     fuser::FileAttr {
       atime: mtime,
@@ -307,48 +707,167 @@ impl FileAttr {
       perm: self.perm,
       rdev: self.rdev,
       size: self.size as u64,
-      blksize: BLOCK_SIZE as u32,
+      blksize: self.blksize.unwrap_or(BLOCK_SIZE as u32),
       blocks: blocks_in(self.size as u64),
     }
   }
 }
 
-fn system_time_from(millis: i64) -> SystemTime {
-  SystemTime::UNIX_EPOCH + Duration::from_millis(millis as u64)
+/// Largest distance from epoch, in milliseconds, of a time, that can be represented by JS `Date`.
+const MAX_JS_DATE_MILLIS: u64 = 8_640_000_000_000_000;
+
+/// Converts milliseconds since epoch into [`SystemTime`]. Negative values are times before epoch. Values outside of
+/// JS `Date`'s range, which can't be valid times from JS side, are replaced with epoch.
+pub fn system_time_from(millis: i64) -> SystemTime {
+  let offset = millis.unsigned_abs();
+  if offset > MAX_JS_DATE_MILLIS {
+    return SystemTime::UNIX_EPOCH;
+  }
+  let offset = Duration::from_millis(offset);
+  let time = if millis >= 0 {
+    SystemTime::UNIX_EPOCH.checked_add(offset)
+  } else {
+    SystemTime::UNIX_EPOCH.checked_sub(offset)
+  };
+  time.unwrap_or(SystemTime::UNIX_EPOCH)
+}
+
+/// Converts [`SystemTime`] into milliseconds since epoch, saturating at bounds of `i64`.
+pub fn millis_from(time: SystemTime) -> i64 {
+  match time.duration_since(SystemTime::UNIX_EPOCH) {
+    Ok(after_epoch) => i64::try_from(after_epoch.as_millis()).unwrap_or(i64::MAX),
+    Err(before_epoch) => i64::try_from(before_epoch.duration().as_millis()).map_or(i64::MIN, |ms| -ms),
+  }
 }
 
 #[napi(object)]
 pub struct AttrChanges {
   pub mode: Option<u32>,
+  pub size: Option<i64>,
   pub uid: Option<u32>,
   pub gid: Option<u32>,
   pub flags: Option<u32>,
+  /// Access time in milliseconds since epoch. Setting to current time, like utimensat(2) with `UTIME_NOW`, comes
+  /// already resolved to time of the call.
+  pub atime: Option<i64>,
+  /// Modification time in milliseconds since epoch, with current time resolved, like in `atime`.
+  pub mtime: Option<i64>,
 }
 
 #[napi(object)]
 pub struct ParamsOfOpened {
   pub fh: i64,
-  pub flags: u32
+  /// Raw `FOPEN_*` flags. Named flags below, that are true, are added to these, and false ones don't clear them.
+  pub flags: Option<u32>,
+  /// Bypass kernel page cache for this open, so that every application read reaches read callback. This is needed
+  /// for files, which content changes on the other side, like live/streaming files.
+  pub direct_io: Option<bool>,
+  /// Don't invalidate kernel page cache of the file on this open.
+  pub keep_cache: Option<bool>,
+  /// File is not seekable.
+  pub nonseekable: Option<bool>,
+  /// For opendir only. Lets kernel cache listing of directory, and, with `keepCache`, keep it across opens, so that
+  /// repeated listing doesn't call readdir. Dropped, when kernel's FUSE protocol is older than 7.28.
+  pub cache_dir: Option<bool>,
+}
+
+impl ParamsOfOpened {
+
+  /// Returns all flags, or `None`, if raw flags are not known to [`fuser`].
+  pub fn fopen_flags(&self) -> Option<FopenFlags> {
+    let mut flags = FopenFlags::from_bits(self.flags.unwrap_or(0))?;
+    let named = [
+      (self.direct_io, FopenFlags::FOPEN_DIRECT_IO),
+      (self.keep_cache, FopenFlags::FOPEN_KEEP_CACHE),
+      (self.nonseekable, FopenFlags::FOPEN_NONSEEKABLE),
+      (self.cache_dir, FopenFlags::FOPEN_CACHE_DIR),
+    ];
+    for (is_set, flag) in named {
+      if is_set == Some(true) {
+        flags.insert(flag);
+      }
+    }
+    Some(flags)
+  }
+
 }
 
 #[napi]
 pub enum ParamsOfOpenedOrErr {
   Params(ParamsOfOpened),
-  Err(i32)
+  Err(FsError)
 }
 
 #[napi]
 pub enum BufferOrErr {
   Ok(Buffer),
-  Err(i32)
+  /// Data with updated attributes, like new atime, see [`DataWithAttr`].
+  OkWithAttr(DataWithAttr),
+  /// Length of data, placed at the start of read pool's buffer with index `ReadArgs.slot`.
+  InSlot(u32),
+  /// Data with holes, that are filled with zeros on Rust side.
+  Sparse(SparseData),
+  Err(FsError)
 }
 
+/// Read data with updated attributes. Like ones of [`WrittenWithAttr`], these answer getattr, that follows read.
 #[napi(object)]
+pub struct DataWithAttr {
+  pub data: Buffer,
+  pub attr: FileAttr,
+}
+
+/// Read data, given as segments, with zeros between them.
+#[napi(object)]
+pub struct SparseData {
+  /// Length of read data, which is less than requested size, when read goes past the end of file.
+  pub len: u32,
+  /// Segments with offsets in file. Parts of segments outside of read range are ignored.
+  pub segments: Vec<DataSegment>,
+}
+
+#[napi(object)]
+pub struct DataSegment {
+  pub offset: i64,
+  pub data: Buffer,
+}
+
+impl SparseData {
+
+  /// Assembles data of a read at a given offset, placing segments over zeros.
+  pub fn assemble(&self, read_offset: u64) -> Vec<u8> {
+    let mut bytes = vec![0u8; self.len as usize];
+    for segment in &self.segments {
+      let seg_start = segment.offset.max(0) as u64;
+      let skip = read_offset.saturating_sub(seg_start) as usize;
+      if skip >= segment.data.len() {
+        continue;
+      }
+      let pos = seg_start.saturating_sub(read_offset) as usize;
+      if pos >= bytes.len() {
+        continue;
+      }
+      let chunk = &segment.data[skip..];
+      let n = chunk.len().min(bytes.len() - pos);
+      bytes[pos..(pos + n)].copy_from_slice(&chunk[..n]);
+    }
+    bytes
+  }
+
+}
+
+#[napi(object)]
+#[derive(Clone)]
 pub struct ReadArgs {
   pub offset: i64,
   pub size: u32,
   pub flags: i32,
   pub lock_owner: Option<i64>,
+  /// Request id, with which this read may be aborted, see [`AbortOpCB`].
+  pub req_id: i64,
+  /// Index of read pool's buffer (see `InitInfo`), into which data may be placed, instead of returning a new buffer.
+  /// Present, when pool is set in mount config, and has a free buffer, big enough for this read.
+  pub slot: Option<u32>,
 }
 
 #[napi(object)]
@@ -362,7 +881,7 @@ pub struct ReleaseArgs {
 pub enum XAttrBytesOrErr {
   Data(Buffer),
   Size(u32),
-  Err(i32)
+  Err(FsError)
 }
 
 #[napi(object)]
@@ -370,30 +889,351 @@ pub struct DirEntry {
   pub ino: i64,
   pub offset: i64,
   pub kind: InodeKind,
-  pub name: String
+  pub name: String,
+  /// Not used by readdir, and is here for symmetry with [`DirEntryPlus`].
+  pub generation: Option<i64>,
 }
 
 #[napi]
 pub enum DirListing {
   Lst(Vec<DirEntry>),
-  Err(i32)
+  /// Some entries with a token, with which listing should continue.
+  Partial(PartialDirListing),
+  Err(FsError)
 }
 
+#[napi(object)]
+pub struct PartialDirListing {
+  pub lst: Vec<DirEntry>,
+  pub token: String,
+}
+
+#[napi(object)]
 pub struct DirEntryPlus {
   pub offset: i64,
-  pub kind: InodeKind,
   pub name: String,
+  /// Attributes are required in every entry. Kernel takes entry with inode number 0 as one without attributes, but
+  /// fuser sends inode number of attributes also as inode number of entry, and readdir(3) skips entries with inode
+  /// number 0. Backends, that can't cheaply give attributes of some entries, should use readdir instead.
+  pub attr: FileAttr,
+  /// Generation of inode, like one in lookup. Default is 0.
+  pub generation: Option<i64>,
+}
+
+#[napi]
+pub enum DirListingPlus {
+  Lst(Vec<DirEntryPlus>),
+  Err(FsError)
 }
 
+/// New entry, made by mknod, mkdir, symlink or create.
+#[cfg(feature = "writable")]
 #[napi(object)]
 pub struct MkNodResult {
-  pub ttl: i64,
+  /// Time in milliseconds, for which kernel may cache this entry and its attributes, without asking for them again.
+  /// Default is mount's default of 1 second.
+  pub ttl: Option<i64>,
   pub attr: FileAttr,
+  /// Generation of inode, that together with inode number identifies file over lifetime of filesystem. It matters
+  /// only for filesystems, that are exported over NFS, and reuse inode numbers. Others should set 0.
   pub generation: i64
 }
 
+#[cfg(feature = "writable")]
 #[napi]
 pub enum NewEntryOrErr {
   Entry(MkNodResult),
-  Err(i32)
+  Err(FsError)
+}
+
+#[napi]
+pub enum LinkTargetOrErr {
+  Target(String),
+  Err(FsError)
+}
+
+#[cfg(feature = "writable")]
+#[napi(object)]
+pub struct CreateArgs {
+  /// Mode with umask already applied, see [`MkDirOpCB`].
+  pub mode: u32,
+  pub umask: u32,
+  pub flags: i32,
+}
+
+#[cfg(feature = "writable")]
+#[napi(object)]
+pub struct CreatedResult {
+  pub entry: MkNodResult,
+  pub opened: ParamsOfOpened,
+}
+
+#[cfg(feature = "writable")]
+#[napi]
+pub enum CreatedOrErr {
+  Created(CreatedResult),
+  Err(FsError)
+}
+
+#[cfg(feature = "extended-ops")]
+#[napi(object)]
+pub struct IoctlArgs {
+  pub flags: u32,
+  pub cmd: u32,
+  pub in_data: Buffer,
+  pub out_size: u32,
+}
+
+#[cfg(feature = "extended-ops")]
+#[napi(object)]
+pub struct IoctlResult {
+  pub result: i32,
+  pub data: Buffer,
+}
+
+#[cfg(feature = "extended-ops")]
+#[napi]
+pub enum IoctlOrErr {
+  Ok(IoctlResult),
+  Err(FsError)
+}
+
+/// Arguments of copy_file_range, besides source ino and fh.
+/// 
+/// Copying within the same file with overlapping source and destination ranges is invalid (see
+/// copy_file_range(2)). Such requests are replied with `EINVAL` without calling JS side, hence callback never sees
+/// them.
+#[cfg(feature = "extended-ops")]
+#[napi(object)]
+pub struct CopyFileRangeArgs {
+  pub offset_in: i64,
+  pub ino_out: i64,
+  pub fh_out: i64,
+  pub offset_out: i64,
+  pub len: i64,
+  /// Flags of copy_file_range(2). Currently, kernel defines none.
+  pub flags: i64,
+}
+
+#[cfg(feature = "writable")]
+#[napi(object)]
+pub struct WriteArgs {
+  pub offset: i64,
+  /// Data of write, when it is not placed in a slot of write buffers pool.
+  pub data: Option<Buffer>,
+  /// Index of a buffer in write buffers pool, that contains data of this write.
+  pub slot: Option<u32>,
+  /// Length of data.
+  pub len: u32,
+  pub write_flags: u32,
+  pub flags: i32,
+  pub lock_owner: Option<i64>,
+  /// File is open with `O_APPEND`, and data should be appended, whatever offset is. With mount's `enforceAppend`,
+  /// offset is already set to the end of file.
+  pub append: bool,
+  /// Request id, with which this write may be aborted, see [`AbortOpCB`].
+  pub req_id: i64,
+}
+
+#[cfg(feature = "writable")]
+#[napi(object)]
+pub struct RenameArgs {
+  /// Raw `RENAME_*` flags. Named flags below are taken from these.
+  pub flags: u32,
+  /// `RENAME_NOREPLACE`: rename should fail with `EEXIST`, when new name exists.
+  pub no_replace: bool,
+  /// `RENAME_EXCHANGE`: both names exist, and inodes should be swapped between them atomically.
+  pub exchange: bool,
+  /// `RENAME_WHITEOUT`: source name should be left with a whiteout, i.e. a character device with device number
+  /// (0, 0), created in the same atomic step, in which file is moved. Overlay filesystems ask for this, when this
+  /// filesystem is their upper layer, to hide files of lower layers. Filesystem, that can't do this, should return
+  /// `EINVAL`.
+  pub whiteout: bool,
+}
+
+#[cfg(feature = "writable")]
+impl From<RenameFlags> for RenameArgs {
+  fn from(flags: RenameFlags) -> Self {
+    // named flags are defined only on linux
+    #[cfg(target_os = "linux")]
+    return RenameArgs {
+      flags: flags.bits(),
+      no_replace: flags.contains(RenameFlags::RENAME_NOREPLACE),
+      exchange: flags.contains(RenameFlags::RENAME_EXCHANGE),
+      whiteout: flags.contains(RenameFlags::RENAME_WHITEOUT),
+    };
+    #[cfg(not(target_os = "linux"))]
+    return RenameArgs { flags: flags.bits(), no_replace: false, exchange: false, whiteout: false };
+  }
+}
+
+/// Poll events, named here, as values of `POLL*` constants differ between platforms.
+#[cfg(feature = "extended-ops")]
+#[napi(object)]
+pub struct PollEvents {
+  /// `POLLIN`
+  pub readable: bool,
+  /// `POLLOUT`
+  pub writable: bool,
+  /// `POLLERR`
+  pub error: bool,
+  /// `POLLHUP`
+  pub hang_up: bool,
+}
+
+#[cfg(feature = "extended-ops")]
+impl PollEvents {
+
+  pub fn from_fuse(events: fuser::PollEvents) -> Self {
+    PollEvents {
+      readable: events.contains(fuser::PollEvents::POLLIN),
+      writable: events.contains(fuser::PollEvents::POLLOUT),
+      error: events.contains(fuser::PollEvents::POLLERR),
+      hang_up: events.contains(fuser::PollEvents::POLLHUP),
+    }
+  }
+
+  pub fn into_fuse(self) -> fuser::PollEvents {
+    let mut events = fuser::PollEvents::empty();
+    events.set(fuser::PollEvents::POLLIN, self.readable);
+    events.set(fuser::PollEvents::POLLOUT, self.writable);
+    events.set(fuser::PollEvents::POLLERR, self.error);
+    events.set(fuser::PollEvents::POLLHUP, self.hang_up);
+    events
+  }
+
+}
+
+#[cfg(feature = "extended-ops")]
+#[napi]
+pub enum PollOrErr {
+  Events(PollEvents),
+  Err(FsError)
+}
+
+#[cfg(any(feature = "writable", feature = "extended-ops"))]
+#[napi]
+pub enum WrittenOrErr {
+  Written(u32),
+  /// Number of written bytes with attributes of file after write, see [`WrittenWithAttr`].
+  WrittenWithAttr(WrittenWithAttr),
+  Err(FsError)
+}
+
+/// Result of write with updated attributes, like new size and mtime. With `MountConfig.getattrCoalesceMs`, these
+/// answer getattr, that follows write, without calling JS side. Kernel itself drops cached attributes after writes.
+#[cfg(any(feature = "writable", feature = "extended-ops"))]
+#[napi(object)]
+pub struct WrittenWithAttr {
+  pub written: u32,
+  pub attr: FileAttr,
+}
+
+/// Extended times of macOS. Values are milliseconds since epoch, like other times in [`FileAttr`].
+#[napi(object)]
+pub struct XTimes {
+  pub bkuptime: i64,
+  pub crtime: i64,
+}
+
+#[napi]
+pub enum XTimesOrErr {
+  Times(XTimes),
+  Err(FsError)
+}
+
+#[napi(object)]
+#[derive(Default)]
+pub struct StatfsResult {
+  pub blocks: i64,
+  pub bfree: i64,
+  pub bavail: i64,
+  pub files: i64,
+  pub ffree: i64,
+  /// Block size. Default is 4096.
+  pub bsize: Option<u32>,
+  /// Fragment size, i.e. unit of blocks' counts. Default is the one set in mount config, or block size.
+  pub frsize: Option<u32>,
+}
+
+#[napi]
+pub enum StatfsOrErr {
+  Stats(StatfsResult),
+  Err(FsError)
+}
+
+#[napi]
+pub enum AccessResult {
+  Granted,
+  Denied(FsError)
+}
+
+#[cfg(feature = "extended-ops")]
+#[napi]
+pub enum OffsetOrErr {
+  Offset(i64),
+  BigOffset(BigInt),
+  Err(FsError)
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::*;
+
+  fn sparse(len: u32, segments: &[(i64, &[u8])]) -> SparseData {
+    let segments = segments.iter()
+    .map(|(offset, data)| DataSegment { offset: *offset, data: Buffer::from(data.to_vec()) })
+    .collect();
+    SparseData { len, segments }
+  }
+
+  #[test]
+  fn assembles_segments_with_zeros_in_holes() {
+    let data = sparse(10, &[(100, b"ab"), (105, b"cde")]);
+    assert_eq!(data.assemble(100), b"ab\0\0\0cde\0\0");
+  }
+
+  #[test]
+  fn assembles_only_read_range_of_segments() {
+    let data = sparse(4, &[(98, b"abcd"), (103, b"xyz")]);
+    assert_eq!(data.assemble(100), b"cd\0x");
+    assert_eq!(sparse(3, &[(0, b"far")]).assemble(100), b"\0\0\0");
+  }
+
+  #[test]
+  fn adds_named_flags_to_raw_ones() {
+    let params = ParamsOfOpened {
+      fh: 1, flags: Some(FopenFlags::FOPEN_NOFLUSH.bits()), direct_io: None, keep_cache: Some(true),
+      nonseekable: None, cache_dir: Some(true),
+    };
+    assert_eq!(
+      params.fopen_flags(),
+      Some(FopenFlags::FOPEN_NOFLUSH | FopenFlags::FOPEN_KEEP_CACHE | FopenFlags::FOPEN_CACHE_DIR)
+    );
+    let raw_only = ParamsOfOpened {
+      fh: 1, flags: Some((FopenFlags::FOPEN_DIRECT_IO | FopenFlags::FOPEN_KEEP_CACHE).bits()), direct_io: None,
+      keep_cache: Some(false), nonseekable: None, cache_dir: None,
+    };
+    assert_eq!(raw_only.fopen_flags(), Some(FopenFlags::FOPEN_DIRECT_IO | FopenFlags::FOPEN_KEEP_CACHE));
+    let unknown = ParamsOfOpened { flags: Some(1 << 31), ..params };
+    assert!(unknown.fopen_flags().is_none());
+  }
+
+  #[test]
+  fn converts_times_before_epoch() {
+    let day_before = system_time_from(-86_400_000);
+    assert_eq!(SystemTime::UNIX_EPOCH.duration_since(day_before).unwrap(), Duration::from_secs(86_400));
+    assert_eq!(millis_from(day_before), -86_400_000);
+  }
+
+  #[test]
+  fn replaces_times_outside_of_js_date_range_with_epoch() {
+    assert_eq!(system_time_from(i64::MAX), SystemTime::UNIX_EPOCH);
+    assert_eq!(system_time_from(i64::MIN), SystemTime::UNIX_EPOCH);
+    assert_eq!(system_time_from(MAX_JS_DATE_MILLIS as i64 + 1), SystemTime::UNIX_EPOCH);
+    assert_eq!(millis_from(system_time_from(MAX_JS_DATE_MILLIS as i64)), MAX_JS_DATE_MILLIS as i64);
+    assert_eq!(millis_from(system_time_from(-(MAX_JS_DATE_MILLIS as i64))), -(MAX_JS_DATE_MILLIS as i64));
+  }
+
 }
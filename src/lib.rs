@@ -17,25 +17,55 @@
 
 mod js_callbacks;
 mod fs_impl;
+mod mount_config;
+mod error_reports;
+mod lookup_counts;
+mod inode_kinds;
+mod dir_cache;
+mod inode_parents;
+mod in_flight;
+mod recent_attrs;
+mod buffer_pool;
+mod pending_forgets;
+mod device_mount;
+mod passthrough;
+pub mod posix_errors;
+pub mod utils;
 
-use std::{path::Path, sync::mpsc::{Sender, channel}, thread};
+use std::{collections::HashMap, io, path::{Path, PathBuf}, process::Command, sync::{Arc, LazyLock, Mutex, Once, atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering}, mpsc::{Receiver, RecvTimeoutError, Sender, channel}}, thread, time::Duration};
 
-use napi::bindgen_prelude::*;
+use napi::{CleanupEnvHook, bindgen_prelude::*};
 use napi_derive::napi;
-use fuser::{Config, MountOption, SessionACL, spawn_mount2};
+use fuser::{BackgroundSession, Config, Filesystem, INodeNo, MountOption, Notifier, SessionACL, spawn_mount2};
 
-use crate::{fs_impl::CallbacksProxy, js_callbacks::*};
+use crate::{error_reports::{ErrorReport, ErrorReportCB, record_fuser_panics}, fs_impl::{CachesOfMount, CallbacksProxy, DirtyInodes, PollNotifiers, StatsOfMount}, js_callbacks::*, mount_config::{MountConfig, MountSettings, is_valid_custom_mount_option, is_valid_mtab_name}, passthrough::Passthrough};
 
 #[napi(js_name = "FSMounter")]
 pub struct JsFSMounter {
-  tx_unmount_signal: Sender<()>
+  /// Sends unmount signal, with true for a forced (lazy) unmount.
+  tx_unmount_signal: Sender<bool>,
+  poll_notifiers: PollNotifiers,
+  open_handles: Arc<AtomicUsize>,
+  dirty_inodes: DirtyInodes,
+  /// Sends invalidations of cached data to kernel.
+  notifier: Notifier,
+  /// Caches of filesystem, when it is implemented with js callbacks.
+  caches: Option<CachesOfMount>,
+  /// Statistics of filesystem, when it is implemented with js callbacks.
+  stats: Option<StatsOfMount>,
+  /// Unmounts filesystem, if js environment is torn down, while filesystem is still mounted. Hook is paired with id
+  /// of registered [`ExitCleanup`].
+  exit_cleanup: Option<(u64, CleanupEnvHook<u64>)>,
 }
 
+#[cfg(feature = "writable")]
 #[napi]
 impl JsFSMounter {
 
   #[napi(factory)]
+  #[allow(clippy::too_many_arguments)]
   pub fn make_and_mount(
+    env: Env,
     mount_root: String, fs_name: String,
     init: InitOpCB,
     destroy: DestroyOpCB,
@@ -50,7 +80,7 @@ impl JsFSMounter {
     rename: RenameOpCB,
     open: OpenOpCB,
     read: ReadOpCB,
-    flush: FlushOpCB,
+    flush: Option<FlushOpCB>,
     release: ReleaseOpCB,
     fsync: FSyncOpCB,
     opendir: OpenDirOpCB,
@@ -61,37 +91,521 @@ impl JsFSMounter {
     listxattr: ListXAttrOpCB,
     removexattr: RemoveXAttrOpCB,
     access: AccessOpCB,
+    config: Option<MountConfig>,
   ) -> Result<Self> {
-
-    let fs_impl = CallbacksProxy::make(CallbacksToJS {
+    let mut config = config.unwrap_or_default();
+    let cbs = CallbacksToJS {
       init, destroy, lookup, forget, getattr, setattr, mknod, mkdir, unlink, rmdir, rename,
       open, read, flush, release, fsync, opendir, readdir, releasedir, fsyncdir,
       getxattr, listxattr, removexattr, access,
-    });
+      #[cfg(feature = "extended-ops")]
+      ioctl: config.ioctl.take(),
+      #[cfg(feature = "extended-ops")]
+      copy_file_range: config.copy_file_range.take(),
+      #[cfg(feature = "extended-ops")]
+      lseek: config.lseek.take(),
+      abort: config.abort.take(),
+      truncate: config.truncate.take(),
+      statfs: config.statfs.take(),
+      generation_store: config.generation_store.take(),
+      readdirplus: config.readdirplus.take(),
+      release_inode: config.release_inode.take(),
+      forget_many: config.forget_many.take(),
+      readlink: config.readlink.take(),
+      direct_read: config.direct_read.take(),
+      create: config.create.take(),
+      symlink: config.symlink.take(),
+      write: config.write.take(),
+      prepare_write: config.prepare_write.take(),
+      direct_write: config.direct_write.take(),
+      #[cfg(feature = "extended-ops")]
+      poll: config.poll.take(),
+      #[cfg(target_os = "macos")]
+      getxtimes: config.getxtimes.take(),
+      on_error: config.on_error.take().map(Arc::new),
+    };
+    Self::mount(env, mount_root, fs_name, cbs, config)
+  }
 
-    let (tx_unmount_signal, rx_unmount_signal) = channel::<()>();
+}
+
+/// Without writable feature, callbacks of operations, that change filesystem, are not taken, and filesystem is
+/// always mounted read-only.
+#[cfg(not(feature = "writable"))]
+#[napi]
+impl JsFSMounter {
+
+  #[napi(factory)]
+  #[allow(clippy::too_many_arguments)]
+  pub fn make_and_mount(
+    env: Env,
+    mount_root: String, fs_name: String,
+    init: InitOpCB,
+    destroy: DestroyOpCB,
+    lookup: LookupOpCB,
+    forget: ForgetOpCB,
+    getattr: GetAttrOpCB,
+    setattr: SetAttrOpCB,
+    open: OpenOpCB,
+    read: ReadOpCB,
+    release: ReleaseOpCB,
+    opendir: OpenDirOpCB,
+    readdir: ReadDirOpCB,
+    releasedir: ReleaseDirOpCB,
+    fsyncdir: FSyncDirOpCB,
+    getxattr: GetXAttrOpCB,
+    listxattr: ListXAttrOpCB,
+    removexattr: RemoveXAttrOpCB,
+    access: AccessOpCB,
+    config: Option<MountConfig>,
+  ) -> Result<Self> {
+    let mut config = config.unwrap_or_default();
+    let cbs = CallbacksToJS {
+      init, destroy, lookup, forget, getattr, setattr,
+      open, read, release, opendir, readdir, releasedir, fsyncdir,
+      getxattr, listxattr, removexattr, access,
+      #[cfg(feature = "extended-ops")]
+      ioctl: config.ioctl.take(),
+      #[cfg(feature = "extended-ops")]
+      copy_file_range: config.copy_file_range.take(),
+      #[cfg(feature = "extended-ops")]
+      lseek: config.lseek.take(),
+      abort: config.abort.take(),
+      truncate: config.truncate.take(),
+      statfs: config.statfs.take(),
+      generation_store: config.generation_store.take(),
+      readdirplus: config.readdirplus.take(),
+      release_inode: config.release_inode.take(),
+      forget_many: config.forget_many.take(),
+      readlink: config.readlink.take(),
+      direct_read: config.direct_read.take(),
+      #[cfg(feature = "extended-ops")]
+      poll: config.poll.take(),
+      #[cfg(target_os = "macos")]
+      getxtimes: config.getxtimes.take(),
+      on_error: config.on_error.take().map(Arc::new),
+    };
+    Self::mount(env, mount_root, fs_name, cbs, config)
+  }
+
+}
+
+#[napi]
+impl JsFSMounter {
+
+  fn mount(env: Env, mount_root: String, fs_name: String, cbs: CallbacksToJS, config: MountConfig) -> Result<Self> {
+    if !is_valid_mtab_name(&fs_name) {
+      return Err(Error::from_reason("FSName must not contain spaces or slashes"));
+    }
+    if let Some(subtype) = &config.subtype && !is_valid_mtab_name(subtype) {
+      return Err(Error::from_reason("Subtype must not contain spaces or slashes"));
+    }
+    let custom_opts = config.custom_mount_options.clone().unwrap_or_default();
+    if let Some(opt) = custom_opts.iter().find(|opt| !is_valid_custom_mount_option(opt)) {
+      return Err(Error::from_reason(format!("Custom mount option {opt:?} is invalid or conflicts with managed ones")));
+    }
+    if !cfg!(feature = "writable") && (config.read_only == Some(false)) {
+      return Err(Error::from_reason("Module is built without writable feature, and mounts only read-only"));
+    }
+    #[cfg(feature = "writable")]
+    if (config.durability_tier == Some(mount_config::DurabilityTier::Flush)) && cbs.flush.is_none() {
+      return Err(Error::from_reason("Durability tier Flush needs flush callback"));
+    }
+    let settings = MountSettings::from(&config);
+    let read_only = settings.read_only;
+    let subtype = config.subtype;
+    let atime = config.atime;
+    let retries = config.mount_retries.unwrap_or(0);
+    let backoff = Duration::from_millis(config.mount_retry_backoff_ms.unwrap_or(100) as u64);
+    let on_error = cbs.on_error.clone();
+    if on_error.is_some() {
+      record_fuser_panics();
+    }
+
+    let fs_impl = CallbacksProxy::make(cbs, settings);
+    let fs_notifier = fs_impl.notifier();
+    let shared = SharedWithMounter {
+      poll_notifiers: fs_impl.poll_notifiers(),
+      open_handles: fs_impl.open_handles(),
+      dirty_inodes: fs_impl.dirty_inodes(),
+      env_exiting: fs_impl.env_exiting(),
+      caches: Some(fs_impl.caches()),
+      stats: Some(fs_impl.stats()),
+    };
+
+    let mut cfg = Config::default();
+    cfg.mount_options.extend([
+      if read_only { MountOption::RO } else { MountOption::RW },
+      MountOption::FSName(fs_name)
+    ]);
+    if let Some(subtype) = subtype {
+      cfg.mount_options.push(MountOption::Subtype(subtype));
+    }
+    if let Some(atime) = atime {
+      cfg.mount_options.push(atime.mount_option());
+    }
+    cfg.mount_options.extend(custom_opts.into_iter().map(MountOption::CUSTOM));
+    cfg.acl = SessionACL::Owner;
+    let device = config.fuse_device_path.map(PathBuf::from)
+    .or_else(|| device_mount::needs_own_mount(&cfg).then(|| PathBuf::from(device_mount::FUSE_DEVICE)));
+    let mounter = Self::start_session(env, fs_impl, shared, mount_root, device, cfg, retries, backoff, on_error)?;
+    let _ = fs_notifier.set(mounter.notifier.clone());
+    Ok(mounter)
+  }
+
+  /// Mounts read-only passthrough of a directory on disk, that is implemented in Rust without JS callbacks. It checks,
+  /// that FUSE mounting works, and is a reference of replies, expected from JS callbacks.
+  #[napi(factory)]
+  pub fn mount_passthrough(env: Env, mount_root: String, target_dir: String) -> Result<Self> {
+    let fs_impl = Passthrough::make(PathBuf::from(&target_dir))
+    .map_err(|err| Error::from_reason(format!("Can't pass through {target_dir}: {err}")))?;
+    let shared = SharedWithMounter {
+      poll_notifiers: Default::default(),
+      open_handles: fs_impl.open_handles(),
+      dirty_inodes: Default::default(),
+      env_exiting: Default::default(),
+      caches: None,
+      stats: None,
+    };
+    let mut cfg = Config::default();
+    cfg.mount_options.extend([MountOption::RO, MountOption::FSName("passthrough".to_string())]);
+    cfg.acl = SessionACL::Owner;
+    Self::start_session(env, fs_impl, shared, mount_root, None, cfg, 0, Duration::ZERO, None)
+  }
+
+  /// Mounts filesystem in its own thread, which then waits for unmount signal, or end of session.
+  #[allow(clippy::too_many_arguments)]
+  fn start_session<FS: Filesystem + Clone + Send + 'static>(
+    env: Env, fs_impl: FS, shared: SharedWithMounter, mount_root: String, device: Option<PathBuf>, cfg: Config,
+    retries: u32, backoff: Duration, on_error: Option<Arc<ErrorReportCB>>,
+  ) -> Result<Self> {
+    let SharedWithMounter { poll_notifiers, open_handles, dirty_inodes, env_exiting, caches, stats } = shared;
+    let (tx_unmount_signal, rx_unmount_signal) = channel::<bool>();
+    let (tx_mount_result, rx_mount_result) = channel::<io::Result<Notifier>>();
+    let (tx_session_end, rx_session_end) = channel::<()>();
 
     thread::spawn(move || {
-      let mut cfg = Config::default();
-      cfg.mount_options.extend([MountOption::RO, MountOption::FSName(fs_name)]);
-      cfg.acl = SessionACL::Owner;
-      let mounting = spawn_mount2(fs_impl, Path::new(&mount_root), &cfg);
+      // dropping of this sender, when thread ends, signals end of session
+      let _tx_session_end = tx_session_end;
+      let mount_root = Path::new(&mount_root);
+      let mounting = spawn_mount_with_retries(&fs_impl, mount_root, device.as_deref(), &cfg, retries, backoff);
       match mounting {
         Ok(mount_session) => {
-          rx_unmount_signal.recv().unwrap_or(());
-          let _ = mount_session.umount_and_join();
+          let _ = tx_mount_result.send(Ok(mount_session.notifier()));
+          loop {
+            match rx_unmount_signal.recv_timeout(SESSION_CHECK_PERIOD) {
+              Err(RecvTimeoutError::Timeout) if !mount_session.guard.is_finished() => {},
+              Err(RecvTimeoutError::Timeout) => {
+                let fuser_thread = mount_session.guard.thread().id();
+                ErrorReport::session_end(fuser_thread, mount_session.join()).send_to(on_error.as_deref());
+                break;
+              },
+              Ok(true) => {
+                let unmounting = if device.is_some() {
+                  device_mount::unmount(mount_root, true)
+                } else {
+                  lazy_unmount(mount_root)
+                }.and_then(|_| mount_session.join());
+                if let Err(err) = unmounting {
+                  ErrorReport::unmount(&err).send_to(on_error.as_deref());
+                }
+                break;
+              },
+              _ => {
+                let unmounting = if device.is_some() {
+                  // session, started on a given device, has no mount of its own
+                  device_mount::unmount(mount_root, false).and_then(|_| mount_session.join())
+                } else {
+                  mount_session.umount_and_join()
+                };
+                if let Err(err) = unmounting {
+                  ErrorReport::unmount(&err).send_to(on_error.as_deref());
+                }
+                break;
+              }
+            }
+          }
         },
-        _ => ()
+        Err(err) => {
+          ErrorReport::mount(&err).send_to(on_error.as_deref());
+          let _ = tx_mount_result.send(Err(err));
+        }
       }
     });
 
-    Ok(JsFSMounter { tx_unmount_signal })
+    match rx_mount_result.recv() {
+      Ok(Ok(notifier)) => {
+        let cleanup_id = ExitCleanup {
+          tx_unmount_signal: tx_unmount_signal.clone(), rx_session_end, env_exiting,
+        }.register();
+        let exit_cleanup = env.add_env_cleanup_hook(cleanup_id, ExitCleanup::run_registered)?;
+        Ok(JsFSMounter {
+          tx_unmount_signal, poll_notifiers, open_handles, dirty_inodes, notifier, caches, stats,
+          exit_cleanup: Some((cleanup_id, exit_cleanup))
+        })
+      },
+      Ok(Err(err)) => Err(Error::from_reason(format!("Failed to mount: {err}"))),
+      Err(_) => Err(Error::from_reason("Mounting thread ended unexpectedly")),
+    }
+  }
+
+  /// Wakes up kernel's poll of a given file handle, if kernel waits for it. Kernel then polls the file again.
+  #[napi]
+  pub fn notify_poll(&self, fh: i64) -> Result<()> {
+    let notifier = self.poll_notifiers.lock().unwrap().remove(&(fh as u64));
+    match notifier {
+      Some(notifier) => notifier.notify().map_err(|err| Error::from_reason(format!("Poll notify failed: {err}"))),
+      None => Ok(()),
+    }
+  }
+
+  /// Invalidates cached data of a directory and of all its descendants, known to kernel, in kernel and in this
+  /// module, for a coarse "everything under here has changed" signal, like after a sync with backend. Entries are
+  /// then looked up and read again from JS side.
+  ///
+  /// This should not be called, while JS side has unfinished reads, as kernel may wait for them, while invalidating.
+  #[napi]
+  pub fn invalidate_subtree(&self, ino: i64) -> Result<()> {
+    let Some(caches) = &self.caches else {
+      return Err(Error::from_reason("Filesystem has no JS side, which data may change"));
+    };
+    for ino in caches.forget_subtree(ino as u64) {
+      match self.notifier.inval_inode(INodeNo(ino), 0, 0) {
+        Ok(()) => {},
+        // kernel may have already dropped inode, or may not know invalidations
+        Err(err) if matches!(err.raw_os_error(), Some(libc::ENOENT) | Some(libc::ENOSYS)) => {},
+        Err(err) => return Err(Error::from_reason(format!("Invalidation of inode {ino} failed: {err}"))),
+      }
+    }
+    Ok(())
+  }
+
+  /// Returns inodes, written since their last successful flush or fsync, so that JS side may flush these ahead of
+  /// kernel, like on a timer, when writeback cache delays flushes. With `durabilityTier` other than `None`, writes are
+  /// flushed before being replied, and inodes don't stay dirty.
+  #[napi]
+  pub fn dirty_inodes(&self) -> Vec<i64> {
+    self.dirty_inodes.lock().unwrap().iter().map(|&ino| ino as i64).collect()
   }
 
+  /// Places data into kernel's page cache of a file, like prefetched data, so that reads of it are served by kernel
+  /// without calling JS side. Kernel drops cached data of a file, when it is opened without `keepCache` in
+  /// `ParamsOfOpened`, and file size grows to the end of stored data, when data goes past it. Stored data should be the
+  /// same, as JS side returns in reads, as kernel doesn't check it, and a later write or truncate, that JS side gets,
+  /// isn't ordered with a store. Data for an inode, that kernel doesn't know, is silently dropped.
+  ///
+  /// Retrieval of cached data back from kernel is not available, as fuser doesn't handle kernel's replies to it.
   #[napi]
-  pub fn unmount(&mut self) -> Result<()> {
-    let _ = self.tx_unmount_signal.send(());
+  pub fn store_data(&self, ino: i64, offset: i64, data: &[u8]) -> Result<()> {
+    self.notifier.store(INodeNo(ino as u64), offset as u64, data)
+    .map_err(|err| Error::from_reason(format!("Storing data of inode {ino} failed: {err}")))
+  }
+
+  /// Returns statistics of filesystem, given by statfs callback, with defaults filled in, as kernel sees them. This
+  /// lets monitoring check space usage without statvfs(2) on mount point.
+  #[napi(ts_return_type = "Promise<StatfsResult>")]
+  pub fn statfs<'env>(&self, env: &'env Env) -> Result<PromiseRaw<'env, StatfsResult>> {
+    let Some(stats) = self.stats.clone() else {
+      return Err(Error::from_reason("Filesystem has no statfs callback"));
+    };
+    env.spawn_future(async move { stats.statfs().await })
+  }
+
+  /// Unmounts filesystem. When process exits without unmount, filesystem is unmounted automatically.
+  /// When files or directories are still open, this fails with busy error, unless `force` is
+  /// true. Forced unmount is lazy: mount point is detached right away, and session ends, when last open file is
+  /// closed. Calls after successful unmount do nothing.
+  #[napi]
+  pub fn unmount(&mut self, env: Env, force: Option<bool>) -> Result<()> {
+    let Some(exit_cleanup) = self.exit_cleanup.take() else {
+      // already unmounted, and files, left open by forced unmount, don't make it busy
+      return Ok(());
+    };
+    let force = force.unwrap_or(false);
+    let open_handles = self.open_handles.load(Ordering::Relaxed);
+    if !force && (open_handles > 0) {
+      self.exit_cleanup = Some(exit_cleanup);
+      return Err(Error::from_reason(format!(
+        "Filesystem is busy: {open_handles} open file(s) or directory(ies)"
+      )));
+    }
+    let _ = self.tx_unmount_signal.send(force);
+    let (cleanup_id, exit_cleanup) = exit_cleanup;
+    ExitCleanup::take_registered(cleanup_id);
+    env.remove_env_cleanup_hook(exit_cleanup)?;
     Ok(())
   }
 
 }
+
+/// Parts of mounted filesystem, that are shared with its mounter.
+struct SharedWithMounter {
+  poll_notifiers: PollNotifiers,
+  open_handles: Arc<AtomicUsize>,
+  dirty_inodes: DirtyInodes,
+  env_exiting: Arc<AtomicBool>,
+  caches: Option<CachesOfMount>,
+  stats: Option<StatsOfMount>,
+}
+
+/// Unmounts filesystem synchronously, when process exits without explicit unmount.
+///
+/// Cleanups are kept in a process-wide registry, and are run either by cleanup hook of js environment, or at exit(3),
+/// as node's `process.exit()` exits without running environment's cleanup hooks, while a mounted filesystem keeps
+/// event loop alive, not letting process exit on its own.
+struct ExitCleanup {
+  tx_unmount_signal: Sender<bool>,
+  rx_session_end: Receiver<()>,
+  env_exiting: Arc<AtomicBool>,
+}
+
+/// Cleanups of mounted filesystems, keyed by ids.
+static EXIT_CLEANUPS: LazyLock<Mutex<HashMap<u64, ExitCleanup>>> = LazyLock::new(Default::default);
+
+static NEXT_CLEANUP_ID: AtomicU64 = AtomicU64::new(1);
+
+static AT_EXIT: Once = Once::new();
+
+impl ExitCleanup {
+
+  /// Adds this cleanup to registry, returning its id.
+  fn register(self) -> u64 {
+    AT_EXIT.call_once(|| unsafe {
+      libc::atexit(run_all_exit_cleanups);
+    });
+    let id = NEXT_CLEANUP_ID.fetch_add(1, Ordering::Relaxed);
+    EXIT_CLEANUPS.lock().unwrap().insert(id, self);
+    id
+  }
+
+  fn take_registered(id: u64) -> Option<Self> {
+    EXIT_CLEANUPS.lock().unwrap().remove(&id)
+  }
+
+  fn run_registered(id: u64) {
+    if let Some(cleanup) = Self::take_registered(id) {
+      cleanup.unmount();
+    }
+  }
+
+  fn unmount(self) {
+    self.env_exiting.store(true, Ordering::Relaxed);
+    if self.tx_unmount_signal.send(false).is_ok() {
+      let _ = self.rx_session_end.recv_timeout(EXIT_UNMOUNT_TIMEOUT);
+    }
+  }
+
+}
+
+extern "C" fn run_all_exit_cleanups() {
+  let cleanups = match EXIT_CLEANUPS.lock() {
+    Ok(mut cleanups) => cleanups.drain().map(|(_, cleanup)| cleanup).collect::<Vec<_>>(),
+    Err(_) => return,
+  };
+  for cleanup in cleanups {
+    cleanup.unmount();
+  }
+}
+
+/// Time, for which exiting process waits for unmount of a filesystem.
+const EXIT_UNMOUNT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Period of checking, if filesystem session has ended by itself, for example, due to panic.
+const SESSION_CHECK_PERIOD: Duration = Duration::from_millis(500);
+
+/// Mounts, retrying with doubling backoff after transient errors, like interruption of mounting by a signal.
+fn spawn_mount_with_retries<FS: Filesystem + Clone + Send + 'static>(
+  fs_impl: &FS, mount_root: &Path, device: Option<&Path>, cfg: &Config, retries: u32, backoff: Duration,
+) -> io::Result<BackgroundSession> {
+  retry_transient(retries, backoff, || match device {
+    Some(device) => device_mount::spawn_mount_with_device(fs_impl.clone(), device, mount_root, cfg),
+    None => spawn_mount2(fs_impl.clone(), mount_root, cfg),
+  })
+}
+
+/// Runs a given mount attempt, retrying it with doubling backoff after transient errors.
+fn retry_transient<T>(
+  retries: u32, backoff: Duration, mut mount_attempt: impl FnMut() -> io::Result<T>,
+) -> io::Result<T> {
+  let mut attempt = 0;
+  let mut backoff = backoff;
+  loop {
+    match mount_attempt() {
+      Err(err) if is_transient_mount_error(&err) && (attempt < retries) => {
+        thread::sleep(backoff);
+        backoff *= 2;
+        attempt += 1;
+      },
+      mounting => return mounting,
+    }
+  }
+}
+
+/// Detaches filesystem from mount point, leaving it to live, while there are open files. Detaching is done directly,
+/// when process may unmount, like root, and with unmount tools otherwise.
+fn lazy_unmount(mount_root: &Path) -> io::Result<()> {
+  if device_mount::unmount(mount_root, true).is_ok() {
+    return Ok(());
+  }
+  #[cfg(target_os = "linux")]
+  let attempts: [(&str, &[&str]); 2] = [("fusermount3", &["-u", "-z"]), ("fusermount", &["-u", "-z"])];
+  #[cfg(not(target_os = "linux"))]
+  let attempts: [(&str, &[&str]); 1] = [("umount", &["-f"])];
+  let mut last_err = io::Error::new(io::ErrorKind::NotFound, "No unmount tool found");
+  for (tool, args) in attempts {
+    match Command::new(tool).args(args).arg(mount_root).status() {
+      Ok(status) if status.success() => return Ok(()),
+      Ok(status) => last_err = io::Error::other(format!("{tool} exited with {status}")),
+      Err(err) if err.kind() == io::ErrorKind::NotFound => {},
+      Err(err) => last_err = err,
+    }
+  }
+  Err(last_err)
+}
+
+/// ERESTARTSYS is kernel internal, and shouldn't reach user space, but it may leak through helper tools.
+const ERESTARTSYS: i32 = 512;
+
+fn is_transient_mount_error(err: &io::Error) -> bool {
+  (err.kind() == io::ErrorKind::Interrupted)
+  || matches!(err.raw_os_error(), Some(libc::EINTR) | Some(libc::EAGAIN) | Some(ERESTARTSYS))
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::*;
+
+  #[test]
+  fn retries_transient_mount_errors() {
+    let mut attempts = 0;
+    let mounting = retry_transient(2, Duration::from_millis(1), || {
+      attempts += 1;
+      if attempts == 1 { Err(io::Error::from_raw_os_error(libc::EINTR)) } else { Ok(attempts) }
+    });
+    assert_eq!(mounting.unwrap(), 2);
+  }
+
+  #[test]
+  fn fails_after_retries_are_exhausted() {
+    let mut attempts = 0;
+    let mounting: io::Result<()> = retry_transient(2, Duration::from_millis(1), || {
+      attempts += 1;
+      Err(io::Error::from_raw_os_error(ERESTARTSYS))
+    });
+    assert_eq!(mounting.unwrap_err().raw_os_error(), Some(ERESTARTSYS));
+    assert_eq!(attempts, 3);
+  }
+
+  #[test]
+  fn does_not_retry_hard_errors() {
+    let mut attempts = 0;
+    let mounting: io::Result<()> = retry_transient(2, Duration::from_millis(1), || {
+      attempts += 1;
+      Err(io::Error::from_raw_os_error(libc::EPERM))
+    });
+    assert!(mounting.is_err());
+    assert_eq!(attempts, 1);
+  }
+
+}
@@ -0,0 +1,106 @@
+// Copyright(c) 2026 3NSoft Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+use std::{collections::HashMap, sync::Mutex};
+
+use fuser::INodeNo;
+
+/// Inode, known to kernel.
+#[derive(Default)]
+struct KnownInode {
+  count: u64,
+  /// Set, when attributes from js side say that inode has no extended attributes.
+  without_xattrs: bool,
+}
+
+/// Lookup counts of inodes, as kernel keeps them. Count goes up with every reply, that gives an inode to kernel
+/// (lookup, mknod, mkdir, create, entries of readdirplus), and goes down with forget. Other facts about inodes are
+/// kept here too, so that these are dropped, when kernel forgets inodes.
+#[derive(Default)]
+pub struct LookupCounts(Mutex<HashMap<u64, KnownInode>>);
+
+impl LookupCounts {
+
+  pub fn add(&self, ino: u64, has_xattrs: bool) {
+    let mut inodes = self.0.lock().unwrap();
+    let inode = inodes.entry(ino).or_default();
+    inode.count += 1;
+    inode.without_xattrs = !has_xattrs;
+  }
+
+  /// Drops given number of lookups, returning true, when inode is no longer known to kernel.
+  pub fn forget(&self, ino: u64, nlookup: u64) -> bool {
+    let mut inodes = self.0.lock().unwrap();
+    let Some(inode) = inodes.get_mut(&ino) else {
+      return false;
+    };
+    inode.count = inode.count.saturating_sub(nlookup);
+    if inode.count == 0 {
+      inodes.remove(&ino);
+      true
+    } else {
+      false
+    }
+  }
+
+  /// Notes presence of extended attributes, as given in fresh attributes of inode. Root, which kernel never looks
+  /// up, is noted without lookup count, and other inodes are noted only while kernel knows them.
+  pub fn note_xattrs_presence(&self, ino: u64, has_xattrs: bool) {
+    let mut inodes = self.0.lock().unwrap();
+    let inode = if ino == INodeNo::ROOT.0 {
+      Some(inodes.entry(ino).or_default())
+    } else {
+      inodes.get_mut(&ino)
+    };
+    if let Some(inode) = inode {
+      inode.without_xattrs = !has_xattrs;
+    }
+  }
+
+  pub fn is_without_xattrs(&self, ino: u64) -> bool {
+    self.0.lock().unwrap().get(&ino).is_some_and(|inode| inode.without_xattrs)
+  }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::*;
+
+  #[test]
+  fn releases_inode_when_all_lookups_are_forgotten() {
+    let lookups = LookupCounts::default();
+    lookups.add(5, true);
+    lookups.add(5, true);
+    lookups.add(5, true);
+    assert!(!lookups.forget(5, 2));
+    assert!(lookups.forget(5, 1));
+    assert!(!lookups.forget(5, 1));
+  }
+
+  #[test]
+  fn keeps_xattrs_presence_only_of_known_inodes() {
+    let lookups = LookupCounts::default();
+    lookups.note_xattrs_presence(7, false);
+    assert!(!lookups.is_without_xattrs(7));
+    lookups.add(7, false);
+    assert!(lookups.is_without_xattrs(7));
+    lookups.note_xattrs_presence(7, true);
+    assert!(!lookups.is_without_xattrs(7));
+    lookups.note_xattrs_presence(INodeNo::ROOT.0, false);
+    assert!(lookups.is_without_xattrs(INodeNo::ROOT.0));
+  }
+
+}
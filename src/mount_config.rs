@@ -0,0 +1,628 @@
+// Copyright(c) 2026 3NSoft Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::time::{Duration, SystemTime};
+
+use fuser::{InitFlags, MountOption};
+use napi_derive::napi;
+
+use crate::{error_reports::ErrorReportCB, js_callbacks::*};
+
+/// `napi(object)` doesn't carry `#[cfg]` of fields into its conversion from JS, hence, callbacks of optional features
+/// are added to [`MountConfig`] by these macros, only when respective feature is on. Each macro appends its fields to
+/// the given list, and passes it to the next macro, the last one being `declare_mount_config`.
+#[cfg(feature = "writable")]
+macro_rules! with_writable_callbacks {
+  ([$($fields:tt)*] $next:ident $(, $rest:ident)*) => {
+    $next!([
+      $($fields)*
+      /// Present only with writable feature, like all callbacks marked with it.
+      pub create: Option<CreateOpCB>,
+      /// writable
+      pub symlink: Option<SymlinkOpCB>,
+      /// writable
+      pub write: Option<WriteOpCB>,
+      /// writable
+      pub prepare_write: Option<PrepareWriteCB>,
+      /// writable
+      pub direct_write: Option<DirectWriteOpCB>,
+    ] $($rest),*);
+  };
+}
+
+#[cfg(not(feature = "writable"))]
+macro_rules! with_writable_callbacks {
+  ([$($fields:tt)*] $next:ident $(, $rest:ident)*) => {
+    $next!([$($fields)*] $($rest),*);
+  };
+}
+
+#[cfg(feature = "extended-ops")]
+macro_rules! with_extended_ops_callbacks {
+  ([$($fields:tt)*] $next:ident $(, $rest:ident)*) => {
+    $next!([
+      $($fields)*
+      /// Present only with extended-ops feature, like all callbacks marked with it.
+      pub ioctl: Option<IoctlOpCB>,
+      /// extended-ops
+      pub copy_file_range: Option<CopyFileRangeOpCB>,
+      /// extended-ops
+      pub lseek: Option<LseekOpCB>,
+      /// extended-ops
+      pub poll: Option<PollOpCB>,
+    ] $($rest),*);
+  };
+}
+
+#[cfg(not(feature = "extended-ops"))]
+macro_rules! with_extended_ops_callbacks {
+  ([$($fields:tt)*] $next:ident $(, $rest:ident)*) => {
+    $next!([$($fields)*] $($rest),*);
+  };
+}
+
+macro_rules! declare_mount_config {
+  ([$($feature_fields:tt)*]) => {
+    /// Optional part of mount setup: callbacks of operations, that not every filesystem needs, and settings.
+    ///
+    /// When callback of an operation is absent, operation is replied with `ENOSYS`, unless stated otherwise.
+    #[napi(object, object_to_js = false)]
+    #[derive(Default)]
+    pub struct MountConfig {
+      $($feature_fields)*
+      pub abort: Option<AbortOpCB>,
+      pub truncate: Option<TruncateOpCB>,
+      pub statfs: Option<StatfsOpCB>,
+      pub generation_store: Option<GenerationStoreCB>,
+      pub readdirplus: Option<ReadDirPlusOpCB>,
+      pub release_inode: Option<ReleaseInodeCB>,
+      pub forget_many: Option<ForgetManyOpCB>,
+      pub readlink: Option<ReadlinkOpCB>,
+      pub direct_read: Option<DirectReadOpCB>,
+      pub on_error: Option<ErrorReportCB>,
+      /// macOS only
+      #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+      pub getxtimes: Option<GetXTimesOpCB>,
+      /// BSD file flags (see chflags(2)), like `UF_IMMUTABLE` or `UF_APPEND`, that are reported for every inode, in
+      /// addition to flags from JS side. Attempts to clear these flags with setattr are rejected with `EPERM`.
+      /// This is useful for immutable/content-addressed stores.
+      pub file_flags: Option<u32>,
+      /// Pairs `(from_uid, to_uid)` to translate uids of inodes, coming from JS side, into uids reported to kernel, for
+      /// example, inside of user namespace. Uids in setattr are translated back, before being passed to JS.
+      /// Uids without a pair are passed unchanged.
+      pub uid_map: Option<Vec<(u32, u32)>>,
+      /// Pairs `(from_gid, to_gid)`, used for gids just like [`MountConfig::uid_map`] is used for uids.
+      pub gid_map: Option<Vec<(u32, u32)>>,
+      /// Pairs `(from_code, to_code)` to translate error codes, returned by JS side, before they reach applications, like
+      /// `ESTALE` into `ENOENT` for tools, that loop on `ESTALE`. Codes without a pair are passed unchanged, and errors,
+      /// made on Rust side, aren't translated.
+      pub errno_map: Option<Vec<(i32, i32)>>,
+      /// Capabilities to request from kernel in init. Denied ones are listed in an argument of init callback.
+      pub capabilities: Option<KernelCaps>,
+      /// Filesystem subtype, shown in mount table as `fuse.<subtype>`. Like filesystem name, it must not contain spaces
+      /// or slashes.
+      pub subtype: Option<String>,
+      /// Number of times mounting is retried after transient errors, like interruption by a signal. Default is 0.
+      pub mount_retries: Option<u32>,
+      /// Pause before the first retry of mounting, doubled on each subsequent one. Default is 100 milliseconds.
+      pub mount_retry_backoff_ms: Option<u32>,
+      /// Number of times idempotent operations (lookup, getattr, read, readdir) are retried, when JS side rejects them
+      /// with an error, which message starts with "retryable", e.g. after a transient failure of a network store. Other
+      /// rejections and all other operations are never retried. Default is 0.
+      pub js_retries: Option<u32>,
+      /// Pause before the first retry of an operation, doubled on each subsequent one. Default is 50 milliseconds.
+      pub js_retry_backoff_ms: Option<u32>,
+      /// Mounts filesystem read-only. Default is false, when module is built with writable feature, and without it
+      /// filesystem is always read-only. When read-only, flush is replied without calling JS side.
+      pub read_only: Option<bool>,
+      /// Maximum length of a file name in bytes, reported in statfs. Longer names are rejected with `ENAMETOOLONG`
+      /// without calling JS side. Default is 255.
+      pub max_name_length: Option<u32>,
+      /// Fragment size, reported in statfs, when statfs callback doesn't give one. Default is block size.
+      pub frsize: Option<u32>,
+      /// Checks in Rust, with getattr of parent, that caller may write into parent directory, before calling mkdir of JS
+      /// side, replying `EACCES` when it can't. This is useful when kernel doesn't check permissions itself, i.e. without
+      /// `default_permissions` mount option. Default is false.
+      pub check_parent_perms: Option<bool>,
+      /// Permission bits, reported for non-directory inodes, which JS side gives with `perm` set to `PERM_OF_MOUNT`.
+      /// Default is 0o644.
+      pub file_mode: Option<u32>,
+      /// Permission bits, reported for directories, which JS side gives with `perm` set to `PERM_OF_MOUNT`. Default is
+      /// 0o755.
+      pub dir_mode: Option<u32>,
+      /// Permission bits to clear in all non-directory inodes, like `fmask` of vfat.
+      pub fmask: Option<u32>,
+      /// Permission bits to clear in all directories, like `dmask` of vfat.
+      pub dmask: Option<u32>,
+      /// Window in milliseconds, during which repeated getattr requests for the same inode and fh are replied with
+      /// attributes from the first one, without calling JS side. It should be much shorter than attributes' TTL.
+      /// Attributes' changes with setattr and writes reset the window, and attributes, given by JS side with results of
+      /// reads and writes, start a new one. Default is no coalescing.
+      pub getattr_coalesce_ms: Option<u32>,
+      /// Time in milliseconds, for which kernel caches entries of lookup, readdirplus and of new files, without looking
+      /// them up again. Attributes, given with these entries, are cached for the same time. New entries may set their own
+      /// time. Default is 1 second.
+      pub entry_timeout_ms: Option<u32>,
+      /// Time in milliseconds, for which kernel caches attributes of getattr and setattr. Default is 1 second.
+      pub attr_timeout_ms: Option<u32>,
+      /// Time in milliseconds, for which kernel remembers, that a name doesn't exist, after lookup has failed with
+      /// `ENOENT`, not calling lookup for it again. Default is no such caching.
+      pub negative_timeout_ms: Option<u32>,
+      /// Number of pre-allocated 1MiB buffers, given to JS side in init, into which data of writes is copied, instead of
+      /// allocating a new buffer for every write. Writes get a new buffer, when all slots are in use. Slot of a write,
+      /// that timed out, is not reused. Pool needs runtime with external buffers, which, for example, Electron lacks.
+      /// Default is no pool.
+      pub write_buffer_pool_slots: Option<u32>,
+      /// Number of pre-allocated 1MiB buffers, given to JS side in init, into which JS side may place data of reads,
+      /// instead of allocating a new buffer for every read. Reads get no slot, when all slots are in use, or when read is
+      /// bigger than a slot. Slot of a read, that timed out, is not reused. Like write pool, this needs runtime with
+      /// external buffers. Default is no pool.
+      pub read_buffer_pool_slots: Option<u32>,
+      /// Raw mount options, passed to mount as they are, for example, `"max_read=131072"`. Options, that are set from
+      /// other fields, like `ro`, `rw`, `fsname`, `subtype`, `atime` and `noatime`, and permission options `allow_other` and
+      /// `allow_root` are rejected, as are options with commas.
+      pub custom_mount_options: Option<Vec<String>>,
+      /// Debug check, that JS side doesn't change kind of an inode, while kernel knows it, for example, returning a file
+      /// in lookup for an inode, that was a directory. Lookup and getattr with a changed kind are replied with `ESTALE`.
+      /// Default is false.
+      pub check_kind_changes: Option<bool>,
+      /// Keeps listings, read by readdir, while directory is open, so that repeated reading of an open directory, like
+      /// after rewinddir(3), doesn't call JS side again. Listings are dropped, when entries of directory are changed
+      /// through this filesystem, but changes, made by JS side on its own, aren't seen in cached listings. Default is
+      /// false.
+      pub cache_dir_listings: Option<bool>,
+      /// After a successful setattr, that changes size, asks kernel to drop cached pages past the new size, as
+      /// `fadvise(POSIX_FADV_DONTNEED)` would, so that no stale data is read after truncation, for example, with writeback
+      /// cache. Default is false.
+      pub evict_cache_on_truncate: Option<bool>,
+      /// Makes writes to files, opened with `O_APPEND`, go to the end of file, as JS side reports it in getattr, whatever
+      /// offset kernel has given. Kernel places appends at the end of file, as it knows it, which may be stale, when file
+      /// is changed on JS side, or with direct I/O. Not needed with writeback cache, where kernel owns file size. Each
+      /// such write costs an additional getattr, unless coalesced attributes are at hand. Default is false, and JS side
+      /// may act on `WriteArgs.append` itself.
+      pub enforce_append: Option<bool>,
+      /// Filesystem has no extended attributes. All xattr operations are replied with `ENOSYS` without calling JS side,
+      /// after which kernel stops sending them, failing them with `EOPNOTSUPP`. This speeds up tools like `cp -a`, `tar`
+      /// and `ls`, that probe xattrs of every file. Default is false.
+      pub no_xattr: Option<bool>,
+      /// Earliest time, in milliseconds since epoch, that may be set as atime or mtime in setattr. Earlier times are
+      /// rejected with `EINVAL` without calling JS side. Default is no restriction.
+      pub min_timestamp_ms: Option<i64>,
+      /// Latest time, in milliseconds since epoch, that may be set as atime or mtime in setattr. Later times are
+      /// rejected with `EINVAL` without calling JS side. Default is no restriction.
+      pub max_timestamp_ms: Option<i64>,
+      /// Reports mtime as creation time of inodes, which JS side gives with `btime` 0, like ones from object stores,
+      /// that have no creation time, so that tools, like Finder, don't show epoch. Kernel gets creation time only on
+      /// macOS. Default is false.
+      pub btime_from_mtime: Option<bool>,
+      /// Creation time, in milliseconds since epoch, reported for inodes, which JS side gives with `btime` 0. This takes
+      /// precedence over `btimeFromMtime`. Default is none.
+      pub default_btime_ms: Option<i64>,
+      /// Makes every write durable before it is replied, by calling flush, and, with `Fsync`, also fsync (with datasync
+      /// flag) of JS side, after write callback. Errors of these are returned by write. `Flush` needs flush callback,
+      /// and factory fails without it. Default is `None`, when data is persisted only by explicit flush and fsync.
+      pub durability_tier: Option<DurabilityTier>,
+      /// Way, in which kernel updates atime of files on reads, sending it to JS side in setattr. With `Never` (`noatime`), reads
+      /// don't update atime at all, which suits read-only and read-mostly mounts, and with `Strict` (`strictatime`),
+      /// every read updates it. Default is mount's default, which usually is `Relative` (`relatime`).
+      pub atime: Option<AtimeMode>,
+      /// Path of FUSE device node, for example, in a container, where device is given at a custom path. With it, given
+      /// device is opened, and filesystem is mounted directly with mount(2), which needs `CAP_SYS_ADMIN`, as `fusermount`
+      /// can't be used with a custom device. Factory fails, when device can't be opened, or mounting fails. Linux only.
+      /// Default is `/dev/fuse`, opened by `fuser`.
+      pub fuse_device_path: Option<String>,
+      /// Maximum number of operations, dispatched to JS side at the same time. Operation, coming above the limit, waits
+      /// for up to 30 seconds, and is then replied with `EAGAIN`. Forget, destroy and other calls without reply aren't
+      /// limited. Default is no limit.
+      pub max_in_flight: Option<u32>,
+      /// Time in milliseconds, after which an operation, that JS side hasn't yet replied to, is reported to `onError`
+      /// with kind `"slow-op"`, giving early warning before the operation times out (see `jsReplyTimeoutMs`). Default
+      /// is no warnings.
+      pub slow_op_warning_ms: Option<u32>,
+      /// Time in milliseconds, for which an operation waits for reply of JS side, before it is replied with `EIO`.
+      /// Reads, writes and listings, that time out, are then passed to `abort`. Default is 30 seconds.
+      pub js_reply_timeout_ms: Option<u32>,
+      /// Readahead in bytes, set in init. It can't be bigger than kernel's, which is given to init callback in
+      /// `KernelLimits`. Default is kernel's.
+      pub max_readahead: Option<u32>,
+    }
+  };
+}
+
+with_extended_ops_callbacks!([] with_writable_callbacks, declare_mount_config);
+
+#[napi]
+#[derive(Clone, Copy, PartialEq)]
+pub enum DurabilityTier {
+  None,
+  Flush,
+  Fsync
+}
+
+/// Kernel's atime update modes, set with respective mount options.
+#[napi]
+#[derive(Clone, Copy, PartialEq)]
+pub enum AtimeMode {
+  /// `noatime`: atime is never updated on reads.
+  Never,
+  /// `relatime`: atime is updated on read, only when it is earlier than mtime or ctime, or is older than a day.
+  Relative,
+  /// `strictatime`: atime is updated on every read.
+  Strict
+}
+
+impl AtimeMode {
+
+  pub fn mount_option(&self) -> MountOption {
+    match self {
+      AtimeMode::Never => MountOption::NoAtime,
+      AtimeMode::Relative => MountOption::CUSTOM("relatime".to_string()),
+      AtimeMode::Strict => MountOption::CUSTOM("strictatime".to_string()),
+    }
+  }
+
+}
+
+/// Optional kernel capabilities, that can be requested at init.
+/// See [libfuse docs](https://libfuse.github.io/doxygen/fuse__common_8h.html) for respective `FUSE_CAP_*`.
+#[napi(object)]
+#[derive(Default, Clone)]
+pub struct KernelCaps {
+  /// Kernel caches writes, sending them later in bigger chunks. Cached writes are sent before fsync and flush.
+  pub writeback_cache: Option<bool>,
+  /// Truncation on open is done by open itself, and is not sent as a separate setattr. Open callback gets a flag,
+  /// telling when to truncate.
+  pub atomic_o_trunc: Option<bool>,
+  /// Filesystem supports lookups of "." and "..", needed for NFS export.
+  /// Such filesystem should also give generation numbers, see `MountConfig.generationStore`.
+  pub export_support: Option<bool>,
+  /// Lookups and readdirs in the same directory may run in parallel.
+  pub parallel_dirops: Option<bool>,
+  /// Kernel caches symlink targets.
+  pub cache_symlinks: Option<bool>,
+  /// Kernel doesn't invalidate cached pages on attribute changes, waiting for explicit invalidation.
+  pub explicit_inval_data: Option<bool>,
+  /// Filesystem keeps no per-open state. When granted, open is replied with `ENOSYS` without calling JS side, after
+  /// which kernel stops sending open and release, and reads come with fh 0.
+  pub no_open_support: Option<bool>,
+  /// Filesystem keeps no per-opendir state. When granted, opendir is replied with `ENOSYS` without calling JS side,
+  /// after which kernel stops sending opendir and releasedir, and readdirs come with fh 0.
+  pub no_opendir_support: Option<bool>,
+}
+
+impl KernelCaps {
+
+  /// Returns requested capabilities with their names, as named in this struct on JS side.
+  pub fn requested(&self) -> Vec<(&'static str, InitFlags)> {
+    [
+      (self.writeback_cache, "writebackCache", InitFlags::FUSE_WRITEBACK_CACHE),
+      (self.atomic_o_trunc, "atomicOTrunc", InitFlags::FUSE_ATOMIC_O_TRUNC),
+      (self.export_support, "exportSupport", InitFlags::FUSE_EXPORT_SUPPORT),
+      (self.parallel_dirops, "parallelDirops", InitFlags::FUSE_PARALLEL_DIROPS),
+      (self.cache_symlinks, "cacheSymlinks", InitFlags::FUSE_CACHE_SYMLINKS),
+      (self.explicit_inval_data, "explicitInvalData", InitFlags::FUSE_EXPLICIT_INVAL_DATA),
+      (self.no_open_support, "noOpenSupport", InitFlags::FUSE_NO_OPEN_SUPPORT),
+      (self.no_opendir_support, "noOpendirSupport", InitFlags::FUSE_NO_OPENDIR_SUPPORT),
+    ]
+    .into_iter()
+    .filter(|(on, _, _)| on.unwrap_or(false))
+    .map(|(_, name, flag)| (name, flag))
+    .collect()
+  }
+
+  /// Requests capabilities with a given `add`, which tells, if kernel has granted a capability. Returns granted
+  /// capabilities, and names of denied ones.
+  pub fn negotiate(&self, mut add: impl FnMut(InitFlags) -> bool) -> (InitFlags, Vec<String>) {
+    let mut granted = InitFlags::empty();
+    let mut denied = Vec::new();
+    for (name, cap) in self.requested() {
+      if add(cap) {
+        granted |= cap;
+      } else {
+        denied.push(name.to_string());
+      }
+    }
+    (granted, denied)
+  }
+
+}
+
+/// Settings from [`MountConfig`], used in Rust side of filesystem.
+pub struct MountSettings {
+  pub read_only: bool,
+  pub max_name_length: u32,
+  pub frsize: Option<u32>,
+  #[cfg(feature = "writable")]
+  pub check_parent_perms: bool,
+  pub file_perms: PermsRule,
+  pub dir_perms: PermsRule,
+  pub getattr_coalescing: Option<Duration>,
+  pub entry_ttl: Duration,
+  pub attr_ttl: Duration,
+  pub negative_ttl: Option<Duration>,
+  pub write_pool_slots: u32,
+  pub read_pool_slots: u32,
+  pub check_kind_changes: bool,
+  pub cache_dir_listings: bool,
+  pub evict_cache_on_truncate: bool,
+  #[cfg(feature = "writable")]
+  pub enforce_append: bool,
+  pub no_xattr: bool,
+  pub timestamps: TimestampsRange,
+  pub missing_btime: MissingBtime,
+  #[cfg(feature = "writable")]
+  pub durability: DurabilityTier,
+  pub max_in_flight: Option<u32>,
+  pub slow_op_warning: Option<Duration>,
+  pub js_reply_timeout: Duration,
+  pub js_retries: u32,
+  pub js_retry_backoff: Duration,
+  pub max_readahead: Option<u32>,
+  pub file_flags: u32,
+  pub uid_map: IdMap,
+  pub gid_map: IdMap,
+  pub errno_map: ErrnoMap,
+  pub caps: KernelCaps,
+}
+
+impl From<&MountConfig> for MountSettings {
+  fn from(cfg: &MountConfig) -> Self {
+    MountSettings {
+      read_only: cfg.read_only.unwrap_or(!cfg!(feature = "writable")),
+      max_name_length: cfg.max_name_length.unwrap_or(255),
+      frsize: cfg.frsize,
+      #[cfg(feature = "writable")]
+      check_parent_perms: cfg.check_parent_perms.unwrap_or(false),
+      file_perms: PermsRule { mode: cfg.file_mode.unwrap_or(0o644), mask: cfg.fmask.unwrap_or(0) },
+      dir_perms: PermsRule { mode: cfg.dir_mode.unwrap_or(0o755), mask: cfg.dmask.unwrap_or(0) },
+      getattr_coalescing: cfg.getattr_coalesce_ms.map(|ms| Duration::from_millis(ms as u64)),
+      entry_ttl: cfg.entry_timeout_ms.map_or(DEFAULT_TTL, |ms| Duration::from_millis(ms as u64)),
+      attr_ttl: cfg.attr_timeout_ms.map_or(DEFAULT_TTL, |ms| Duration::from_millis(ms as u64)),
+      negative_ttl: cfg.negative_timeout_ms.filter(|ms| *ms > 0).map(|ms| Duration::from_millis(ms as u64)),
+      write_pool_slots: cfg.write_buffer_pool_slots.unwrap_or(0),
+      read_pool_slots: cfg.read_buffer_pool_slots.unwrap_or(0),
+      check_kind_changes: cfg.check_kind_changes.unwrap_or(false),
+      cache_dir_listings: cfg.cache_dir_listings.unwrap_or(false),
+      evict_cache_on_truncate: cfg.evict_cache_on_truncate.unwrap_or(false),
+      #[cfg(feature = "writable")]
+      enforce_append: cfg.enforce_append.unwrap_or(false),
+      no_xattr: cfg.no_xattr.unwrap_or(false),
+      timestamps: TimestampsRange { min: cfg.min_timestamp_ms, max: cfg.max_timestamp_ms },
+      missing_btime: match (cfg.default_btime_ms, cfg.btime_from_mtime) {
+        (Some(ms), _) => MissingBtime::Fixed(system_time_from(ms)),
+        (None, Some(true)) => MissingBtime::Mtime,
+        _ => MissingBtime::Zero,
+      },
+      #[cfg(feature = "writable")]
+      durability: cfg.durability_tier.unwrap_or(DurabilityTier::None),
+      max_in_flight: cfg.max_in_flight,
+      slow_op_warning: cfg.slow_op_warning_ms.map(|ms| Duration::from_millis(ms as u64)),
+      js_reply_timeout: cfg.js_reply_timeout_ms
+      .map_or(DEFAULT_JS_REPLY_TIMEOUT, |ms| Duration::from_millis(ms as u64)),
+      js_retries: cfg.js_retries.unwrap_or(0),
+      js_retry_backoff: Duration::from_millis(cfg.js_retry_backoff_ms.unwrap_or(50) as u64),
+      max_readahead: cfg.max_readahead,
+      file_flags: cfg.file_flags.unwrap_or(0),
+      uid_map: IdMap(cfg.uid_map.clone().unwrap_or_default()),
+      gid_map: IdMap(cfg.gid_map.clone().unwrap_or_default()),
+      errno_map: ErrnoMap(cfg.errno_map.clone().unwrap_or_default()),
+      caps: cfg.capabilities.clone().unwrap_or_default(),
+    }
+  }
+}
+
+/// Time of caching of entries and attributes by kernel, when it isn't set in config.
+const DEFAULT_TTL: Duration = Duration::from_secs(1);
+
+/// Time of waiting for reply of JS side, when it isn't set in config.
+const DEFAULT_JS_REPLY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Mount-wide rule for permission bits of either files or directories. Mode replaces only `PERM_OF_MOUNT`, while mask
+/// clears bits of every inode.
+pub struct PermsRule {
+  mode: u32,
+  mask: u32,
+}
+
+impl PermsRule {
+
+  pub fn apply(&self, perm: u16) -> u16 {
+    let perm = if perm == PERM_OF_MOUNT { (self.mode & 0o7777) as u16 } else { perm };
+    perm & !(self.mask as u16)
+  }
+
+}
+
+/// Range of times, that may be set to inodes.
+pub struct TimestampsRange {
+  min: Option<i64>,
+  max: Option<i64>,
+}
+
+impl TimestampsRange {
+
+  pub fn contains(&self, time: SystemTime) -> bool {
+    let millis = millis_from(time);
+    self.min.is_none_or(|min| min <= millis) && self.max.is_none_or(|max| millis <= max)
+  }
+
+}
+
+/// Creation time, reported for inodes, that JS side gives without one, i.e. with btime 0.
+pub enum MissingBtime {
+  Zero,
+  Mtime,
+  Fixed(SystemTime),
+}
+
+impl MissingBtime {
+
+  pub fn fill(&self, attr: &mut fuser::FileAttr) {
+    if attr.crtime != SystemTime::UNIX_EPOCH {
+      return;
+    }
+    match self {
+      MissingBtime::Zero => {},
+      MissingBtime::Mtime => attr.crtime = attr.mtime,
+      MissingBtime::Fixed(time) => attr.crtime = *time,
+    }
+  }
+
+}
+
+/// Translation of user/group ids between JS side (from) and kernel (to).
+pub struct IdMap(Vec<(u32, u32)>);
+
+impl IdMap {
+
+  pub fn kernel_id(&self, id: u32) -> u32 {
+    self.0.iter().find(|(from, _)| *from == id).map_or(id, |(_, to)| *to)
+  }
+
+  pub fn js_id(&self, id: u32) -> u32 {
+    self.0.iter().find(|(_, to)| *to == id).map_or(id, |(from, _)| *from)
+  }
+
+}
+
+/// Translation of error codes from JS side (from) into ones, given to kernel (to).
+pub struct ErrnoMap(Vec<(i32, i32)>);
+
+impl ErrnoMap {
+
+  pub fn remap(&self, code: i32) -> i32 {
+    self.0.iter().find(|(from, _)| *from == code).map_or(code, |(_, to)| *to)
+  }
+
+}
+
+/// Mount options, that are set from other fields of [`MountConfig`].
+const MANAGED_MOUNT_OPTIONS: [&str; 10] = [
+  "ro", "rw", "fsname", "subtype", "allow_other", "allow_root", "atime", "noatime", "relatime", "strictatime",
+];
+
+/// Checks that custom mount option is a single option, that doesn't conflict with options, set by this crate.
+pub fn is_valid_custom_mount_option(opt: &str) -> bool {
+  let name = opt.split('=').next().unwrap_or_default();
+  !opt.is_empty() && !opt.contains(|c: char| c.is_whitespace() || (c == ','))
+  && !MANAGED_MOUNT_OPTIONS.contains(&name)
+}
+
+/// Checks that name can be placed into mount table, which is space-delimited.
+pub fn is_valid_mtab_name(name: &str) -> bool {
+  !name.is_empty() && !name.contains(|c: char| c.is_whitespace() || (c == '/'))
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::*;
+
+  #[test]
+  fn reports_denied_capabilities() {
+    let caps = KernelCaps {
+      writeback_cache: Some(true), cache_symlinks: Some(true), parallel_dirops: Some(false),
+      ..Default::default()
+    };
+    let (granted, denied) = caps.negotiate(|cap| cap != InitFlags::FUSE_CACHE_SYMLINKS);
+    assert_eq!(granted, InitFlags::FUSE_WRITEBACK_CACHE);
+    assert_eq!(denied, vec!["cacheSymlinks".to_string()]);
+  }
+
+  #[test]
+  fn applies_mode_to_perm_of_mount_and_mask_to_all() {
+    let rule = PermsRule { mode: 0o640, mask: 0o027 };
+    assert_eq!(rule.apply(PERM_OF_MOUNT), 0o640);
+    assert_eq!(rule.apply(0o777), 0o750);
+    assert_eq!(PermsRule { mode: 0o100644, mask: 0 }.apply(PERM_OF_MOUNT), 0o644);
+  }
+
+  #[test]
+  fn accepts_only_single_unmanaged_custom_options() {
+    assert!(is_valid_custom_mount_option("max_read=131072"));
+    assert!(is_valid_custom_mount_option("nodiratime"));
+    assert!(!is_valid_custom_mount_option(""));
+    assert!(!is_valid_custom_mount_option("nodiratime,ro"));
+    assert!(!is_valid_custom_mount_option("max_read= 1"));
+    assert!(!is_valid_custom_mount_option("ro"));
+    assert!(!is_valid_custom_mount_option("fsname=other"));
+    assert!(!is_valid_custom_mount_option("relatime"));
+  }
+
+  #[test]
+  fn accepts_only_names_fitting_mount_table() {
+    assert!(is_valid_mtab_name("napi-fuser"));
+    assert!(is_valid_mtab_name("my.fs_1"));
+    assert!(!is_valid_mtab_name(""));
+    assert!(!is_valid_mtab_name("my fs"));
+    assert!(!is_valid_mtab_name("my\tfs"));
+    assert!(!is_valid_mtab_name("my/fs"));
+  }
+
+  #[test]
+  fn checks_times_against_configured_bounds() {
+    let range = TimestampsRange { min: Some(-1000), max: Some(5000) };
+    assert!(range.contains(system_time_from(-1000)));
+    assert!(range.contains(system_time_from(0)));
+    assert!(range.contains(system_time_from(5000)));
+    assert!(!range.contains(system_time_from(-1001)));
+    assert!(!range.contains(system_time_from(5001)));
+    let unbounded = TimestampsRange { min: None, max: None };
+    assert!(unbounded.contains(system_time_from(-86_400_000)));
+    assert!(unbounded.contains(system_time_from(i64::from(i32::MAX) * 1000)));
+  }
+
+  #[test]
+  fn translates_ids_both_ways() {
+    let id_map = IdMap(vec![(1000, 0), (1001, 2000)]);
+    assert_eq!(id_map.kernel_id(1000), 0);
+    assert_eq!(id_map.kernel_id(1001), 2000);
+    assert_eq!(id_map.kernel_id(1002), 1002);
+    assert_eq!(id_map.js_id(0), 1000);
+    assert_eq!(id_map.js_id(2000), 1001);
+    assert_eq!(id_map.js_id(1002), 1002);
+  }
+
+  #[test]
+  fn remaps_only_listed_error_codes() {
+    let errno_map = ErrnoMap(vec![(libc::ESTALE, libc::ENOENT), (libc::EIO, libc::EAGAIN)]);
+    assert_eq!(errno_map.remap(libc::ESTALE), libc::ENOENT);
+    assert_eq!(errno_map.remap(libc::EIO), libc::EAGAIN);
+    assert_eq!(errno_map.remap(libc::ENOENT), libc::ENOENT);
+    assert_eq!(ErrnoMap(vec![]).remap(libc::EIO), libc::EIO);
+  }
+
+  #[test]
+  fn fills_only_missing_btime() {
+    let attr_with_times = |mtime: i64, btime: i64| FileAttr {
+      ino: 2, size: 0, mtime, ctime: mtime, btime, kind: InodeKind::File, perm: 0o644, uid: 0, gid: 0, rdev: 0,
+      flags: 0, has_xattrs: None, blksize: None,
+    }.into_fuse();
+    let fixed = SystemTime::UNIX_EPOCH + Duration::from_secs(1000);
+    let mut attr = attr_with_times(5000, 0);
+    MissingBtime::Mtime.fill(&mut attr);
+    assert_eq!(attr.crtime, attr.mtime);
+    let mut attr = attr_with_times(5000, 0);
+    MissingBtime::Fixed(fixed).fill(&mut attr);
+    assert_eq!(attr.crtime, fixed);
+    let mut attr = attr_with_times(5000, 0);
+    MissingBtime::Zero.fill(&mut attr);
+    assert_eq!(attr.crtime, SystemTime::UNIX_EPOCH);
+    let mut attr = attr_with_times(5000, 3000);
+    MissingBtime::Mtime.fill(&mut attr);
+    assert_eq!(attr.crtime, SystemTime::UNIX_EPOCH + Duration::from_secs(3));
+  }
+
+}
@@ -0,0 +1,276 @@
+// Copyright(c) 2026 3NSoft Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{collections::HashMap, ffi::{CString, OsStr, OsString}, fs::{self, File, Metadata}, io, os::unix::{ffi::OsStrExt, fs::{DirEntryExt, FileExt, FileTypeExt, MetadataExt}}, path::{Path, PathBuf}, sync::{Arc, Mutex, atomic::{AtomicU64, AtomicUsize, Ordering}}, time::{Duration, SystemTime}};
+
+use fuser::{Errno, FileAttr, FileHandle, FileType, Filesystem, FopenFlags, Generation, INodeNo, LockOwner, OpenFlags, ReplyAttr, ReplyData, ReplyDirectory, ReplyEmpty, ReplyEntry, ReplyOpen, ReplyStatfs, Request};
+
+/// Entry of directory listing: inode, kind and name.
+type ListedEntry = (u64, FileType, OsString);
+
+/// Read-only filesystem, that passes operations to a directory on disk, using standard library instead of js side.
+/// It checks mounting end-to-end without js, and is a reference of replies, that js callbacks are expected to give.
+///
+/// Inode numbers are those of underlying filesystem, with target directory's number swapped with root's 1. Hence,
+/// target should not contain mount points of other filesystems, as numbers from those may collide.
+/// Paths of inodes are kept after forget, which is fine for a reference, but not for a long running mount.
+#[derive(Clone)]
+pub struct Passthrough {
+  /// Number of target directory in underlying filesystem.
+  target_ino: u64,
+  paths: Arc<Mutex<HashMap<u64, PathBuf>>>,
+  files: Arc<Mutex<HashMap<u64, Arc<File>>>>,
+  /// Listings of open directories, taken in opendir, in order of offsets.
+  dirs: Arc<Mutex<HashMap<u64, Vec<ListedEntry>>>>,
+  last_fh: Arc<AtomicU64>,
+  open_handles: Arc<AtomicUsize>,
+}
+
+impl Passthrough {
+
+  pub fn make(target_dir: PathBuf) -> io::Result<Passthrough> {
+    let meta = fs::metadata(&target_dir)?;
+    if !meta.is_dir() {
+      return Err(io::Error::new(io::ErrorKind::NotADirectory, "target is not a directory"));
+    }
+    Ok(Passthrough {
+      target_ino: meta.ino(),
+      paths: Arc::new(Mutex::new(HashMap::from([(INodeNo::ROOT.0, target_dir)]))),
+      files: Arc::new(Mutex::new(HashMap::new())),
+      dirs: Arc::new(Mutex::new(HashMap::new())),
+      last_fh: Arc::new(AtomicU64::new(0)),
+      open_handles: Arc::new(AtomicUsize::new(0)),
+    })
+  }
+
+  /// Returns counter of open file and directory handles, shared by all clones.
+  pub fn open_handles(&self) -> Arc<AtomicUsize> {
+    self.open_handles.clone()
+  }
+
+  fn path_of(&self, ino: INodeNo) -> Result<PathBuf, Errno> {
+    self.paths.lock().unwrap().get(&ino.0).cloned().ok_or(Errno::ENOENT)
+  }
+
+  /// Converts inode number of underlying filesystem into one, seen in this filesystem.
+  fn ino_of(&self, ino: u64) -> u64 {
+    if ino == self.target_ino {
+      INodeNo::ROOT.0
+    } else if ino == INodeNo::ROOT.0 {
+      self.target_ino
+    } else {
+      ino
+    }
+  }
+
+  fn attr_of(&self, meta: &Metadata) -> FileAttr {
+    FileAttr {
+      ino: INodeNo(self.ino_of(meta.ino())),
+      size: meta.size(),
+      blocks: meta.blocks(),
+      atime: time_of(meta.atime(), meta.atime_nsec()),
+      mtime: time_of(meta.mtime(), meta.mtime_nsec()),
+      ctime: time_of(meta.ctime(), meta.ctime_nsec()),
+      crtime: meta.created().unwrap_or(SystemTime::UNIX_EPOCH),
+      kind: kind_of(meta.file_type()),
+      perm: (meta.mode() & 0o7777) as u16,
+      nlink: meta.nlink() as u32,
+      uid: meta.uid(),
+      gid: meta.gid(),
+      rdev: meta.rdev() as u32,
+      blksize: meta.blksize() as u32,
+      flags: 0,
+    }
+  }
+
+  fn new_fh(&self) -> u64 {
+    self.open_handles.fetch_add(1, Ordering::Relaxed);
+    self.last_fh.fetch_add(1, Ordering::Relaxed) + 1
+  }
+
+  fn count_release(&self) {
+    let _ = self.open_handles.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1));
+  }
+
+  fn list_dir(&self, ino: INodeNo, path: &Path) -> io::Result<Vec<ListedEntry>> {
+    let parent_ino = match path.parent() {
+      Some(parent) if ino != INodeNo::ROOT => self.ino_of(fs::metadata(parent)?.ino()),
+      _ => INodeNo::ROOT.0,
+    };
+    let mut lst = vec![
+      (ino.0, FileType::Directory, OsString::from(".")),
+      (parent_ino, FileType::Directory, OsString::from("..")),
+    ];
+    for entry in fs::read_dir(path)? {
+      let entry = entry?;
+      lst.push((self.ino_of(entry.ino()), kind_of(entry.file_type()?), entry.file_name()));
+    }
+    Ok(lst)
+  }
+
+}
+
+fn time_of(secs: i64, nsecs: i64) -> SystemTime {
+  let offset = Duration::new(secs.unsigned_abs(), nsecs as u32);
+  if secs < 0 {
+    SystemTime::UNIX_EPOCH - offset
+  } else {
+    SystemTime::UNIX_EPOCH + offset
+  }
+}
+
+fn kind_of(file_type: fs::FileType) -> FileType {
+  if file_type.is_dir() {
+    FileType::Directory
+  } else if file_type.is_symlink() {
+    FileType::Symlink
+  } else if file_type.is_block_device() {
+    FileType::BlockDevice
+  } else if file_type.is_char_device() {
+    FileType::CharDevice
+  } else if file_type.is_fifo() {
+    FileType::NamedPipe
+  } else if file_type.is_socket() {
+    FileType::Socket
+  } else {
+    FileType::RegularFile
+  }
+}
+
+const TTL: Duration = Duration::from_secs(1);
+
+impl Filesystem for Passthrough {
+
+  fn lookup(&self, _req: &Request, parent: INodeNo, name: &OsStr, reply: ReplyEntry) {
+    let path = match self.path_of(parent) {
+      Ok(parent_path) => parent_path.join(name),
+      Err(err) => return reply.error(err),
+    };
+    match fs::symlink_metadata(&path) {
+      Ok(meta) => {
+        let attr = self.attr_of(&meta);
+        self.paths.lock().unwrap().insert(attr.ino.0, path);
+        reply.entry(&TTL, &attr, Generation(0));
+      },
+      Err(err) => reply.error(err.into()),
+    }
+  }
+
+  fn getattr(&self, _req: &Request, ino: INodeNo, _fh: Option<FileHandle>, reply: ReplyAttr) {
+    match self.path_of(ino).and_then(|path| fs::symlink_metadata(path).map_err(Errno::from)) {
+      Ok(meta) => reply.attr(&TTL, &self.attr_of(&meta)),
+      Err(err) => reply.error(err),
+    }
+  }
+
+  fn readlink(&self, _req: &Request, ino: INodeNo, reply: ReplyData) {
+    match self.path_of(ino).and_then(|path| fs::read_link(path).map_err(Errno::from)) {
+      Ok(target) => reply.data(target.as_os_str().as_bytes()),
+      Err(err) => reply.error(err),
+    }
+  }
+
+  fn open(&self, _req: &Request, ino: INodeNo, _flags: OpenFlags, reply: ReplyOpen) {
+    match self.path_of(ino).and_then(|path| File::open(path).map_err(Errno::from)) {
+      Ok(file) => {
+        let fh = self.new_fh();
+        self.files.lock().unwrap().insert(fh, Arc::new(file));
+        reply.opened(FileHandle(fh), FopenFlags::empty());
+      },
+      Err(err) => reply.error(err),
+    }
+  }
+
+  fn read(
+    &self, _req: &Request, _ino: INodeNo, fh: FileHandle, offset: u64, size: u32, _flags: OpenFlags,
+    _lock_owner: Option<LockOwner>, reply: ReplyData,
+  ) {
+    let Some(file) = self.files.lock().unwrap().get(&fh.0).cloned() else {
+      return reply.error(Errno::EBADF);
+    };
+    let mut buf = vec![0u8; size as usize];
+    let mut len = 0;
+    while len < buf.len() {
+      match file.read_at(&mut buf[len..], offset + len as u64) {
+        Ok(0) => break,
+        Ok(n) => len += n,
+        Err(err) if err.kind() == io::ErrorKind::Interrupted => {},
+        Err(err) => return reply.error(err.into()),
+      }
+    }
+    reply.data(&buf[..len]);
+  }
+
+  fn release(
+    &self, _req: &Request, _ino: INodeNo, fh: FileHandle, _flags: OpenFlags,
+    _lock_owner: Option<LockOwner>, _flush: bool, reply: ReplyEmpty,
+  ) {
+    if self.files.lock().unwrap().remove(&fh.0).is_some() {
+      self.count_release();
+    }
+    reply.ok();
+  }
+
+  fn opendir(&self, _req: &Request, ino: INodeNo, _flags: OpenFlags, reply: ReplyOpen) {
+    match self.path_of(ino).and_then(|path| self.list_dir(ino, &path).map_err(Errno::from)) {
+      Ok(lst) => {
+        let fh = self.new_fh();
+        self.dirs.lock().unwrap().insert(fh, lst);
+        reply.opened(FileHandle(fh), FopenFlags::empty());
+      },
+      Err(err) => reply.error(err),
+    }
+  }
+
+  fn readdir(&self, _req: &Request, _ino: INodeNo, fh: FileHandle, offset: u64, mut reply: ReplyDirectory) {
+    let dirs = self.dirs.lock().unwrap();
+    let Some(lst) = dirs.get(&fh.0) else {
+      return reply.error(Errno::EBADF);
+    };
+    for (i, (ino, kind, name)) in lst.iter().enumerate().skip(offset as usize) {
+      // offset of an entry is the one, from which reading continues after it
+      if reply.add(INodeNo(*ino), (i + 1) as u64, *kind, name) {
+        break;
+      }
+    }
+    reply.ok();
+  }
+
+  fn releasedir(&self, _req: &Request, _ino: INodeNo, fh: FileHandle, _flags: OpenFlags, reply: ReplyEmpty) {
+    if self.dirs.lock().unwrap().remove(&fh.0).is_some() {
+      self.count_release();
+    }
+    reply.ok();
+  }
+
+  fn statfs(&self, _req: &Request, _ino: INodeNo, reply: ReplyStatfs) {
+    let path = match self.path_of(INodeNo::ROOT) {
+      Ok(path) => path,
+      Err(err) => return reply.error(err),
+    };
+    let Ok(path) = CString::new(path.as_os_str().as_bytes()) else {
+      return reply.error(Errno::EINVAL);
+    };
+    let mut st: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut st) } != 0 {
+      return reply.error(io::Error::last_os_error().into());
+    }
+    reply.statfs(
+      st.f_blocks as u64, st.f_bfree as u64, st.f_bavail as u64, st.f_files as u64, st.f_ffree as u64,
+      st.f_bsize as u32, st.f_namemax as u32, st.f_frsize as u32
+    );
+  }
+
+}
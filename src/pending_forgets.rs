@@ -0,0 +1,54 @@
+// Copyright(c) 2026 3NSoft Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{mem, sync::{Arc, Mutex}};
+
+use napi::{bindgen_prelude::*, sys};
+
+/// Forgets, that wait to be passed to js side in one call.
+///
+/// Kernel sends forgets of many inodes in one batch, and fuser passes them one by one. Forgets are queued here, and
+/// only the first one into an empty queue makes a call to js side. Queue is drained on js thread, when that call
+/// converts its argument, hence, all forgets, that arrived in the meantime, go with it.
+#[derive(Clone, Default)]
+pub struct PendingForgets(Arc<Mutex<Vec<(i64, i64)>>>);
+
+impl PendingForgets {
+
+  /// Queues a forget, returning true, when queue was empty, and a call to js side should be made.
+  pub fn push(&self, ino: u64, nlookup: u64) -> bool {
+    let mut pending = self.0.lock().unwrap();
+    pending.push((ino as i64, nlookup as i64));
+    pending.len() == 1
+  }
+
+}
+
+impl TypeName for PendingForgets {
+  fn type_name() -> &'static str {
+    "Array<[number, number]>"
+  }
+
+  fn value_type() -> ValueType {
+    ValueType::Object
+  }
+}
+
+impl ToNapiValue for PendingForgets {
+  unsafe fn to_napi_value(raw_env: sys::napi_env, val: Self) -> Result<sys::napi_value> {
+    let forgets = mem::take(&mut *val.0.lock().unwrap());
+    unsafe { Vec::to_napi_value(raw_env, forgets) }
+  }
+}
@@ -0,0 +1,227 @@
+// Copyright(c) 2026 3NSoft Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use fuser::Errno;
+use napi_derive::napi;
+
+// Common errno constants of current platform, exported to JS as `Errno` namespace, for JS to write `Errno.ENOENT`
+// instead of magic numbers, some of which differ between Linux and macOS.
+
+#[napi(namespace = "Errno")]
+pub const EPERM: i32 = libc::EPERM;
+#[napi(namespace = "Errno")]
+pub const ENOENT: i32 = libc::ENOENT;
+#[napi(namespace = "Errno")]
+pub const ESRCH: i32 = libc::ESRCH;
+#[napi(namespace = "Errno")]
+pub const EINTR: i32 = libc::EINTR;
+#[napi(namespace = "Errno")]
+pub const EIO: i32 = libc::EIO;
+#[napi(namespace = "Errno")]
+pub const ENXIO: i32 = libc::ENXIO;
+#[napi(namespace = "Errno")]
+pub const E2BIG: i32 = libc::E2BIG;
+#[napi(namespace = "Errno")]
+pub const EBADF: i32 = libc::EBADF;
+#[napi(namespace = "Errno")]
+pub const EAGAIN: i32 = libc::EAGAIN;
+#[napi(namespace = "Errno")]
+pub const ENOMEM: i32 = libc::ENOMEM;
+#[napi(namespace = "Errno")]
+pub const EACCES: i32 = libc::EACCES;
+#[napi(namespace = "Errno")]
+pub const EFAULT: i32 = libc::EFAULT;
+#[napi(namespace = "Errno")]
+pub const EBUSY: i32 = libc::EBUSY;
+#[napi(namespace = "Errno")]
+pub const EEXIST: i32 = libc::EEXIST;
+#[napi(namespace = "Errno")]
+pub const EXDEV: i32 = libc::EXDEV;
+#[napi(namespace = "Errno")]
+pub const ENODEV: i32 = libc::ENODEV;
+#[napi(namespace = "Errno")]
+pub const ENOTDIR: i32 = libc::ENOTDIR;
+#[napi(namespace = "Errno")]
+pub const EISDIR: i32 = libc::EISDIR;
+#[napi(namespace = "Errno")]
+pub const EINVAL: i32 = libc::EINVAL;
+#[napi(namespace = "Errno")]
+pub const ENFILE: i32 = libc::ENFILE;
+#[napi(namespace = "Errno")]
+pub const EMFILE: i32 = libc::EMFILE;
+#[napi(namespace = "Errno")]
+pub const ETXTBSY: i32 = libc::ETXTBSY;
+#[napi(namespace = "Errno")]
+pub const EFBIG: i32 = libc::EFBIG;
+#[napi(namespace = "Errno")]
+pub const ENOSPC: i32 = libc::ENOSPC;
+#[napi(namespace = "Errno")]
+pub const ESPIPE: i32 = libc::ESPIPE;
+#[napi(namespace = "Errno")]
+pub const EROFS: i32 = libc::EROFS;
+#[napi(namespace = "Errno")]
+pub const EMLINK: i32 = libc::EMLINK;
+#[napi(namespace = "Errno")]
+pub const ERANGE: i32 = libc::ERANGE;
+#[napi(namespace = "Errno")]
+pub const ENAMETOOLONG: i32 = libc::ENAMETOOLONG;
+#[napi(namespace = "Errno")]
+pub const ENOSYS: i32 = libc::ENOSYS;
+#[napi(namespace = "Errno")]
+pub const ENOTEMPTY: i32 = libc::ENOTEMPTY;
+#[napi(namespace = "Errno")]
+pub const ELOOP: i32 = libc::ELOOP;
+#[napi(namespace = "Errno")]
+pub const ENODATA: i32 = libc::ENODATA;
+#[napi(namespace = "Errno")]
+pub const ENOTSUP: i32 = libc::ENOTSUP;
+#[napi(namespace = "Errno")]
+pub const EOPNOTSUPP: i32 = libc::EOPNOTSUPP;
+#[napi(namespace = "Errno")]
+pub const ETIMEDOUT: i32 = libc::ETIMEDOUT;
+#[napi(namespace = "Errno")]
+pub const ESTALE: i32 = libc::ESTALE;
+#[napi(namespace = "Errno")]
+pub const EDQUOT: i32 = libc::EDQUOT;
+
+/// Error for a missing extended attribute, see [`PosixError::enoattr`].
+#[napi(namespace = "Errno")]
+pub const ENOATTR: i32 = enoattr_code();
+
+const fn enoattr_code() -> i32 {
+  #[cfg(target_os = "macos")]
+  { libc::ENOATTR }
+  #[cfg(not(target_os = "macos"))]
+  { libc::ENODATA }
+}
+
+/// Symbolic names of errno codes. When platform has aliases with the same code, the first name is used.
+const ERRNO_NAMES: [(i32, &str); 38] = [
+  (libc::EPERM, "EPERM"),
+  (libc::ENOENT, "ENOENT"),
+  (libc::ESRCH, "ESRCH"),
+  (libc::EINTR, "EINTR"),
+  (libc::EIO, "EIO"),
+  (libc::ENXIO, "ENXIO"),
+  (libc::E2BIG, "E2BIG"),
+  (libc::EBADF, "EBADF"),
+  (libc::EAGAIN, "EAGAIN"),
+  (libc::ENOMEM, "ENOMEM"),
+  (libc::EACCES, "EACCES"),
+  (libc::EFAULT, "EFAULT"),
+  (libc::EBUSY, "EBUSY"),
+  (libc::EEXIST, "EEXIST"),
+  (libc::EXDEV, "EXDEV"),
+  (libc::ENODEV, "ENODEV"),
+  (libc::ENOTDIR, "ENOTDIR"),
+  (libc::EISDIR, "EISDIR"),
+  (libc::EINVAL, "EINVAL"),
+  (libc::ENFILE, "ENFILE"),
+  (libc::EMFILE, "EMFILE"),
+  (libc::ETXTBSY, "ETXTBSY"),
+  (libc::EFBIG, "EFBIG"),
+  (libc::ENOSPC, "ENOSPC"),
+  (libc::ESPIPE, "ESPIPE"),
+  (libc::EROFS, "EROFS"),
+  (libc::EMLINK, "EMLINK"),
+  (libc::ERANGE, "ERANGE"),
+  (libc::ENAMETOOLONG, "ENAMETOOLONG"),
+  (libc::ENOSYS, "ENOSYS"),
+  (libc::ENOTEMPTY, "ENOTEMPTY"),
+  (libc::ELOOP, "ELOOP"),
+  (libc::ENODATA, "ENODATA"),
+  (libc::ENOTSUP, "ENOTSUP"),
+  (libc::EOPNOTSUPP, "EOPNOTSUPP"),
+  (libc::ETIMEDOUT, "ETIMEDOUT"),
+  (libc::ESTALE, "ESTALE"),
+  (libc::EDQUOT, "EDQUOT"),
+];
+
+/// Returns symbolic name of an errno code, like `"EINVAL"` for 22, for logging of errors in callbacks. Codes without
+/// a known name are described by platform's error message.
+#[napi]
+pub fn fuse_errno_to_string(code: i32) -> String {
+  match ERRNO_NAMES.iter().find(|(errno, _)| *errno == code) {
+    Some((_, name)) => name.to_string(),
+    None => std::io::Error::from_raw_os_error(code).to_string(),
+  }
+}
+
+/// `ENOATTR` of macOS, which is `EPROTONOSUPPORT` on Linux.
+#[cfg(not(target_os = "macos"))]
+const MACOS_ENOATTR: i32 = 93;
+/// `ENODATA` of Linux, which is `ECONNREFUSED` on macOS.
+#[cfg(target_os = "macos")]
+const LINUX_ENODATA: i32 = 61;
+
+/// Turns error code from JS side into error for kernel. Missing xattr error of the other platform is translated into
+/// one of current platform, as JS code, written for one platform, may hardcode it. Such codes aren't expected from a
+/// filesystem otherwise.
+pub fn into_fuse_error(code: i32) -> Errno {
+  #[cfg(not(target_os = "macos"))]
+  let code = if code == MACOS_ENOATTR { enoattr_code() } else { code };
+  #[cfg(target_os = "macos")]
+  let code = if code == LINUX_ENODATA { enoattr_code() } else { code };
+  Errno::from_i32(code)
+}
+
+/// POSIX error codes, values of which differ between platforms.
+/// JS side should take codes from here, instead of hardcoding numbers.
+#[napi]
+pub struct PosixError {}
+
+#[napi]
+impl PosixError {
+
+  /// `ENODATA` of current platform (61 on Linux, 96 on macOS).
+  #[napi]
+  pub fn enodata() -> i32 {
+    libc::ENODATA
+  }
+
+  /// Error for a missing extended attribute.
+  /// It is `ENOATTR` (93) on macOS, while on Linux `ENOATTR` is an alias of `ENODATA` (61).
+  #[napi]
+  pub fn enoattr() -> i32 {
+    enoattr_code()
+  }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+  use super::*;
+
+  #[test]
+  fn names_known_errno_codes() {
+    assert_eq!(fuse_errno_to_string(libc::EINVAL), "EINVAL");
+    assert_eq!(fuse_errno_to_string(libc::ENOENT), "ENOENT");
+    // alias with the same code gets the first name
+    assert_eq!(fuse_errno_to_string(libc::ENOTSUP), fuse_errno_to_string(libc::EOPNOTSUPP));
+    assert_eq!(fuse_errno_to_string(9999), std::io::Error::from_raw_os_error(9999).to_string());
+  }
+
+  #[test]
+  fn turns_missing_xattr_code_of_either_platform_into_own_one() {
+    assert_eq!(into_fuse_error(libc::EIO).code(), libc::EIO);
+    assert_eq!(into_fuse_error(enoattr_code()).code(), enoattr_code());
+    #[cfg(not(target_os = "macos"))]
+    assert_eq!(into_fuse_error(MACOS_ENOATTR).code(), libc::ENODATA);
+    #[cfg(target_os = "macos")]
+    assert_eq!(into_fuse_error(LINUX_ENODATA).code(), libc::ENOATTR);
+  }
+
+}
@@ -0,0 +1,99 @@
+// Copyright(c) 2026 3NSoft Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{collections::HashMap, sync::Mutex, time::{Duration, Instant}};
+
+use fuser::FileAttr;
+
+/// Attributes from recent getattr replies, keyed by (ino, fh), so that getattr requests, coming back-to-back for the
+/// same inode, share one js call.
+///
+/// Note that [`fuser`] dispatches requests one at a time, and getattr waits for js reply, hence, concurrent getattr
+/// requests for the same inode are already serialized, and with this cache, all but the first one are answered
+/// without js.
+pub struct RecentAttrs {
+  window: Duration,
+  attrs: Mutex<HashMap<InoAndFh, (Instant, FileAttr)>>,
+}
+
+type InoAndFh = (u64, Option<u64>);
+
+impl RecentAttrs {
+
+  pub fn new(window: Duration) -> Self {
+    RecentAttrs { window, attrs: Mutex::new(HashMap::new()) }
+  }
+
+  pub fn get(&self, ino: u64, fh: Option<u64>) -> Option<FileAttr> {
+    let attrs = self.attrs.lock().unwrap();
+    attrs.get(&(ino, fh))
+    .filter(|(received, _)| received.elapsed() < self.window)
+    .map(|(_, attr)| *attr)
+  }
+
+  pub fn put(&self, ino: u64, fh: Option<u64>, attr: FileAttr) {
+    let mut attrs = self.attrs.lock().unwrap();
+    attrs.retain(|_, (received, _)| received.elapsed() < self.window);
+    attrs.insert((ino, fh), (Instant::now(), attr));
+  }
+
+  /// Drops attributes of an inode, when they are changed.
+  pub fn forget(&self, ino: u64) {
+    self.attrs.lock().unwrap().retain(|(cached_ino, _), _| *cached_ino != ino);
+  }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+  use std::thread;
+
+  use crate::js_callbacks::{self, InodeKind};
+
+  use super::*;
+
+  fn attr_of(ino: i64, size: i64) -> FileAttr {
+    js_callbacks::FileAttr {
+      ino, size, mtime: 0, ctime: 0, btime: 0, kind: InodeKind::File, perm: 0o644, uid: 0, gid: 0, rdev: 0,
+      flags: 0, has_xattrs: None, blksize: None,
+    }.into_fuse()
+  }
+
+  #[test]
+  fn gives_attributes_within_window() {
+    let recent = RecentAttrs::new(Duration::from_millis(50));
+    recent.put(2, None, attr_of(2, 10));
+    recent.put(2, Some(7), attr_of(2, 20));
+    assert_eq!(recent.get(2, None).unwrap().size, 10);
+    assert_eq!(recent.get(2, Some(7)).unwrap().size, 20);
+    assert!(recent.get(3, None).is_none());
+    thread::sleep(Duration::from_millis(60));
+    assert!(recent.get(2, None).is_none());
+  }
+
+  #[test]
+  fn forgets_all_attributes_of_inode() {
+    let recent = RecentAttrs::new(Duration::from_secs(10));
+    recent.put(2, None, attr_of(2, 10));
+    recent.put(2, Some(7), attr_of(2, 20));
+    recent.put(3, None, attr_of(3, 30));
+    recent.forget(2);
+    assert!(recent.get(2, None).is_none());
+    assert!(recent.get(2, Some(7)).is_none());
+    assert!(recent.get(3, None).is_some());
+  }
+
+}
@@ -0,0 +1,189 @@
+// Copyright(c) 2026 3NSoft Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{ffi::{CString, OsString}, fs, io, os::unix::ffi::OsStringExt, path::{self, Path, PathBuf}, time::SystemTime};
+
+use napi::{Error, Result};
+use napi_derive::napi;
+
+/// Returns user id of this process, to be used as an owner of files.
+#[napi]
+pub fn current_uid() -> u32 {
+  unsafe { libc::getuid() }
+}
+
+/// Returns group id of this process, to be used as a group of files.
+#[napi]
+pub fn current_gid() -> u32 {
+  unsafe { libc::getgid() }
+}
+
+/// Returns umask of this process.
+/// There is no call to only read umask, hence, it is set and immediately restored.
+#[napi]
+pub fn process_umask() -> u32 {
+  unsafe {
+    let mask = libc::umask(0);
+    libc::umask(mask);
+    mask as u32
+  }
+}
+
+/// Returns current wall-clock time in milliseconds since epoch, as used for times in `FileAttr`. This is handy for
+/// setting times to now, for example, in setattr.
+#[napi]
+pub fn now_millis() -> i64 {
+  SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).map_or(0, |d| d.as_millis() as i64)
+}
+
+/// Creates a new directory with a unique name, made from given path prefix with six random characters, for example,
+/// `mkdtemp(join(tmpdir(), 'fs-'))`. Returns path of created directory, that can be used as a mount point.
+#[napi]
+pub fn mkdtemp(prefix: String) -> Result<String> {
+  let template = CString::new(format!("{prefix}XXXXXX"))
+  .map_err(|_| Error::from_reason("Prefix must not contain null bytes"))?;
+  let mut template = template.into_bytes_with_nul();
+  let created = unsafe { libc::mkdtemp(template.as_mut_ptr().cast()) };
+  if created.is_null() {
+    return Err(Error::from_reason(format!("mkdtemp failed: {}", io::Error::last_os_error())));
+  }
+  template.pop();
+  OsString::from_vec(template).into_string()
+  .map_err(|_| Error::from_reason("Created path is not valid UTF-8"))
+}
+
+/// Removes an empty mount point directory, refusing to do so, while something is still mounted on it.
+#[napi]
+pub fn cleanup_mount_point(path: String) -> Result<()> {
+  let dir = Path::new(&path);
+  if is_mounted(dir).map_err(|err| Error::from_reason(format!("Can't check mount point {path}: {err}")))? {
+    return Err(Error::from_reason(format!("{path} is still mounted")));
+  }
+  fs::remove_dir(dir).map_err(|err| Error::from_reason(format!("Can't remove {path}: {err}")))
+}
+
+/// Checks if path is a mount point, looking it up in the table of mounts. Path itself isn't touched, as a stat of a
+/// mount of this process would wait for js callbacks, which can't run, while this is called on js thread.
+#[cfg(target_os = "linux")]
+fn is_mounted(dir: &Path) -> io::Result<bool> {
+  let dir = mount_point_path(dir)?;
+  let mountinfo = fs::read("/proc/self/mountinfo")?;
+  Ok(mountinfo.split(|&b| b == b'\n').any(|line| {
+    // mount point is the fifth field
+    line.split(|&b| b == b' ').nth(4).is_some_and(|mount_point| unescape_mountinfo(mount_point) == dir)
+  }))
+}
+
+#[cfg(target_os = "macos")]
+fn is_mounted(dir: &Path) -> io::Result<bool> {
+  use std::{ffi::CStr, os::unix::ffi::OsStrExt};
+  let dir = mount_point_path(dir)?;
+  let mut mounts: *mut libc::statfs = std::ptr::null_mut();
+  // MNT_NOWAIT takes cached statistics, without asking filesystems
+  let count = unsafe { libc::getmntinfo(&mut mounts, libc::MNT_NOWAIT) };
+  if count <= 0 {
+    return Err(io::Error::last_os_error());
+  }
+  let mounts = unsafe { std::slice::from_raw_parts(mounts, count as usize) };
+  Ok(mounts.iter().any(|mount| {
+    let mount_point = unsafe { CStr::from_ptr(mount.f_mntonname.as_ptr()) };
+    Path::new(std::ffi::OsStr::from_bytes(mount_point.to_bytes())) == dir
+  }))
+}
+
+/// Returns absolute path, as it is written in the table of mounts, resolving symlinks in parent directory only, so
+/// that mount point itself isn't touched.
+fn mount_point_path(dir: &Path) -> io::Result<PathBuf> {
+  let dir = path::absolute(dir)?;
+  match (dir.parent(), dir.file_name()) {
+    (Some(parent), Some(name)) => Ok(parent.canonicalize()?.join(name)),
+    _ => Err(io::Error::new(io::ErrorKind::InvalidInput, "path has no directory name")),
+  }
+}
+
+/// Decodes path in mountinfo, where space, tab, newline and backslash are written as octal escapes, like `\040`.
+#[cfg(target_os = "linux")]
+fn unescape_mountinfo(escaped: &[u8]) -> PathBuf {
+  let mut bytes = Vec::with_capacity(escaped.len());
+  let mut i = 0;
+  while i < escaped.len() {
+    let code = escaped.get(i + 1..i + 4)
+    .filter(|_| escaped[i] == b'\\')
+    .and_then(|digits| std::str::from_utf8(digits).ok())
+    .and_then(|digits| u8::from_str_radix(digits, 8).ok());
+    match code {
+      Some(code) => {
+        bytes.push(code);
+        i += 4;
+      },
+      None => {
+        bytes.push(escaped[i]);
+        i += 1;
+      },
+    }
+  }
+  PathBuf::from(OsString::from_vec(bytes))
+}
+
+/// Returns block size, recommended to report in `blksize` of `FileAttr`, for a backing storage with a given typical
+/// size of its I/O, like 512 for HDD, 4096 for SSD, or 65536 for an object store. Applications use `blksize` as a
+/// preferred size of their reads and writes.
+///
+/// Storage's size is rounded up to a power of two, and is kept between page size of 4 KiB, below which kernel does
+/// read-modify-write of pages anyway, and 128 KiB, above which kernel splits requests with default `max_read` and
+/// `max_write`.
+#[napi]
+pub fn block_size_for_storage(storage_bytes_per_io: u32) -> u32 {
+  storage_bytes_per_io.clamp(MIN_BLOCK_SIZE, MAX_BLOCK_SIZE).next_power_of_two()
+}
+
+const MIN_BLOCK_SIZE: u32 = 4096;
+const MAX_BLOCK_SIZE: u32 = 128 * 1024;
+
+#[cfg(test)]
+mod tests {
+
+  use super::*;
+
+  #[cfg(target_os = "linux")]
+  #[test]
+  fn unescapes_mountinfo_paths() {
+    assert_eq!(unescape_mountinfo(br"/tmp/with\040space\011tab\134"), PathBuf::from("/tmp/with space\ttab\\"));
+    assert_eq!(unescape_mountinfo(br"/tmp/plain\04"), PathBuf::from(r"/tmp/plain\04"));
+  }
+
+  #[cfg(target_os = "linux")]
+  #[test]
+  fn finds_mount_points() {
+    assert!(is_mounted(Path::new("/proc")).unwrap());
+    let dir = std::env::temp_dir().join(format!("napi-fuser-test-{}", std::process::id()));
+    fs::create_dir(&dir).unwrap();
+    let mounted = is_mounted(&dir);
+    fs::remove_dir(&dir).unwrap();
+    assert!(!mounted.unwrap());
+  }
+
+  #[test]
+  fn rounds_block_size_to_power_of_two_within_bounds() {
+    assert_eq!(block_size_for_storage(0), 4096);
+    assert_eq!(block_size_for_storage(512), 4096);
+    assert_eq!(block_size_for_storage(4096), 4096);
+    assert_eq!(block_size_for_storage(5000), 8192);
+    assert_eq!(block_size_for_storage(64 * 1024), 64 * 1024);
+    assert_eq!(block_size_for_storage(100 * 1024), 128 * 1024);
+    assert_eq!(block_size_for_storage(u32::MAX), 128 * 1024);
+  }
+
+}